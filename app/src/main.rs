@@ -1,95 +1,111 @@
 use std::{collections::HashMap, env};
 
-use hawk_cli_io::error::error;
-pub use hawk_common::*;
+use hawk_cli_io::environment::Terminal;
+use hawk_cli_io::error::{error, report};
+use hawk_common::error::{ErrorKind, HawkError};
 pub use hawk_cli_io::*;
 
 // Used extremely often, so brought into scope
 use hawk_common::object::Object;
 
+mod session;
+mod testing;
 mod utils;
 
 
 fn main() {
     let args: Vec<String> = env::args().collect();
 
-    let filein_fn = |filename: String| {
-        match std::fs::read_to_string(filename) {
-            Ok(file) => Ok(file),
-            Err(_) => Err("Unable to read file".to_string())
-        }
-    };
-    let fileout_fn = |filename, data| {
-        match std::fs::write(filename, data) {
-            Ok(()) => Ok(()),
-            Err(_) => Err(())
-        }
-    };
-    let warn_fn = |message: String, line: usize| {hawk_cli_io::error::warn(message, line);};
-    let err_fn = |message: String, line: usize| {hawk_cli_io::error::error(message, line);};
-    let output_fn = |text| hawk_cli_io::output::output(text);
-    
     match args.len() {
         // 1 argument, that being the name of the application
-        1 => repl(),
+        1 => repl(HashMap::new()),
+
+        // `hawk --load <file>` opens the REPL with a saved session
+        3 if args[1] == "--load" => match session::load(&args[2], &mut Terminal) {
+            Ok(state) => repl(state),
+            Err(hawk_error) => {
+                report(&hawk_error);
+                std::process::exit(1)
+            }
+        }
+
+        // `hawk test` runs the tests in `tests/` (or the current directory if there isn't one)
+        2 if args[1] == "test" => {
+            let dir = std::path::Path::new("tests");
+            let passed = testing::run_tests(if dir.is_dir() {dir} else {std::path::Path::new(".")});
+            std::process::exit(if passed {0} else {1})
+        }
+
+        // `hawk test <path>` runs the tests in a given file or directory
+        3 if args[1] == "test" => {
+            let passed = testing::run_tests(std::path::Path::new(&args[2]));
+            std::process::exit(if passed {0} else {1})
+        }
 
         // 2 arguments: the name of the application and the name of the file to run
         2 => {
-            hawk_interpreter::run::run_script(args[1].clone(), HashMap::new(), filein_fn, fileout_fn, warn_fn, err_fn, output_fn);
+            // Report every error before exiting
+            if let Err(errors) = hawk_interpreter::run::run_script(args[1].clone(), HashMap::new(), &mut Terminal) {
+                errors.iter().for_each(report);
+                std::process::exit(1)
+            }
         }
 
         // Expect either 1 argument for REPL or 2 for executing a file
         _ => {
-            error(
+            error(HawkError::new(ErrorKind::Usage,
 "Incorrect args: expected either:
-No arguments (open REPL) or
-1 Argument (run a .hawk file)".to_string(), 0
-            );
-            std::process::exit(1)
+No arguments (open REPL),
+--load <file> (open REPL with a saved session),
+1 Argument (run a .hawk file) or
+test [path] (run the tests in a file or directory)".to_string(), 0
+            ));
         }
     }
 }
 
-/// Manages the Hawk REPL
-fn repl() {
-    let filein_fn = |filename: String| {
-        match std::fs::read_to_string(filename) {
-            Ok(file) => Ok(file),
-            Err(_) => Err("Unable to read file".to_string())
-        }
-    };
-    let fileout_fn = |filename, data| {
-        match std::fs::write(filename, data) {
-            Ok(()) => Ok(()),
-            Err(_) => Err(())
-        }
-    };
-    let warn_fn = |message: String, line: usize| {hawk_cli_io::error::warn(message, line);};
-    let err_fn = |message: String, line: usize| {hawk_cli_io::error::error(message, line);};
-    let output_fn = |text| hawk_cli_io::output::output(text);
-
-    // Global state that will be kept throughout the REPL session
-    let mut state: HashMap<String, Object> = HashMap::new();
-
+/// Manages the Hawk REPL, starting with the globals in `state`. One interpreter runs every line, so variables and
+/// settings like the propagation mode are kept between lines
+fn repl(state: HashMap<String, Object>) {
     hawk_cli_io::shell::print_welcome_message();
 
-    let mut history: Vec<String> = Vec::new();
+    let mut terminal = Terminal;
+    let mut interpreter = hawk_interpreter::run::Builder::new().repl(true).interpreter(state, &mut terminal);
+
+    // Most recent first, including lines from previous sessions
+    let mut history: Vec<String> = session::load_history();
 
     loop {
         let line = hawk_cli_io::shell::Input::get_input(history.clone());
         history.insert(0, line.to_owned());
+        session::save_history(&history);
 
         println!();
 
+        let words: Vec<&str> = line.split_whitespace().collect();
+
         if line == "exit" {
             break;
+        } else if let ["save", filename] = words[..] {
+            match session::save(&interpreter.globals, filename, &mut Terminal) {
+                Ok(()) => println!("Saved session to {filename}"),
+                Err(hawk_error) => report(&hawk_error),
+            }
+        } else if let ["load", filename] = words[..] {
+            // Loaded globals replace ones with the same name, and others are kept
+            match session::load(filename, &mut Terminal) {
+                Ok(loaded) => {
+                    interpreter.globals.extend(loaded);
+                    println!("Loaded session from {filename}")
+                },
+                Err(hawk_error) => report(&hawk_error),
+            }
         } else {
-            let result = hawk_interpreter::run::run(line, state.clone(), true, filein_fn, fileout_fn, warn_fn, err_fn, output_fn);
-            match result {
-                Ok(result) => state = result,
-                Err((message, _)) => {
-                    error(message, 0);
-                }
+            // A line that fails doesn't change any variables
+            let globals = interpreter.globals.clone();
+            if let Err(errors) = interpreter.run_source(&line) {
+                errors.iter().for_each(report);
+                interpreter.globals = globals
             }
         }
 
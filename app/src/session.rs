@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use hawk_common::environment::Environment;
+use hawk_common::error::HawkError;
+use hawk_common::object::Object;
+
+/// Name of the file in the home directory that REPL history is kept in between sessions
+const HISTORY_FILE: &str = ".hawk_history";
+
+/// Number of lines of history that are kept
+const HISTORY_LENGTH: usize = 1000;
+
+/// Saves every global of a REPL session (variables, functions, finders, data tables, etc.) to a file
+pub fn save(state: &HashMap<String, Object>, filename: &str, env: &mut dyn Environment) -> Result<(), HawkError> {
+    let contents = serde_json::to_string(state)
+        .map_err(|error| HawkError::io(format!("Couldn't save session: {error}"), 0))?;
+    env.write_file(filename, &contents)
+        .map_err(|error| HawkError::io(format!("Couldn't save session to {filename}: {error}"), 0))
+}
+
+/// Loads the globals of a session saved with `save`
+pub fn load(filename: &str, env: &mut dyn Environment) -> Result<HashMap<String, Object>, HawkError> {
+    let contents = env.read_file(filename)
+        .map_err(|error| HawkError::io(format!("Couldn't read session from {filename}: {error}"), 0))?;
+    let state: HashMap<String, Object> = serde_json::from_str(&contents)
+        .map_err(|error| HawkError::io(format!("{filename} isn't a saved session: {error}"), 0))?;
+
+    state.values().for_each(reserve_ids);
+    Ok(state)
+}
+
+/// Makes sure uncertain values created from now on don't share error sources with loaded ones, which would make them
+/// look correlated
+fn reserve_ids(object: &Object) {
+    match object {
+        Object::Uncertain { sources, .. } => sources.reserve_ids(),
+        Object::Quantity { value, .. } => reserve_ids(value),
+        Object::Array(items) | Object::Column(items) | Object::DataTable { data: items, .. } => {
+            items.iter().for_each(reserve_ids)
+        },
+        Object::Function { captured: values, .. } | Object::Instance { fields: values, .. } => {
+            values.values().for_each(reserve_ids)
+        },
+        _ => (),
+    }
+}
+
+fn history_file() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(HISTORY_FILE))
+}
+
+/// Lines entered in previous sessions, most recent first
+pub fn load_history() -> Vec<String> {
+    match history_file().and_then(|path| std::fs::read_to_string(path).ok()) {
+        Some(contents) => contents.lines().rev().map(String::from).collect(),
+        None => Vec::new(),
+    }
+}
+
+/// Keeps the most recent lines of `history` (most recent first) for the next session. History is a convenience, so
+/// it isn't an error if it can't be written
+pub fn save_history(history: &[String]) {
+    if let Some(path) = history_file() {
+        let lines: Vec<&str> = history.iter().take(HISTORY_LENGTH).rev().map(String::as_str).collect();
+        let _ = std::fs::write(path, lines.join("\n") + "\n");
+    }
+}
+
+#[test]
+fn save_and_load() {
+    use hawk_common::environment::Memory;
+
+    let mut env = Memory::new();
+    let source = String::from("let x = 2 ± 0.1
+let f = (y) => y * x
+finder force {
+    equation f = m * a
+}");
+    let state = hawk_interpreter::run::run(source, HashMap::new(), true, &mut env).unwrap();
+
+    save(&state, "session.json", &mut env).unwrap();
+    let loaded = load("session.json", &mut env).unwrap();
+
+    let result = hawk_interpreter::run::run(
+        String::from("let doubled = f(2)\nlet found = find force(f: 3, m?, a: 1.5)"), loaded, true, &mut env,
+    ).unwrap();
+    assert!(matches!(result.get("doubled"), Some(Object::Uncertain { .. })));
+    assert_eq!(result.get("found"), Some(&Object::Int(2)));
+
+    assert!(load("missing.json", &mut env).is_err());
+}
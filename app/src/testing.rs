@@ -0,0 +1,71 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use hawk_cli_io::environment::Terminal;
+use hawk_common::error::HawkError;
+
+/// Runs every `.hawk` file under `path` (or `path` itself if it is a file) as a test, each with its own global scope.
+/// A test fails if any of its `expect` statements fail or it stops with an error. Returns whether every test passed
+pub fn run_tests(path: &Path) -> bool {
+    let mut files: Vec<PathBuf> = Vec::new();
+    if path.is_dir() {
+        find_tests(path, &mut files);
+    } else {
+        files.push(path.to_path_buf());
+    }
+    files.sort();
+
+    let mut failed = 0;
+
+    for file in files.iter() {
+        println!("Running {}", file.display());
+
+        let mut terminal = Terminal;
+        let mut interpreter = hawk_interpreter::run::Builder::new().interpreter(HashMap::new(), &mut terminal);
+        let result = match std::fs::read_to_string(file) {
+            Ok(source) => interpreter.run_source(&source),
+            Err(_) => Err(vec![HawkError::io(String::from("Unable to read file"), 0)]),
+        };
+
+        let passed = match result {
+            Ok(()) if interpreter.failed_expects > 0 => {
+                println!("FAIL {} ({} failed expect statements)", file.display(), interpreter.failed_expects);
+                false
+            },
+            Ok(()) => {
+                println!("PASS {}", file.display());
+                true
+            },
+            Err(errors) => {
+                for error in errors {
+                    hawk_cli_io::error::report(&error.in_file(file.display().to_string()));
+                }
+                println!("FAIL {}", file.display());
+                false
+            }
+        };
+
+        if !passed {
+            failed += 1
+        }
+    }
+
+    println!();
+    println!("{} passed, {} failed", files.len() - failed, failed);
+
+    failed == 0
+}
+
+/// Recursively finds `.hawk` files in a directory
+fn find_tests(dir: &Path, files: &mut Vec<PathBuf>) {
+    if let Ok(entries) = std::fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                find_tests(&path, files)
+            } else if path.extension().map_or(false, |extension| extension == "hawk") {
+                files.push(path)
+            }
+        }
+    }
+}
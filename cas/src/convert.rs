@@ -0,0 +1,182 @@
+use hawk_common::object::Object;
+use hawk_common::token::TokenType;
+use hawk_common::tree;
+
+use rust_decimal::prelude::*;
+
+use crate::{expression::*, factor::*, term::*, terminal::*, ToCASExpr};
+
+/// Hawk expressions are converted for finders, and the rearranged equation converted back to be evaluated
+impl ToCASExpr for tree::Expression {
+    fn to_cas_expr(&self) -> Option<Expression> {
+        match self {
+            Self::Literal(Object::Int(x)) => Some(Expression::from_literal(Literal::Int(*x))),
+            Self::Literal(Object::Decimal(x)) => Some(Expression::from_literal(Literal::Decimal(*x))),
+            Self::Literal(Object::Identifier(name)) => Some(Expression::symbol(name.clone())),
+            Self::Parenthesized(expr) => expr.to_cas_expr(),
+            Self::Unary{operand, operator: TokenType::Minus} => Some(Expression::from_term(Term::negate(operand.to_cas_expr()?))),
+            Self::Binary{operand1, operand2, operator} => {
+                let (a, b) = (operand1.to_cas_expr()?, operand2.to_cas_expr()?);
+                match operator {
+                    TokenType::Plus => Some(a.sum(b)),
+                    TokenType::Minus => Some(a.sum(Expression::from_term(Term::negate(b)))),
+                    TokenType::Asterisk => Some(a.product(b)),
+                    TokenType::Slash => Some(Expression::from_term(Term::div(Factor::from_expression(a), Factor::from_expression(b)))),
+                    TokenType::Caret => Some(Expression::from_factor(Factor::exponent(a, b))),
+                    _ => None
+                }
+            },
+            Self::FunctionCall{identifier, args} => {
+                let args = args.iter().map(|arg| arg.to_cas_expr()).collect::<Option<Vec<Expression>>>()?;
+                Some(Expression::from_factor(Factor::function(identifier, args)))
+            },
+            _ => None
+        }
+    }
+
+    fn from_cas_expr(expr: Expression) -> Self {
+        match expr {
+            Expression::Literal(Literal::Int(x)) => Self::Literal(Object::Int(x)),
+            Expression::Literal(Literal::Decimal(x)) => Self::Literal(Object::Decimal(x)),
+            Expression::Literal(Literal::Symbol(name)) => Self::Literal(Object::Identifier(name)),
+            Expression::Term(term) => term_to_tree(term),
+            // Negative terms are subtracted, e.g. `a - b` rather than `a + -b`
+            Expression::Terms(terms) => terms.into_iter()
+                .map(term_to_tree)
+                .reduce(|a, b| match negated(&b) {
+                    Some(b) => binary(a, b, TokenType::Minus),
+                    None => binary(a, b, TokenType::Plus),
+                })
+                .unwrap_or(Self::Literal(Object::Int(0)))
+        }
+    }
+}
+
+fn binary(operand1: tree::Expression, operand2: tree::Expression, operator: TokenType) -> tree::Expression {
+    tree::Expression::Binary{operand1: Box::new(operand1), operand2: Box::new(operand2), operator}
+}
+
+/// The positive version of an expression that starts with a minus sign or a negative number, e.g. `2 * x` for
+/// `-2 * x`
+fn negated(expr: &tree::Expression) -> Option<tree::Expression> {
+    match expr {
+        tree::Expression::Unary{operand, operator: TokenType::Minus} => Some(*operand.clone()),
+        tree::Expression::Literal(Object::Int(x)) if *x < 0 => Some(tree::Expression::Literal(Object::Int(-x))),
+        tree::Expression::Literal(Object::Decimal(x)) if x.is_sign_negative() => Some(tree::Expression::Literal(Object::Decimal(-x))),
+        tree::Expression::Binary{operand1, operand2, operator: operator @ (TokenType::Asterisk | TokenType::Slash)} => {
+            Some(binary(negated(operand1)?, *operand2.clone(), *operator))
+        },
+        _ => None
+    }
+}
+
+fn term_to_tree(term: Term) -> tree::Expression {
+    match term {
+        // A coefficient of -1 is a minus sign
+        Term::Factors(mut factors) if factors.len() > 1 && factors[0] == Factor::from_literal(Literal::Int(-1)) => {
+            factors.remove(0);
+            tree::Expression::Unary{operand: Box::new(term_to_tree(Term::Factors(factors))), operator: TokenType::Minus}
+        },
+        Term::Factors(factors) => factors.into_iter()
+            .map(factor_to_tree)
+            .reduce(|a, b| binary(a, b, TokenType::Asterisk))
+            .unwrap_or(tree::Expression::Literal(Object::Int(1))),
+        // Hawk divides integers with integer division, which would make e.g. `1 / 2` zero
+        Term::Div{op1: Factor::Expression(expr), op2} if matches!(*expr, Expression::Literal(Literal::Int(_))) => {
+            let numerator = match *expr {
+                Expression::Literal(Literal::Int(x)) => tree::Expression::Literal(Object::Decimal(Decimal::from_i128(x).unwrap_or_default())),
+                _ => unreachable!()
+            };
+            binary(numerator, factor_to_tree(op2), TokenType::Slash)
+        },
+        Term::Div{op1, op2} => binary(factor_to_tree(op1), factor_to_tree(op2), TokenType::Slash),
+        Term::Expression(expr) => tree::Expression::from_cas_expr(*expr)
+    }
+}
+
+fn factor_to_tree(factor: Factor) -> tree::Expression {
+    match factor {
+        Factor::Exponent{base, exponent} => binary(
+            tree::Expression::from_cas_expr(*base), tree::Expression::from_cas_expr(*exponent), TokenType::Caret
+        ),
+        Factor::Expression(expr) => tree::Expression::from_cas_expr(*expr),
+        Factor::Function{name, args} => tree::Expression::FunctionCall{
+            identifier: name,
+            args: args.into_iter().map(|arg| Box::new(tree::Expression::from_cas_expr(arg))).collect()
+        }
+    }
+}
+
+#[test]
+fn negative_terms() {
+    let symbol = |name: &str| Expression::symbol(String::from(name));
+    let tree_symbol = |name: &str| tree::Expression::Literal(Object::Identifier(String::from(name)));
+
+    // a - b - 2 * c
+    let expr = Expression::from_terms(vec![
+        Term::from_expression(symbol("a")),
+        Term::negate(symbol("b")),
+        Term::from_factors(vec![Factor::from_literal(Literal::Int(-2)), Factor::from_expression(symbol("c"))]),
+    ]);
+    let expected = binary(
+        binary(tree_symbol("a"), tree_symbol("b"), TokenType::Minus),
+        binary(tree::Expression::Literal(Object::Int(2)), tree_symbol("c"), TokenType::Asterisk),
+        TokenType::Minus,
+    );
+    assert_eq!(tree::Expression::from_cas_expr(expr), expected);
+
+    let negative = Expression::from_term(Term::negate(symbol("x")));
+    let expected = tree::Expression::Unary{operand: Box::new(tree_symbol("x")), operator: TokenType::Minus};
+    assert_eq!(tree::Expression::from_cas_expr(negative), expected);
+}
+
+#[test]
+fn solve_tree_equations() {
+    use crate::equation::Equation;
+
+    let x = || Box::new(tree::Expression::Literal(Object::Identifier(String::from("x"))));
+    let int = |x| Box::new(tree::Expression::Literal(Object::Int(x)));
+    let solve = |lhs: Box<tree::Expression>, rhs: Box<tree::Expression>| {
+        let mut equation = Equation::new(lhs.to_cas_expr().unwrap(), rhs.to_cas_expr().unwrap());
+        equation.solve_for(String::from("x")).map(tree::Expression::from_cas_expr)
+    };
+
+    // x * 2 = 4, 4 = x * 2 and 4 = 2 * x all give 2
+    assert_eq!(solve(Box::new(binary(*x(), *int(2), TokenType::Asterisk)), int(4)), Ok(*int(2)));
+    assert_eq!(solve(int(4), Box::new(binary(*x(), *int(2), TokenType::Asterisk))), Ok(*int(2)));
+    assert_eq!(solve(int(4), Box::new(binary(*int(2), *x(), TokenType::Asterisk))), Ok(*int(2)));
+
+    // x / 2 = 4 gives 8
+    assert_eq!(solve(Box::new(binary(*x(), *int(2), TokenType::Slash)), int(4)), Ok(*int(8)));
+    assert_eq!(solve(int(4), Box::new(binary(*x(), *int(2), TokenType::Slash))), Ok(*int(8)));
+
+    // 2 / x = 4 gives 1 / 2, with a decimal numerator so that it isn't integer division
+    let half = Ok(binary(tree::Expression::Literal(Object::Decimal(Decimal::ONE)), *int(2), TokenType::Slash));
+    assert_eq!(solve(Box::new(binary(*int(2), *x(), TokenType::Slash)), int(4)), half);
+    assert_eq!(solve(int(4), Box::new(binary(*int(2), *x(), TokenType::Slash))), half);
+
+    // (x + 1)^2 = 9 gives 9^(1 / 2) - 1, which is 2
+    let lhs = binary(tree::Expression::Parenthesized(Box::new(binary(*x(), *int(1), TokenType::Plus))), *int(2), TokenType::Caret);
+    assert_eq!(solve(Box::new(lhs), int(9)), Ok(*int(2)));
+
+    // 2 * x = x + 3, with x on both sides
+    let rhs = binary(*x(), *int(3), TokenType::Plus);
+    assert_eq!(solve(Box::new(binary(*int(2), *x(), TokenType::Asterisk)), Box::new(rhs)), Ok(*int(3)));
+
+    // sin(x) = 1 can't be solved, as there is no inverse of sin
+    let sin = tree::Expression::FunctionCall{identifier: String::from("sin"), args: vec![x()]};
+    assert_eq!(solve(Box::new(sin), int(1)), Err(String::from("Can't solve for x inside sin()")));
+
+    // x * x = 4, once like factors are combined
+    assert_eq!(solve(Box::new(binary(*x(), *x(), TokenType::Asterisk)), int(4)), Ok(*int(2)));
+
+    // x * (x + 1) = 2 is a quadratic
+    let lhs = binary(*x(), tree::Expression::Parenthesized(Box::new(binary(*x(), *int(1), TokenType::Plus))), TokenType::Asterisk);
+    assert!(solve(Box::new(lhs), int(2)).is_err());
+
+    // x + 2 = 1 gives 1 - 2, which is -1
+    assert_eq!(solve(Box::new(binary(*x(), *int(2), TokenType::Plus)), int(1)), Ok(*int(-1)));
+
+    let comparison = binary(*x(), *int(1), TokenType::GreaterThan);
+    assert_eq!(comparison.to_cas_expr(), None);
+}
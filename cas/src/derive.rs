@@ -0,0 +1,160 @@
+use crate::{expression::*, factor::*, term::*, terminal::*};
+
+fn int(x: i128) -> Expression {
+    Expression::from_literal(Literal::Int(x))
+}
+
+fn quotient(numerator: Expression, denominator: Expression) -> Expression {
+    Expression::from_term(Term::div(Factor::from_expression(numerator), Factor::from_expression(denominator)))
+}
+
+fn function(name: &str, arg: Expression) -> Expression {
+    Expression::from_factor(Factor::function(name, vec![arg]))
+}
+
+fn power(base: Expression, exponent: Expression) -> Expression {
+    Expression::from_factor(Factor::exponent(base, exponent))
+}
+
+/// Sums the derivatives of terms, leaving out those that are zero
+fn sum(derivatives: Vec<Expression>) -> Expression {
+    derivatives.into_iter()
+        .filter(|derivative| *derivative != int(0))
+        .reduce(Expression::sum)
+        .unwrap_or(int(0))
+}
+
+impl Expression {
+    /// The derivative of the expression with respect to `var`, simplified. Other variables are treated as constants
+    pub fn derivative(&self, var: &str) -> Result<Expression, String> {
+        Ok(self.derive(var)?.simplified())
+    }
+
+    fn derive(&self, var: &str) -> Result<Expression, String> {
+        if !self.contains(String::from(var)) {
+            return Ok(int(0))
+        }
+        match self {
+            Self::Literal(_) => Ok(int(1)),
+            Self::Term(term) => term.derive(var),
+            Self::Terms(terms) => Ok(sum(terms.iter().map(|term| term.derive(var)).collect::<Result<_, _>>()?))
+        }
+    }
+}
+
+impl Term {
+    fn derive(&self, var: &str) -> Result<Expression, String> {
+        match self {
+            // Product rule, differentiating one factor at a time
+            Self::Factors(factors) => {
+                let mut derivatives = Vec::new();
+                for (i, factor) in factors.iter().enumerate() {
+                    if !factor.contains(String::from(var)) {
+                        continue
+                    }
+                    let others = factors.iter().enumerate()
+                        .filter(|(j, _)| *j != i)
+                        .map(|(_, other)| Expression::from_factor(other.clone()));
+                    derivatives.push(others.fold(factor.derive(var)?, Expression::product));
+                }
+                Ok(sum(derivatives))
+            },
+            // Quotient rule, (a' * b - a * b') / b^2
+            Self::Div{op1, op2} => {
+                let (a, b) = (Expression::from_factor(op1.clone()), Expression::from_factor(op2.clone()));
+                let numerator = op1.derive(var)?.product(b.clone())
+                    .sum(Expression::from_term(Term::negate(a.product(op2.derive(var)?))));
+                Ok(quotient(numerator, power(b, int(2))))
+            },
+            Self::Expression(expr) => expr.derive(var)
+        }
+    }
+}
+
+impl Factor {
+    fn derive(&self, var: &str) -> Result<Expression, String> {
+        if !self.contains(String::from(var)) {
+            return Ok(int(0))
+        }
+        match self {
+            Self::Expression(expr) => expr.derive(var),
+            Self::Exponent{base, exponent} => {
+                let (base, exponent) = (*base.clone(), *exponent.clone());
+                if !exponent.contains(String::from(var)) {
+                    // Power rule, n * u^(n - 1) * u'
+                    let lowered = power(base.clone(), exponent.clone().sum(int(-1)));
+                    Ok(exponent.product(lowered).product(base.derive(var)?))
+                } else {
+                    // u^v * (v' * ln(u) + v * u' / u), which is u^v * ln(u) * v' when u is constant
+                    let logarithm = exponent.derive(var)?.product(function("ln", base.clone()));
+                    let rest = quotient(exponent.clone().product(base.derive(var)?), base.clone());
+                    Ok(power(base, exponent).product(logarithm.sum(rest)))
+                }
+            },
+            // Chain rule, f'(u) * u'
+            Self::Function{name, args} => {
+                let arg = match &args[..] {
+                    [arg] => arg.clone(),
+                    _ => return Err(format!("Can't differentiate {name}() with {} arguments", args.len()))
+                };
+                let outer = match name.as_str() {
+                    "sin" => function("cos", arg.clone()),
+                    "cos" => Expression::from_term(Term::negate(function("sin", arg.clone()))),
+                    "tan" => quotient(int(1), power(function("cos", arg.clone()), int(2))),
+                    "ln" => quotient(int(1), arg.clone()),
+                    "exp" => function("exp", arg.clone()),
+                    _ => return Err(format!("Can't differentiate {name}()"))
+                };
+                Ok(outer.product(arg.derive(var)?))
+            }
+        }
+    }
+}
+
+/// Parses `source` as Hawk, differentiates it with respect to `x` and prints it
+#[cfg(test)]
+fn derived(source: &str) -> String {
+    use hawk_common::tree::Statement;
+    use crate::ToCASExpr;
+
+    let tokens = hawk_lib::lexer::Lexer::lex(source, &mut |_, _| ()).unwrap();
+    let statements = hawk_lib::parser::Parser::parse(&tokens, &mut |_, _| ()).unwrap();
+    match statements.into_iter().find(|statement| matches!(statement, Statement::Expression(_))) {
+        Some(Statement::Expression(expr)) => match expr.to_cas_expr().unwrap().derivative("x") {
+            Ok(derivative) => derivative.to_string(),
+            Err(message) => message
+        },
+        _ => panic!("expected an expression")
+    }
+}
+
+#[test]
+fn sums_and_products() {
+    assert_eq!(derived("3"), "0");
+    assert_eq!(derived("y"), "0");
+    assert_eq!(derived("x"), "1");
+    assert_eq!(derived("3 * x + 2"), "3");
+    assert_eq!(derived("x * y + y"), "y");
+    assert_eq!(derived("x * sin(x)"), "sin(x) + x * cos(x)");
+}
+
+#[test]
+fn powers_and_quotients() {
+    assert_eq!(derived("x^2"), "2 * x");
+    assert_eq!(derived("x^3 + 2 * x^2 + x"), "4 * x + 3 * x^2 + 1");
+    assert_eq!(derived("1 / x"), "-1 / x^2");
+    assert_eq!(derived("x / (x + 1)"), "1 / (x + 1)^2");
+    assert_eq!(derived("2^x"), "2^x * ln(2)");
+    assert_eq!(derived("e()^x"), "e()^x");
+}
+
+#[test]
+fn chain_rule() {
+    assert_eq!(derived("sin(2 * x)"), "2 * cos(2 * x)");
+    assert_eq!(derived("cos(x^2)"), "-2 * x * sin(x^2)");
+    assert_eq!(derived("ln(x)"), "1 / x");
+    assert_eq!(derived("exp(3 * x)"), "3 * exp(3 * x)");
+    assert_eq!(derived("(x + 1)^2"), "2 * (x + 1)");
+    assert_eq!(derived("sqrt(x)"), "Can't differentiate sqrt()");
+    assert_eq!(derived("sqrt(y)"), "0");
+}
@@ -1,5 +1,3 @@
-#![allow(unused)]
-
 use crate::{expression::*, terminal::*, term::*, factor::*};
 
 #[derive(Debug, PartialEq)]
@@ -13,35 +11,137 @@ impl Equation {
         Equation{lhs, rhs}
     }
 
-    pub fn solve_for(&mut self, var: String) -> Expression {
+    /// Rearranges the equation to give `var` in terms of the other variables. Fails if `var` can't be isolated, e.g.
+    /// if it is inside a function with no known inverse
+    pub fn solve_for(&mut self, var: String) -> Result<Expression, String> {
         self.lhs.simplify();
+        self.rhs.simplify();
+
+        match (self.lhs.contains(var.to_owned()), self.rhs.contains(var.to_owned())) {
+            (false, false) => return Err(format!("The equation doesn't contain {var}")),
+            (false, true) => std::mem::swap(&mut self.lhs, &mut self.rhs),
+            // Move everything to the left, `lhs - rhs = 0`, so that `var` can be collected into one term
+            (true, true) => {
+                let lhs = std::mem::replace(&mut self.lhs, Expression::Literal(Literal::Int(0)));
+                let rhs = std::mem::replace(&mut self.rhs, Expression::Literal(Literal::Int(0)));
+                self.lhs = lhs.sum(Expression::from_term(Term::negate(rhs)));
+                self.lhs.simplify();
+            },
+            (true, false) => ()
+        }
 
         while !self.is_solved(var.to_owned()) {
-            self.do_rearrange_step(var.to_owned())
+            self.do_rearrange_step(var.to_owned())?
         }
 
-        self.rhs.clone()
+        self.rhs.simplify();
+        Ok(self.rhs.clone())
+    }
+
+    /// Names of the variables in the equation
+    pub fn symbols(&self) -> Vec<String> {
+        let mut symbols = Vec::new();
+        self.lhs.symbols(&mut symbols);
+        self.rhs.symbols(&mut symbols);
+        symbols
     }
 
     fn is_solved(&self, var: String) -> bool {
         self.lhs == Expression::symbol(var)
     }
 
-    fn do_rearrange_step(&mut self, var: String) {
-        if !self.lhs.contains(var.to_owned()) {
+    /// Undoes the outermost operation on the left hand side, which contains `var` while the right hand side doesn't
+    fn do_rearrange_step(&mut self, var: String) -> Result<(), String> {
+        let lhs = std::mem::replace(&mut self.lhs, Expression::Literal(Literal::Int(0)));
+        let rhs = std::mem::replace(&mut self.rhs, Expression::Literal(Literal::Int(0)));
+
+        let (lhs, rhs) = match lhs {
+            Expression::Literal(_) => return Err(format!("Can't solve for {var}")),
+            Expression::Term(term) => Self::rearrange_term(term, rhs, &var)?,
+            // a + x = b  =>  x = b - a
+            Expression::Terms(mut terms) => {
+                let index = Self::only_one(terms.iter().map(|term| term.contains(var.to_owned())), &var)?;
+                let term = terms.remove(index);
+
+                let mut moved = vec![Term::from_expression(rhs)];
+                moved.extend(terms.into_iter().map(|term| Term::negate(Expression::from_term(term))));
+                (Expression::from_term(term), Expression::Terms(moved))
+            }
+        };
+
+        self.lhs = lhs;
+        self.rhs = rhs;
+        Ok(())
+    }
+
+    fn rearrange_term(term: Term, rhs: Expression, var: &str) -> Result<(Expression, Expression), String> {
+        match term {
+            Term::Expression(expr) => Ok((*expr, rhs)),
+            // a * x = b  =>  x = b / a
+            Term::Factors(mut factors) => {
+                let index = Self::only_one(factors.iter().map(|factor| factor.contains(var.to_owned())), var)?;
+                let factor = factors.remove(index);
 
-        } else if !self.rhs.contains(var.to_owned()) {
-            match &self.lhs {
-                Expression::Term(x) => {
-    
+                if factors.is_empty() {
+                    Self::rearrange_factor(factor, rhs, var)
+                } else {
+                    let rhs = Term::div(Factor::from_expression(rhs), Factor::product(factors));
+                    Ok((Expression::from_factor(factor), Expression::from_term(rhs)))
+                }
+            },
+            Term::Div{op1, op2} => match (op1.contains(var.to_owned()), op2.contains(var.to_owned())) {
+                // x / a = b  =>  x = b * a
+                (true, false) => Ok((
+                    Expression::from_factor(op1),
+                    Expression::from_term(Term::Factors(vec![Factor::from_expression(rhs), op2]))
+                )),
+                // a / x = b  =>  x = a / b
+                (false, true) => Ok((
+                    Expression::from_factor(op2),
+                    Expression::from_term(Term::div(op1, Factor::from_expression(rhs)))
+                )),
+                _ => Err(Self::more_than_once(var))
+            }
+        }
+    }
+
+    fn rearrange_factor(factor: Factor, rhs: Expression, var: &str) -> Result<(Expression, Expression), String> {
+        match factor {
+            Factor::Expression(expr) => Ok((*expr, rhs)),
+            Factor::Exponent{base, exponent} => match (base.contains(var.to_owned()), exponent.contains(var.to_owned())) {
+                // x ^ a = b  =>  x = b ^ (1 / a)
+                (true, false) => {
+                    let root = Term::div(Factor::from_literal(Literal::Int(1)), Factor::from_expression(*exponent));
+                    Ok((*base, Expression::from_factor(Factor::exponent(rhs, Expression::from_term(root)))))
                 },
-                Expression::Terms(x) => {
-    
+                // a ^ x = b  =>  x = ln(b) / ln(a)
+                (false, true) => {
+                    let log = Term::div(Factor::function("ln", vec![rhs]), Factor::function("ln", vec![*base]));
+                    Ok((*exponent, Expression::from_term(log)))
                 },
-                _ => {}
-            }
+                _ => Err(Self::more_than_once(var))
+            },
+            // ln(x) = b  =>  x = e() ^ b
+            Factor::Function{name, mut args} if name == "ln" && args.len() == 1 => {
+                let e = Expression::from_factor(Factor::function("e", Vec::new()));
+                Ok((args.remove(0), Expression::from_factor(Factor::exponent(e, rhs))))
+            },
+            Factor::Function{name, ..} => Err(format!("Can't solve for {var} inside {name}()"))
         }
     }
+
+    /// Index of the only part of a sum or product that contains `var`
+    fn only_one(contains: impl Iterator<Item = bool>, var: &str) -> Result<usize, String> {
+        let indices: Vec<usize> = contains.enumerate().filter(|(_, contains)| *contains).map(|(index, _)| index).collect();
+        match indices[..] {
+            [index] => Ok(index),
+            _ => Err(Self::more_than_once(var))
+        }
+    }
+
+    fn more_than_once(var: &str) -> String {
+        format!("Can't solve for {var}, as it appears in more than one place")
+    }
 }
 
 #[test]
@@ -63,11 +163,11 @@ fn solve_mult() {
 
     assert_eq!(
         eq.solve_for(String::from("x")), 
-        Expression::from_term(
+        Ok(Expression::from_term(
             Term::div(
-                Factor::from_literal(Int(5)), 
-                Factor::from_literal(Int(10))
+                Factor::from_literal(Int(1)), 
+                Factor::from_literal(Int(2))
             )
-        )
+        ))
     )
 }
@@ -1,3 +1,5 @@
+use std::fmt::{Display, Formatter, Result};
+
 use crate::{equation::*, terminal::*, term::*, factor::*};
 
 #[derive(Debug, PartialEq, Clone)]
@@ -20,6 +22,14 @@ impl Expression {
         Self::Term(term)
     }
 
+    /// Create an expression from a factor, without nesting it if the factor is just an expression in parentheses
+    pub fn from_factor(factor: Factor) -> Self {
+        match factor {
+            Factor::Expression(expr) => *expr,
+            factor => Self::Term(Term::Factors(vec![factor])),
+        }
+    }
+
     pub fn from_terms(terms: Vec<Term>) -> Self {
         Self::Terms(terms)
     }
@@ -58,8 +68,91 @@ impl Expression {
         }
     }
 
-    pub fn simplify(&mut self) {
+    /// `self + other`, adding to the terms of a sum rather than nesting it
+    pub fn sum(self, other: Expression) -> Self {
+        let mut terms = self.into_terms();
+        terms.append(&mut other.into_terms());
+        Self::Terms(terms)
+    }
+
+    /// `self * other`, adding to the factors of a product rather than nesting it
+    pub fn product(self, other: Expression) -> Self {
+        let mut factors = self.into_factors();
+        factors.append(&mut other.into_factors());
+        Self::Term(Term::Factors(factors))
+    }
+
+    fn into_terms(self) -> Vec<Term> {
+        match self {
+            Self::Terms(terms) => terms,
+            expr => vec![Term::from_expression(expr)],
+        }
+    }
+
+    fn into_factors(self) -> Vec<Factor> {
+        match self {
+            Self::Term(Term::Factors(factors)) => factors,
+            expr => vec![Factor::from_expression(expr)],
+        }
+    }
 
+    /// Adds the names of the variables in the expression to `symbols`, each only once
+    pub fn symbols(&self, symbols: &mut Vec<String>) {
+        match self {
+            Self::Literal(Literal::Symbol(sym)) => {
+                if !symbols.contains(sym) {
+                    symbols.push(sym.clone())
+                }
+            },
+            Self::Literal(_) => (),
+            Self::Term(term) => term.symbols(symbols),
+            Self::Terms(terms) => terms.iter().for_each(|term| term.symbols(symbols))
+        }
+    }
+
+    /// Replaces every use of the variable `var` with `value`
+    pub fn substitute(&self, var: &str, value: &Expression) -> Self {
+        match self {
+            Self::Literal(Literal::Symbol(sym)) if sym == var => value.clone(),
+            Self::Literal(literal) => Self::Literal(literal.clone()),
+            Self::Term(term) => Self::Term(term.substitute(var, value)),
+            Self::Terms(terms) => Self::Terms(terms.iter().map(|term| term.substitute(var, value)).collect())
+        }
+    }
+}
+
+/// Prints the expression as Hawk code, with only the parentheses that are needed
+impl Display for Expression {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            Self::Literal(literal) => write!(f, "{literal}"),
+            Self::Term(term) => write!(f, "{term}"),
+            Self::Terms(terms) => {
+                for (index, term) in terms.iter().enumerate() {
+                    let term = term.to_string();
+                    match (index, term.strip_prefix('-')) {
+                        (0, _) => write!(f, "{term}")?,
+                        (_, Some(negated)) => write!(f, " - {negated}")?,
+                        (_, None) => write!(f, " + {term}")?
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Expression {
+    /// Whether the expression can be printed next to an operator without parentheses, e.g. `x`, `2` or `sin(x)`
+    pub fn is_atomic(&self) -> bool {
+        match self {
+            Self::Literal(Literal::Int(x)) => *x >= 0,
+            Self::Literal(Literal::Decimal(x)) => x.is_sign_positive(),
+            Self::Literal(Literal::Symbol(_)) => true,
+            Self::Term(Term::Factors(factors)) => matches!(factors[..], [Factor::Function{..}]),
+            Self::Term(Term::Expression(expr)) => expr.is_atomic(),
+            _ => false
+        }
     }
 }
 
@@ -1,9 +1,13 @@
+use std::fmt::{Display, Formatter, Result};
+
 use crate::{equation::*, terminal::*, expression::*, term::*};
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum Factor {
     Exponent{base: Box<Expression>, exponent: Box<Expression>},
-    Expression(Box<Expression>)
+    Expression(Box<Expression>),
+    /// A call to a function, e.g. `sin(x)` or `pi()`
+    Function{name: String, args: Vec<Expression>}
 }
 
 impl Factor {
@@ -17,10 +21,83 @@ impl Factor {
         Self::Expression(Expression::box_terms(terms))
     }
 
+    /// Create a factor from an expression, without nesting it if it is already a single factor
+    pub fn from_expression(expr: Expression) -> Self {
+        match expr {
+            Expression::Term(Term::Factors(mut factors)) if factors.len() == 1 => factors.remove(0),
+            expr => Self::Expression(Box::new(expr)),
+        }
+    }
+
+    /// Create a factor from a product of factors, `Factor * Factor * ...`
+    pub fn product(mut factors: Vec<Factor>) -> Self {
+        if factors.len() == 1 {
+            factors.remove(0)
+        } else {
+            Self::Expression(Box::new(Expression::from_term(Term::Factors(factors))))
+        }
+    }
+
+    /// `base ^ exponent`
+    pub fn exponent(base: Expression, exponent: Expression) -> Self {
+        Self::Exponent{base: Box::new(base), exponent: Box::new(exponent)}
+    }
+
+    pub fn function(name: &str, args: Vec<Expression>) -> Self {
+        Self::Function{name: String::from(name), args}
+    }
+
     pub fn contains(&self, var: String) -> bool {
         match self {
             Self::Exponent{base, exponent} => base.contains(var.clone()) || exponent.contains(var),
-            Self::Expression(expr) => expr.contains(var)
+            Self::Expression(expr) => expr.contains(var),
+            Self::Function{args, ..} => args.iter().any(|arg| arg.contains(var.clone()))
+        }
+    }
+
+    /// Adds the names of the variables in the factor to `symbols`
+    pub fn symbols(&self, symbols: &mut Vec<String>) {
+        match self {
+            Self::Exponent{base, exponent} => {
+                base.symbols(symbols);
+                exponent.symbols(symbols)
+            },
+            Self::Expression(expr) => expr.symbols(symbols),
+            Self::Function{args, ..} => args.iter().for_each(|arg| arg.symbols(symbols))
+        }
+    }
+
+    /// Replaces every use of the variable `var` with `value`
+    pub fn substitute(&self, var: &str, value: &Expression) -> Self {
+        match self {
+            Self::Exponent{base, exponent} => Self::exponent(base.substitute(var, value), exponent.substitute(var, value)),
+            Self::Expression(expr) => Self::Expression(Box::new(expr.substitute(var, value))),
+            Self::Function{name, args} => Self::Function{
+                name: name.clone(),
+                args: args.iter().map(|arg| arg.substitute(var, value)).collect()
+            }
+        }
+    }
+}
+
+impl Display for Factor {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            Self::Exponent{base, exponent} => {
+                match base.is_atomic() {
+                    true => write!(f, "{base}^")?,
+                    false => write!(f, "({base})^")?
+                }
+                match exponent.is_atomic() {
+                    true => write!(f, "{exponent}"),
+                    false => write!(f, "({exponent})")
+                }
+            },
+            Self::Expression(expr) => write!(f, "{expr}"),
+            Self::Function{name, args} => {
+                let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+                write!(f, "{name}({})", args.join(", "))
+            }
         }
     }
 }
@@ -2,14 +2,19 @@
 
 use expression::Expression;
 
-mod equation;
-mod expression;
-mod terminal;
-mod term;
-mod factor;
+pub mod convert;
+pub mod derive;
+pub mod equation;
+pub mod expression;
+pub mod simplify;
+pub mod terminal;
+pub mod term;
+pub mod factor;
 
 
-trait ToCASExpr {
-    fn to_cas_expr(&mut self) -> Expression;
+/// Conversion between the expressions of another representation (e.g. Hawk's syntax tree) and CAS expressions
+pub trait ToCASExpr: Sized {
+    /// Gives `None` if the expression has parts the CAS can't represent, e.g. comparisons
+    fn to_cas_expr(&self) -> Option<Expression>;
     fn from_cas_expr(expr: Expression) -> Self;
 }
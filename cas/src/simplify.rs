@@ -0,0 +1,564 @@
+use std::cmp::Ordering;
+
+use rust_decimal::prelude::*;
+
+use crate::{expression::*, factor::*, term::*, terminal::*};
+
+/// An exact fraction of integers, or a decimal once a decimal is involved. Keeping fractions exact means e.g. `x / 3`
+/// stays as it is rather than becoming `0.3333 * x`
+#[derive(Debug, Clone, Copy)]
+enum Number {
+    /// Numerator and denominator, with no common factors and a positive denominator
+    Rational(i128, i128),
+    Decimal(Decimal)
+}
+
+impl PartialEq for Number {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Rational(n1, d1), Self::Rational(n2, d2)) => n1 == n2 && d1 == d2,
+            (Self::Decimal(a), Self::Decimal(b)) => a == b,
+            _ => false
+        }
+    }
+}
+
+impl Number {
+    fn int(x: i128) -> Self {
+        Self::Rational(x, 1)
+    }
+
+    /// `None` if the denominator is zero
+    fn rational(numerator: i128, denominator: i128) -> Option<Self> {
+        if denominator == 0 {
+            return None
+        }
+        let divisor = gcd(numerator, denominator) * denominator.signum();
+        Some(Self::Rational(numerator / divisor, denominator / divisor))
+    }
+
+    fn to_decimal(self) -> Option<Decimal> {
+        match self {
+            Self::Rational(n, d) => Decimal::from_i128(n)?.checked_div(Decimal::from_i128(d)?),
+            Self::Decimal(x) => Some(x)
+        }
+    }
+
+    fn is_zero(self) -> bool {
+        self.to_decimal().is_some_and(|x| x.is_zero())
+    }
+
+    fn is_one(self) -> bool {
+        self.to_decimal().is_some_and(|x| x == Decimal::ONE)
+    }
+
+    fn is_negative(self) -> bool {
+        self.to_decimal().is_some_and(|x| x.is_sign_negative() && !x.is_zero())
+    }
+
+    fn as_integer(self) -> Option<i128> {
+        match self {
+            Self::Rational(n, 1) => Some(n),
+            _ => None
+        }
+    }
+
+    /// `None` if the result doesn't fit, in which case the expression isn't folded
+    fn add(self, other: Self) -> Option<Self> {
+        match (self, other) {
+            (Self::Rational(n1, d1), Self::Rational(n2, d2)) => {
+                Self::rational(n1.checked_mul(d2)?.checked_add(n2.checked_mul(d1)?)?, d1.checked_mul(d2)?)
+            },
+            _ => Some(Self::Decimal(self.to_decimal()?.checked_add(other.to_decimal()?)?))
+        }
+    }
+
+    fn mul(self, other: Self) -> Option<Self> {
+        match (self, other) {
+            (Self::Rational(n1, d1), Self::Rational(n2, d2)) => Self::rational(n1.checked_mul(n2)?, d1.checked_mul(d2)?),
+            _ => Some(Self::Decimal(self.to_decimal()?.checked_mul(other.to_decimal()?)?))
+        }
+    }
+
+    fn neg(self) -> Self {
+        match self {
+            Self::Rational(n, d) => Self::Rational(-n, d),
+            Self::Decimal(x) => Self::Decimal(-x)
+        }
+    }
+
+    fn recip(self) -> Option<Self> {
+        match self {
+            Self::Rational(n, d) => Self::rational(d, n),
+            Self::Decimal(x) if !x.is_zero() => Some(Self::Decimal(Decimal::ONE / x)),
+            _ => None
+        }
+    }
+
+    /// Integer powers, roots that come out exactly (e.g. `4^(1/2)`) and powers of decimals. Others are left as they are
+    fn pow(self, exponent: Self) -> Option<Self> {
+        match (self, exponent) {
+            (_, Self::Rational(p, 1)) if p < 0 => self.recip()?.pow(Self::int(-p)),
+            (Self::Rational(n, d), Self::Rational(p, 1)) => {
+                let p = u32::try_from(p).ok()?;
+                Self::rational(n.checked_pow(p)?, d.checked_pow(p)?)
+            },
+            (Self::Decimal(x), Self::Rational(p, 1)) => Some(Self::Decimal(x.checked_powi(i64::try_from(p).ok()?)?)),
+            (Self::Rational(n, d), Self::Rational(p, q)) => {
+                let root = Self::rational(exact_root(n, q)?, exact_root(d, q)?)?;
+                root.pow(Self::int(p))
+            },
+            _ => {
+                let base = self.to_decimal()?;
+                if base.is_sign_negative() {
+                    None
+                } else {
+                    Some(Self::Decimal(base.checked_powd(exponent.to_decimal()?)?))
+                }
+            }
+        }
+    }
+
+    fn to_literal(self) -> Literal {
+        match self {
+            Self::Rational(n, _) => Literal::Int(n),
+            Self::Decimal(x) => Literal::Decimal(x.normalize())
+        }
+    }
+}
+
+fn gcd(a: i128, b: i128) -> i128 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        (a, b) = (b, a % b)
+    }
+    a.max(1)
+}
+
+/// The `n`th root of `x`, if it is an integer
+fn exact_root(x: i128, n: i128) -> Option<i128> {
+    if x < 0 || n <= 0 {
+        return None
+    }
+    let n = u32::try_from(n).ok()?;
+    let guess = (x as f64).powf(1.0 / n as f64).round() as i128;
+    (guess.max(0)..=guess + 1).find(|root| root.checked_pow(n) == Some(x))
+}
+
+/// An expression in a form that is easy to simplify: sums and products are flat lists, and division and negation are
+/// products with negative powers and a coefficient of -1
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Number(Number),
+    Symbol(String),
+    Function(String, Vec<Node>),
+    Sum(Vec<Node>),
+    Product(Vec<Node>),
+    Power(Box<Node>, Box<Node>)
+}
+
+impl Node {
+    fn from_expression(expr: &Expression) -> Self {
+        match expr {
+            Expression::Literal(Literal::Int(x)) => Self::Number(Number::int(*x)),
+            Expression::Literal(Literal::Decimal(x)) => Self::Number(Number::Decimal(*x)),
+            Expression::Literal(Literal::Symbol(name)) => Self::Symbol(name.clone()),
+            Expression::Term(term) => Self::from_term(term),
+            Expression::Terms(terms) => Self::Sum(terms.iter().map(Self::from_term).collect())
+        }
+    }
+
+    fn from_term(term: &Term) -> Self {
+        match term {
+            Term::Factors(factors) => Self::Product(factors.iter().map(Self::from_factor).collect()),
+            Term::Div{op1, op2} => Self::Product(vec![
+                Self::from_factor(op1),
+                Self::Power(Box::new(Self::from_factor(op2)), Box::new(Self::Number(Number::int(-1))))
+            ]),
+            Term::Expression(expr) => Self::from_expression(expr)
+        }
+    }
+
+    fn from_factor(factor: &Factor) -> Self {
+        match factor {
+            Factor::Exponent{base, exponent} => Self::Power(
+                Box::new(Self::from_expression(base)), Box::new(Self::from_expression(exponent))
+            ),
+            Factor::Expression(expr) => Self::from_expression(expr),
+            Factor::Function{name, args} => Self::Function(name.clone(), args.iter().map(Self::from_expression).collect())
+        }
+    }
+
+    fn into_expression(self) -> Expression {
+        match self {
+            Self::Number(Number::Rational(_, d)) if d != 1 => Self::Product(vec![self]).into_expression(),
+            Self::Number(x) => Expression::Literal(x.to_literal()),
+            Self::Symbol(name) => Expression::symbol(name),
+            Self::Function(name, args) => Expression::from_factor(
+                Factor::Function{name, args: args.into_iter().map(Self::into_expression).collect()}
+            ),
+            Self::Sum(terms) => Expression::Terms(
+                terms.into_iter().map(|term| Term::from_expression(term.into_expression())).collect()
+            ),
+            Self::Power(_, ref exponent) if matches!(**exponent, Self::Number(x) if x.is_negative()) => {
+                Self::Product(vec![self]).into_expression()
+            },
+            Self::Power(base, exponent) => Expression::from_factor(
+                Factor::exponent(base.into_expression(), exponent.into_expression())
+            ),
+            Self::Product(factors) => Self::product_into_expression(factors)
+        }
+    }
+
+    /// Factors with negative powers (and the denominator of the coefficient) are put below a division
+    fn product_into_expression(factors: Vec<Node>) -> Expression {
+        let mut numerator: Vec<Factor> = Vec::new();
+        let mut denominator: Vec<Factor> = Vec::new();
+
+        for factor in factors {
+            match factor {
+                Self::Number(Number::Rational(n, d)) => {
+                    if n != 1 {
+                        numerator.push(Factor::from_literal(Literal::Int(n)))
+                    }
+                    if d != 1 {
+                        denominator.push(Factor::from_literal(Literal::Int(d)))
+                    }
+                },
+                Self::Power(base, exponent) if matches!(*exponent, Self::Number(x) if x.is_negative()) => {
+                    if let Self::Number(x) = *exponent {
+                        denominator.push(Factor::from_expression(simplify_power(*base, Self::Number(x.neg())).into_expression()))
+                    }
+                },
+                factor => numerator.push(Factor::from_expression(factor.into_expression()))
+            }
+        }
+
+        let numerator = match numerator.len() {
+            0 => Factor::from_literal(Literal::Int(1)),
+            _ => Factor::product(numerator)
+        };
+        if denominator.is_empty() {
+            Expression::from_factor(numerator)
+        } else {
+            Expression::from_term(Term::div(numerator, Factor::product(denominator)))
+        }
+    }
+
+    /// Orders the kinds of nodes, so that e.g. numbers come before symbols in products
+    fn rank(&self) -> u8 {
+        match self {
+            Self::Number(_) => 0,
+            Self::Symbol(_) => 1,
+            Self::Function(..) => 2,
+            Self::Power(base, _) => base.rank(),
+            Self::Product(_) => 4,
+            Self::Sum(_) => 5
+        }
+    }
+
+    /// A string that is the same for equal nodes, used to put them in a canonical order
+    fn key(&self) -> String {
+        match self {
+            Self::Number(x) => format!("{x:?}"),
+            Self::Symbol(name) => name.clone(),
+            Self::Function(name, args) => format!("{name}({})", args.iter().map(Self::key).collect::<Vec<_>>().join(", ")),
+            Self::Sum(terms) => format!("({})", terms.iter().map(Self::key).collect::<Vec<_>>().join(" + ")),
+            Self::Product(factors) => factors.iter().map(Self::key).collect::<Vec<_>>().join(" * "),
+            Self::Power(base, exponent) => format!("{}^{}", base.key(), exponent.key())
+        }
+    }
+
+    /// The base and exponent of a factor in a product, `x` being `x^1`
+    fn base_and_exponent(self) -> (Node, Node) {
+        match self {
+            Self::Power(base, exponent) => (*base, *exponent),
+            node => (node, Self::Number(Number::int(1)))
+        }
+    }
+
+    /// The number a term is multiplied by, and the rest of the term, e.g. `3` and `x * y` for `3 * x * y`
+    fn split_coefficient(self) -> (Number, Node) {
+        match self {
+            Self::Product(mut factors) if matches!(factors.first(), Some(Self::Number(_))) => {
+                let coefficient = match factors.remove(0) {
+                    Self::Number(x) => x,
+                    _ => unreachable!()
+                };
+                let rest = if factors.len() == 1 {factors.remove(0)} else {Self::Product(factors)};
+                (coefficient, rest)
+            },
+            node => (Number::int(1), node)
+        }
+    }
+
+    fn simplify(self) -> Node {
+        match self {
+            Self::Number(_) | Self::Symbol(_) => self,
+            Self::Function(name, args) => simplify_function(name, args.into_iter().map(Self::simplify).collect()),
+            Self::Sum(terms) => simplify_sum(terms.into_iter().map(Self::simplify).collect()),
+            Self::Product(factors) => match simplify_product(factors.into_iter().map(Self::simplify).collect()) {
+                // A negated sum has its terms negated instead (`-(a - b)` is `-a + b`)
+                product @ Self::Product(_) if matches!(
+                    product.clone().split_coefficient(), (coefficient, Self::Sum(_)) if coefficient == Number::int(-1)
+                ) => simplify_sum(vec![product]),
+                product => product
+            },
+            Self::Power(base, exponent) => simplify_power(base.simplify(), exponent.simplify())
+        }
+    }
+}
+
+fn compare(a: &Node, b: &Node) -> Ordering {
+    a.rank().cmp(&b.rank()).then_with(|| a.key().cmp(&b.key()))
+}
+
+/// Adds up numbers, collects like terms (`x + 2 * x` is `3 * x`) and sorts the terms, with the constant last
+fn simplify_sum(terms: Vec<Node>) -> Node {
+    let mut constant = Number::int(0);
+    let mut collected: Vec<(Number, Node)> = Vec::new();
+
+    let mut add = |term: Node| {
+        if let Node::Number(x) = term {
+            if let Some(sum) = constant.add(x) {
+                constant = sum;
+                return
+            }
+        }
+
+        let (coefficient, rest) = term.split_coefficient();
+        match collected.iter_mut().find(|(_, other)| *other == rest) {
+            Some((total, _)) if total.add(coefficient).is_some() => *total = total.add(coefficient).unwrap(),
+            _ => collected.push((coefficient, rest))
+        }
+    };
+
+    let mut expanded = Vec::new();
+    terms.into_iter().for_each(|term| expand_term(term, &mut expanded));
+    expanded.into_iter().for_each(&mut add);
+
+    let mut terms: Vec<Node> = collected.into_iter()
+        .filter(|(coefficient, _)| !coefficient.is_zero())
+        .map(|(coefficient, rest)| simplify_product(vec![Node::Number(coefficient), rest]))
+        .collect();
+    terms.sort_by(|a, b| compare(&a.clone().split_coefficient().1, &b.clone().split_coefficient().1));
+    if !constant.is_zero() {
+        terms.push(Node::Number(constant))
+    }
+
+    match terms.len() {
+        0 => Node::Number(Number::int(0)),
+        1 => terms.remove(0),
+        _ => Node::Sum(terms)
+    }
+}
+
+/// Flattens sums within a sum, and multiplies out a number times a sum (`2 * (x + 1)` is `2 * x + 2`), so that like
+/// terms inside them can be collected
+fn expand_term(term: Node, expanded: &mut Vec<Node>) {
+    match term {
+        Node::Sum(inner) => inner.into_iter().for_each(|term| expand_term(term, expanded)),
+        term => match term.split_coefficient() {
+            (coefficient, Node::Sum(inner)) => inner.into_iter()
+                .for_each(|term| expand_term(simplify_product(vec![Node::Number(coefficient), term]), expanded)),
+            (coefficient, rest) => expanded.push(simplify_product(vec![Node::Number(coefficient), rest]))
+        }
+    }
+}
+
+/// Multiplies numbers, combines powers of the same base (`x * x^2` is `x^3`, and `x / x` cancels) and sorts the
+/// factors, with the coefficient first
+fn simplify_product(factors: Vec<Node>) -> Node {
+    let mut coefficient = Number::int(1);
+    let mut powers: Vec<(Node, Node)> = Vec::new();
+    let mut unfolded: Vec<Node> = Vec::new();
+
+    let mut flat: Vec<Node> = Vec::new();
+    for factor in factors {
+        match factor {
+            Node::Product(inner) => flat.extend(inner),
+            factor => flat.push(factor)
+        }
+    }
+
+    for factor in flat {
+        match factor {
+            Node::Number(x) => match coefficient.mul(x) {
+                Some(product) => coefficient = product,
+                None => unfolded.push(Node::Number(x))
+            },
+            factor => {
+                let (base, exponent) = factor.base_and_exponent();
+                match powers.iter_mut().find(|(other, _)| *other == base) {
+                    Some((_, total)) => *total = simplify_sum(vec![total.clone(), exponent]),
+                    None => powers.push((base, exponent))
+                }
+            }
+        }
+    }
+
+    if coefficient.is_zero() {
+        return Node::Number(Number::int(0))
+    }
+
+    let mut rest: Vec<Node> = unfolded;
+    for (base, exponent) in powers {
+        match simplify_power(base, exponent) {
+            Node::Number(x) => match coefficient.mul(x) {
+                Some(product) => coefficient = product,
+                None => rest.push(Node::Number(x))
+            },
+            Node::Product(inner) => rest.extend(inner),
+            node => rest.push(node)
+        }
+    }
+    rest.sort_by(compare);
+
+    if !coefficient.is_one() {
+        rest.insert(0, Node::Number(coefficient))
+    }
+    match rest.len() {
+        0 => Node::Number(Number::int(1)),
+        1 => rest.remove(0),
+        _ => Node::Product(rest)
+    }
+}
+
+/// Removes powers of 0 and 1, works out powers of numbers, and multiplies out integer powers of powers and products,
+/// e.g. `(2 * x^2)^3` is `8 * x^6`
+fn simplify_power(base: Node, exponent: Node) -> Node {
+    match (base, exponent) {
+        (base, Node::Number(x)) if x.is_one() => base,
+        (_, Node::Number(x)) if x.is_zero() => Node::Number(Number::int(1)),
+        (Node::Number(x), _) if x.is_one() => Node::Number(x),
+        (Node::Number(x), Node::Number(y)) => match x.pow(y) {
+            Some(power) => Node::Number(power),
+            None => Node::Power(Box::new(Node::Number(x)), Box::new(Node::Number(y)))
+        },
+        (Node::Power(base, inner), Node::Number(y)) if y.as_integer().is_some() => {
+            simplify_power(*base, simplify_product(vec![*inner, Node::Number(y)]))
+        },
+        (Node::Product(factors), Node::Number(y)) if y.as_integer().is_some() => {
+            simplify_product(factors.into_iter().map(|factor| simplify_power(factor, Node::Number(y))).collect())
+        },
+        (base, exponent) => Node::Power(Box::new(base), Box::new(exponent))
+    }
+}
+
+/// Works out functions whose values are known exactly, e.g. `ln(1)`
+fn simplify_function(name: String, args: Vec<Node>) -> Node {
+    let zero = Node::Number(Number::int(0));
+    let one = Node::Number(Number::int(1));
+
+    match (name.as_str(), &args[..]) {
+        ("ln", [x]) if *x == one => zero,
+        ("ln", [Node::Function(e, inner)]) if e == "e" && inner.is_empty() => one,
+        ("sin", [x]) if *x == zero => zero,
+        ("cos", [x]) if *x == zero => one,
+        ("exp", [x]) if *x == zero => one,
+        _ => Node::Function(name, args)
+    }
+}
+
+impl Expression {
+    /// Rewrites the expression in a simpler, canonical form: numbers are worked out, like terms collected, powers of
+    /// the same base combined and common factors cancelled, and terms and factors are put in a consistent order
+    pub fn simplify(&mut self) {
+        *self = Node::from_expression(self).simplify().into_expression()
+    }
+
+    pub fn simplified(&self) -> Self {
+        let mut simplified = self.clone();
+        simplified.simplify();
+        simplified
+    }
+}
+
+/// Parses `source` as Hawk, simplifies it and prints it, checking that simplifying the printed result changes nothing
+#[cfg(test)]
+fn simplified(source: &str) -> String {
+    use hawk_common::tree::Statement;
+    use crate::ToCASExpr;
+
+    let parse = |source: &str| {
+        let tokens = hawk_lib::lexer::Lexer::lex(source, &mut |_, _| ()).unwrap();
+        let statements = hawk_lib::parser::Parser::parse(&tokens, &mut |_, _| ()).unwrap();
+        match statements.into_iter().find(|statement| matches!(statement, Statement::Expression(_))) {
+            Some(Statement::Expression(expr)) => expr.to_cas_expr().unwrap(),
+            _ => panic!("expected an expression")
+        }
+    };
+
+    let result = parse(source).simplified().to_string();
+    assert_eq!(parse(&result).simplified().to_string(), result, "simplifying {result} again changed it");
+    result
+}
+
+#[test]
+fn substitution() {
+    let x = Expression::from_literal(Literal::Int(3));
+    let y = Expression::symbol(String::from("y"));
+    let expr = Expression::symbol(String::from("x")).product(Expression::symbol(String::from("x"))).sum(y.clone());
+
+    assert_eq!(expr.substitute("x", &x).simplified().to_string(), "y + 9");
+    assert_eq!(expr.substitute("x", &y).simplified().to_string(), "y + y^2");
+    assert_eq!(expr.substitute("z", &x), expr);
+}
+
+#[test]
+fn constant_folding() {
+    assert_eq!(simplified("1 + 2 * 3"), "7");
+    assert_eq!(simplified("6 / 4"), "3 / 2");
+    assert_eq!(simplified("0.5 + 1 / 4"), "0.75");
+    assert_eq!(simplified("2^10"), "1024");
+    assert_eq!(simplified("2^-2"), "1 / 4");
+    assert_eq!(simplified("4^(1 / 2)"), "2");
+    assert_eq!(simplified("2^(1 / 2)"), "2^(1 / 2)");
+    assert_eq!(simplified("ln(1) + cos(0)"), "1");
+}
+
+#[test]
+fn identities() {
+    assert_eq!(simplified("x * 1"), "x");
+    assert_eq!(simplified("x + 0"), "x");
+    assert_eq!(simplified("x^1"), "x");
+    assert_eq!(simplified("x^0"), "1");
+    assert_eq!(simplified("0 * x + y"), "y");
+    assert_eq!(simplified("1^x"), "1");
+}
+
+#[test]
+fn like_terms_and_powers() {
+    assert_eq!(simplified("x + x"), "2 * x");
+    assert_eq!(simplified("2 * x - x"), "x");
+    assert_eq!(simplified("x - x"), "0");
+    assert_eq!(simplified("3 * x * y + y * x"), "4 * x * y");
+    assert_eq!(simplified("x * x"), "x^2");
+    assert_eq!(simplified("x^2 * x^3"), "x^5");
+    assert_eq!(simplified("(x^2)^3"), "x^6");
+    assert_eq!(simplified("(2 * x)^2"), "4 * x^2");
+    assert_eq!(simplified("a^b * a^c"), "a^(b + c)");
+}
+
+#[test]
+fn cancelling() {
+    assert_eq!(simplified("x * y / x"), "y");
+    assert_eq!(simplified("6 * x / (3 * x^2)"), "2 / x");
+    assert_eq!(simplified("x / 2"), "x / 2");
+    assert_eq!(simplified("(a + b) / (a + b)"), "1");
+    assert_eq!(simplified("-x / -y"), "x / y");
+}
+
+#[test]
+fn canonical_order() {
+    assert_eq!(simplified("y + x"), "x + y");
+    assert_eq!(simplified("1 + x"), "x + 1");
+    assert_eq!(simplified("z * 2 * y"), "2 * y * z");
+    assert_eq!(simplified("sin(x) * x"), "x * sin(x)");
+    assert_eq!(simplified("b - a"), "-a + b");
+    assert_eq!(simplified("0 - x^2"), "-(x^2)");
+    assert_eq!(simplified("y + 2 * (x + 1) - 2"), "2 * x + y");
+    assert_eq!(simplified("(x + 1) * (1 + x)"), "(x + 1)^2");
+    assert_eq!(simplified("-(a - b)"), "-a + b");
+    assert_eq!(simplified("-(x + 1) + x"), "-1");
+}
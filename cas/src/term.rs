@@ -1,3 +1,5 @@
+use std::fmt::{Display, Formatter, Result};
+
 use crate::{factor::*, equation::*, terminal::*, expression::*};
 
 #[derive(Debug, PartialEq, Clone)]
@@ -24,6 +26,19 @@ impl Term {
         Self::Div{op1, op2}
     }
 
+    /// Create a term from an expression, without nesting it if it is already a single term
+    pub fn from_expression(expr: Expression) -> Self {
+        match expr {
+            Expression::Term(term) => term,
+            expr => Self::Expression(Box::new(expr)),
+        }
+    }
+
+    /// `-expr`, stored as `-1 * expr`
+    pub fn negate(expr: Expression) -> Self {
+        Self::Factors(vec![Factor::from_literal(Literal::Int(-1)), Factor::from_expression(expr)])
+    }
+
     pub fn contains(&self, var: String) -> bool {
         match self {
             Self::Factors(factors) => {
@@ -39,6 +54,70 @@ impl Term {
             Self::Div{op1, op2} => op1.contains(var.clone()) || op2.contains(var)
         }
     }
+
+    /// Adds the names of the variables in the term to `symbols`
+    pub fn symbols(&self, symbols: &mut Vec<String>) {
+        match self {
+            Self::Factors(factors) => factors.iter().for_each(|factor| factor.symbols(symbols)),
+            Self::Expression(expr) => expr.symbols(symbols),
+            Self::Div{op1, op2} => {
+                op1.symbols(symbols);
+                op2.symbols(symbols)
+            }
+        }
+    }
+
+    /// Replaces every use of the variable `var` with `value`
+    pub fn substitute(&self, var: &str, value: &Expression) -> Self {
+        match self {
+            Self::Factors(factors) => Self::Factors(factors.iter().map(|factor| factor.substitute(var, value)).collect()),
+            Self::Expression(expr) => Self::Expression(Box::new(expr.substitute(var, value))),
+            Self::Div{op1, op2} => Self::Div{op1: op1.substitute(var, value), op2: op2.substitute(var, value)}
+        }
+    }
+}
+
+impl Display for Term {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            Self::Factors(factors) => {
+                // A coefficient of -1 is printed as a minus sign
+                let (factors, negated) = match &factors[..] {
+                    [Factor::Expression(first), rest @ ..] if !rest.is_empty() && **first == Expression::Literal(Literal::Int(-1)) => {
+                        write!(f, "-")?;
+                        (rest, true)
+                    },
+                    _ => (&factors[..], false)
+                };
+                for (index, factor) in factors.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, " * ")?
+                    }
+                    match factor {
+                        Factor::Expression(expr) if index > 0 && !expr.is_atomic() && !matches!(**expr, Expression::Term(Term::Factors(_))) => {
+                            write!(f, "({expr})")?
+                        },
+                        Factor::Expression(expr) if matches!(**expr, Expression::Terms(_)) => write!(f, "({expr})")?,
+                        // Hawk's minus sign binds tighter than `^`, so `-x^2` would be `(-x)^2`
+                        Factor::Exponent{..} if index == 0 && negated => write!(f, "({factor})")?,
+                        factor => write!(f, "{factor}")?
+                    }
+                }
+                Ok(())
+            },
+            Self::Div{op1, op2} => {
+                match op1 {
+                    Factor::Expression(expr) if matches!(**expr, Expression::Terms(_)) => write!(f, "({op1})")?,
+                    _ => write!(f, "{op1}")?
+                }
+                match op2 {
+                    Factor::Expression(expr) if !expr.is_atomic() => write!(f, " / ({op2})"),
+                    _ => write!(f, " / {op2}")
+                }
+            },
+            Self::Expression(expr) => write!(f, "{expr}")
+        }
+    }
 }
 
 #[test]
@@ -1,6 +1,21 @@
+use std::fmt::{Display, Formatter, Result};
+
+use rust_decimal::Decimal;
+
+/// A number or a variable, the smallest part of an expression
 #[derive(PartialEq, Debug, Clone)]
 pub enum Literal {
     Int(i128),
-    Decimal(f64),
+    Decimal(Decimal),
     Symbol(String)
-}
\ No newline at end of file
+}
+
+impl Display for Literal {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            Self::Int(x) => write!(f, "{x}"),
+            Self::Decimal(x) => write!(f, "{}", x.normalize()),
+            Self::Symbol(name) => write!(f, "{name}")
+        }
+    }
+}
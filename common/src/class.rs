@@ -0,0 +1,34 @@
+use std::collections::HashMap;
+
+use crate::object::Object;
+use crate::tree::Expression;
+
+/// A user defined type, declared with `class`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Class {
+    pub name: String,
+    /// The class this one inherits from, if any
+    pub parent: Option<Box<Class>>,
+    /// Fields, with the expressions that give their initial values
+    pub fields: Vec<(String, Expression)>,
+    /// Methods, stored as `Object::Function`s
+    pub methods: HashMap<String, Object>,
+}
+
+impl Class {
+    /// Finds a method in this class or the classes it inherits from, along with the class it is defined in
+    pub fn find_method(&self, name: &str) -> Option<(&Object, &Class)> {
+        match self.methods.get(name) {
+            Some(method) => Some((method, self)),
+            None => self.parent.as_ref()?.find_method(name),
+        }
+    }
+
+    /// All fields of the class, including inherited ones, with those of parent classes first
+    pub fn all_fields(&self) -> Vec<(String, Expression)> {
+        let mut fields = self.parent.as_ref().map(|parent| parent.all_fields()).unwrap_or_default();
+        fields.extend(self.fields.iter().cloned());
+        fields
+    }
+}
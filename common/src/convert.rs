@@ -0,0 +1,317 @@
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::fmt::{Display, Formatter, Result};
+
+use rust_decimal::prelude::*;
+
+use crate::class::Class;
+use crate::object::Object;
+use crate::tree::Expression;
+
+/// Name of the class of the instances `HashMap`s are converted to
+const RECORD_CLASS: &str = "Record";
+
+/// An `Object` that couldn't be converted to a Rust type
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConversionError {
+    /// Describes the type it should have been, e.g. "a number"
+    pub expected: &'static str,
+    /// The value that was given instead, boxed since a `ConversionError` is returned from every conversion
+    pub found: Box<Object>,
+}
+
+impl ConversionError {
+    fn new(expected: &'static str, found: Object) -> Self {
+        ConversionError { expected, found: Box::new(found) }
+    }
+}
+
+impl Display for ConversionError {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "Expected {}, found {}", self.expected, self.found.type_name())
+    }
+}
+
+impl std::error::Error for ConversionError {}
+
+/// Converting an `Object` to itself can't fail, but lets `Object` be used wherever a conversion is expected
+impl From<Infallible> for ConversionError {
+    fn from(never: Infallible) -> Self {
+        match never {}
+    }
+}
+
+/// A value with an uncertainty, e.g. a measurement. Converting it to an `Object` makes its uncertainty a new
+/// independent error source
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Measurement {
+    pub value: Decimal,
+    pub uncertainty: Decimal,
+}
+
+impl Measurement {
+    pub fn new(value: Decimal, uncertainty: Decimal) -> Self {
+        Measurement { value, uncertainty }
+    }
+}
+
+impl From<Measurement> for Object {
+    fn from(x: Measurement) -> Self {
+        Object::uncertain(x.value, x.uncertainty)
+    }
+}
+
+/// Uncertain values, and exact numbers, which have no uncertainty
+impl TryFrom<Object> for Measurement {
+    type Error = ConversionError;
+
+    fn try_from(object: Object) -> std::result::Result<Self, Self::Error> {
+        match object {
+            Object::Uncertain { value, uncertainty, .. } => Ok(Measurement::new(value, uncertainty)),
+            Object::Int(_) | Object::Decimal(_) => Ok(Measurement::new(Decimal::try_from(object)?, Decimal::ZERO)),
+            _ => Err(ConversionError::new("a number", object)),
+        }
+    }
+}
+
+macro_rules! integer_conversions {
+    ($($type:ty),*) => {
+        $(
+            impl From<$type> for Object {
+                fn from(x: $type) -> Self {
+                    Object::Int(x as i128)
+                }
+            }
+
+            impl TryFrom<Object> for $type {
+                type Error = ConversionError;
+
+                fn try_from(object: Object) -> std::result::Result<Self, Self::Error> {
+                    match object {
+                        Object::Int(x) => <$type>::try_from(x).map_err(|_| ConversionError::new(
+                            concat!("an integer that fits in ", stringify!($type)), object
+                        )),
+                        _ => Err(ConversionError::new("an integer", object)),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+integer_conversions!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, usize);
+
+impl From<Decimal> for Object {
+    fn from(x: Decimal) -> Self {
+        Object::Decimal(x)
+    }
+}
+
+/// Integers and decimals. Uncertain values are refused so that their uncertainty isn't silently lost, see
+/// `Measurement`
+impl TryFrom<Object> for Decimal {
+    type Error = ConversionError;
+
+    fn try_from(object: Object) -> std::result::Result<Self, Self::Error> {
+        match object {
+            Object::Int(x) => Decimal::from_i128(x).ok_or(ConversionError::new("a number that fits in a decimal", object)),
+            Object::Decimal(x) => Ok(x),
+            _ => Err(ConversionError::new("a number", object)),
+        }
+    }
+}
+
+/// `NaN` and infinities can't be stored as decimals, and become `Null`
+impl From<f64> for Object {
+    fn from(x: f64) -> Self {
+        Decimal::from_f64(x).map(Object::Decimal).unwrap_or(Object::Null)
+    }
+}
+
+impl TryFrom<Object> for f64 {
+    type Error = ConversionError;
+
+    fn try_from(object: Object) -> std::result::Result<Self, Self::Error> {
+        let found = object.clone();
+        Decimal::try_from(object)?.to_f64().ok_or(ConversionError::new("a number", found))
+    }
+}
+
+impl From<bool> for Object {
+    fn from(x: bool) -> Self {
+        Object::Boolean(x)
+    }
+}
+
+impl TryFrom<Object> for bool {
+    type Error = ConversionError;
+
+    fn try_from(object: Object) -> std::result::Result<Self, Self::Error> {
+        match object {
+            Object::Boolean(x) => Ok(x),
+            _ => Err(ConversionError::new("a boolean", object)),
+        }
+    }
+}
+
+impl From<String> for Object {
+    fn from(x: String) -> Self {
+        Object::String(x)
+    }
+}
+
+impl From<&str> for Object {
+    fn from(x: &str) -> Self {
+        Object::String(String::from(x))
+    }
+}
+
+impl TryFrom<Object> for String {
+    type Error = ConversionError;
+
+    fn try_from(object: Object) -> std::result::Result<Self, Self::Error> {
+        match object {
+            Object::String(x) => Ok(x),
+            _ => Err(ConversionError::new("a string", object)),
+        }
+    }
+}
+
+impl From<()> for Object {
+    fn from(_: ()) -> Self {
+        Object::Null
+    }
+}
+
+impl<T: Into<Object>> From<Vec<T>> for Object {
+    fn from(x: Vec<T>) -> Self {
+        Object::Array(x.into_iter().map(Into::into).collect())
+    }
+}
+
+/// Arrays and columns. If an item can't be converted, the error is about that item
+impl<T> TryFrom<Object> for Vec<T>
+where
+    T: TryFrom<Object>,
+    ConversionError: From<T::Error>,
+{
+    type Error = ConversionError;
+
+    fn try_from(object: Object) -> std::result::Result<Self, Self::Error> {
+        match object {
+            Object::Array(items) | Object::Column(items) => items.into_iter()
+                .map(|item| T::try_from(item).map_err(ConversionError::from))
+                .collect(),
+            _ => Err(ConversionError::new("an array", object)),
+        }
+    }
+}
+
+/// Becomes an instance with a field for each key, which Hawk code can read with `.`
+impl<T: Into<Object>> From<HashMap<String, T>> for Object {
+    fn from(x: HashMap<String, T>) -> Self {
+        let fields: HashMap<String, Object> = x.into_iter().map(|(name, value)| (name, value.into())).collect();
+
+        let mut names: Vec<&String> = fields.keys().collect();
+        names.sort();
+        let class = Class {
+            name: String::from(RECORD_CLASS),
+            parent: None,
+            fields: names.into_iter().map(|name| (name.clone(), Expression::Literal(Object::Null))).collect(),
+            methods: HashMap::new(),
+        };
+        Object::Instance { class: Box::new(class), fields }
+    }
+}
+
+/// The fields of an instance, or the columns of a data table by name
+impl<T> TryFrom<Object> for HashMap<String, T>
+where
+    T: TryFrom<Object>,
+    ConversionError: From<T::Error>,
+{
+    type Error = ConversionError;
+
+    fn try_from(object: Object) -> std::result::Result<Self, Self::Error> {
+        match object {
+            Object::Instance { fields, .. } => fields.into_iter()
+                .map(|(name, value)| Ok((name, T::try_from(value)?)))
+                .collect(),
+            Object::DataTable { names, data } => names.into_iter().zip(data)
+                .map(|(name, column)| Ok((name, T::try_from(column)?)))
+                .collect(),
+            _ => Err(ConversionError::new("an instance or data table", object)),
+        }
+    }
+}
+
+#[test]
+fn conversions() {
+    use rust_decimal_macros::dec;
+
+    assert_eq!(i64::try_from(Object::Int(3)), Ok(3));
+    assert_eq!(Object::from(3_u8), Object::Int(3));
+    assert_eq!(u8::try_from(Object::Int(300)).unwrap_err().expected, "an integer that fits in u8");
+    assert_eq!(Decimal::try_from(Object::Int(2)), Ok(dec!(2)));
+    assert_eq!(f64::try_from(Object::Decimal(dec!(0.5))), Ok(0.5));
+    assert_eq!(String::try_from(Object::from("text")), Ok(String::from("text")));
+    assert_eq!(bool::try_from(Object::Boolean(true)), Ok(true));
+
+    let error = Decimal::try_from(Object::uncertain(dec!(1), dec!(0.1))).unwrap_err();
+    assert_eq!(error.to_string(), "Expected a number, found uncertain value");
+
+    let array = Object::from(vec![1_i64, 2, 3]);
+    assert_eq!(array, Object::Array(vec![Object::Int(1), Object::Int(2), Object::Int(3)]));
+    assert_eq!(Vec::<i64>::try_from(array), Ok(vec![1, 2, 3]));
+    let error = Vec::<i64>::try_from(Object::Column(vec![Object::Int(1), Object::from("x")])).unwrap_err();
+    assert_eq!(*error.found, Object::from("x"));
+
+    assert_eq!(i64::try_from(Object::Int(1)), Ok(1));
+}
+
+#[test]
+fn measurements_and_records() {
+    use rust_decimal_macros::dec;
+
+    let measurement = Measurement::try_from(Object::uncertain(dec!(1.5), dec!(0.1))).unwrap();
+    assert_eq!(measurement, Measurement::new(dec!(1.5), dec!(0.1)));
+    assert_eq!(Measurement::try_from(Object::Int(2)), Ok(Measurement::new(dec!(2), dec!(0))));
+    assert!(matches!(Object::from(measurement), Object::Uncertain { .. }));
+
+    let record = Object::from(HashMap::from([(String::from("g"), dec!(9.81))]));
+    let fields: HashMap<String, Decimal> = record.try_into().unwrap();
+    assert_eq!(fields.get("g"), Some(&dec!(9.81)));
+
+    let table = Object::DataTable { names: vec![String::from("t")], data: vec![Object::Column(vec![Object::Int(1)])] };
+    let columns: HashMap<String, Vec<i64>> = table.try_into().unwrap();
+    assert_eq!(columns.get("t"), Some(&vec![1]));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_round_trip() {
+    use rust_decimal_macros::dec;
+
+    use crate::token::TokenType;
+    use crate::tree::Statement;
+
+    let body = Statement::Return(Box::new(Expression::Binary {
+        operand1: Box::new(Expression::Literal(Object::Identifier(String::from("x")))),
+        operand2: Box::new(Expression::Literal(Object::Decimal(dec!(1.5)))),
+        operator: TokenType::Asterisk,
+    }));
+    let function = Object::Function { params: vec![String::from("x")], block: Box::new(body.clone()), captured: HashMap::new() };
+
+    let json = serde_json::to_string(&function).unwrap();
+    match serde_json::from_str::<Object>(&json).unwrap() {
+        Object::Function { params, block, .. } => {
+            assert_eq!(params, vec![String::from("x")]);
+            assert_eq!(*block, body);
+        },
+        x => panic!("expected a function, found {x:?}"),
+    }
+
+    let uncertain = Object::uncertain(dec!(2), dec!(0.1));
+    let json = serde_json::to_string(&uncertain).unwrap();
+    assert!(matches!(serde_json::from_str::<Object>(&json).unwrap(), Object::Uncertain { sources, .. } if sources.0.len() == 1));
+}
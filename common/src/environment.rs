@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+
+use crate::error::HawkError;
+
+/// Everything Hawk code does outside of the interpreter: reading and writing files, and showing output, warnings and
+/// errors. Implemented by whatever runs Hawk, e.g. the terminal, a web page or a Rust program embedding it
+pub trait Environment {
+    /// Reads a whole file, or gives a message saying why it couldn't be read
+    fn read_file(&mut self, name: &str) -> Result<String, String>;
+    /// Writes `contents` to a file, replacing it if it exists
+    fn write_file(&mut self, name: &str, contents: &str) -> Result<(), String>;
+    /// Shows the output of `print`, or of an expression in the REPL
+    fn output(&mut self, text: String);
+    /// Shows a warning on `line` (0 in the REPL)
+    fn warn(&mut self, message: String, line: usize);
+    /// Shows an error that doesn't stop the code, e.g. one in a file that couldn't be imported
+    fn error(&mut self, error: HawkError);
+}
+
+/// An `Environment` that keeps everything in memory. Files are read from and written to `files`, and output, warnings
+/// and errors are collected so that they can be checked afterwards, e.g. in tests
+#[derive(Debug, Clone, Default)]
+pub struct Memory {
+    pub files: HashMap<String, String>,
+    pub output: Vec<String>,
+    /// Warnings with the lines they are on
+    pub warnings: Vec<(String, usize)>,
+    pub errors: Vec<HawkError>,
+}
+
+impl Memory {
+    pub fn new() -> Self {
+        Memory::default()
+    }
+
+    /// Adds a file that Hawk code can read
+    pub fn with_file(mut self, name: &str, contents: &str) -> Self {
+        self.files.insert(String::from(name), String::from(contents));
+        self
+    }
+}
+
+impl Environment for Memory {
+    fn read_file(&mut self, name: &str) -> Result<String, String> {
+        self.files.get(name).cloned().ok_or(format!("No file called {name}"))
+    }
+
+    fn write_file(&mut self, name: &str, contents: &str) -> Result<(), String> {
+        self.files.insert(String::from(name), String::from(contents));
+        Ok(())
+    }
+
+    fn output(&mut self, text: String) {
+        self.output.push(text)
+    }
+
+    fn warn(&mut self, message: String, line: usize) {
+        self.warnings.push((message, line))
+    }
+
+    fn error(&mut self, error: HawkError) {
+        self.errors.push(error)
+    }
+}
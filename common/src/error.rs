@@ -0,0 +1,229 @@
+use std::fmt::{Display, Formatter, Result};
+
+/// What went wrong, used to label an error when it is shown
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ErrorKind {
+    /// Code that couldn't be parsed
+    Syntax,
+    /// Something that went wrong while running code
+    Runtime,
+    /// A file that couldn't be read or written
+    Io,
+    /// Hawk was started with the wrong arguments
+    Usage,
+}
+
+impl ErrorKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ErrorKind::Syntax => "syntax error",
+            ErrorKind::Runtime => "error",
+            ErrorKind::Io => "file error",
+            ErrorKind::Usage => "usage error",
+        }
+    }
+
+    /// The name used for the kind in Hawk code, e.g. `error.kind == "io"` in a `catch` block
+    pub fn id(&self) -> &'static str {
+        match self {
+            ErrorKind::Syntax => "syntax",
+            ErrorKind::Runtime => "runtime",
+            ErrorKind::Io => "io",
+            ErrorKind::Usage => "usage",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        match id {
+            "syntax" => Some(ErrorKind::Syntax),
+            "runtime" => Some(ErrorKind::Runtime),
+            "io" => Some(ErrorKind::Io),
+            "usage" => Some(ErrorKind::Usage),
+            _ => None,
+        }
+    }
+}
+
+/// Where in the source code an error is. Lines and columns count from 1, and 0 means that it isn't known. They are
+/// stored as `u32` to keep `HawkError` small, since it is returned from almost every function
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Span {
+    pub line: u32,
+    pub column: u32,
+    /// Number of characters to underline
+    pub length: u32,
+}
+
+impl Span {
+    pub fn new(line: usize, column: usize, length: usize) -> Self {
+        Span { line: line as u32, column: column as u32, length: length as u32 }
+    }
+}
+
+/// A span covering a whole line, for errors where only the line is known
+impl From<usize> for Span {
+    fn from(line: usize) -> Self {
+        Span { line: line as u32, column: 0, length: 0 }
+    }
+}
+
+/// A call to a Hawk function that was running when an error happened
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    /// Name of the function, or e.g. `Particle.init` for a method
+    pub function: String,
+    /// Line the function was called from
+    pub line: usize,
+    /// File the function was called from, or `None` for the REPL
+    pub file: Option<String>,
+}
+
+impl Display for Frame {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match &self.file {
+            Some(file) => write!(f, "{}, called from {file}:{}", self.function, self.line),
+            None => write!(f, "{}, called from line {}", self.function, self.line),
+        }
+    }
+}
+
+/// An error in Hawk code, with everything needed to show the user where it is
+#[derive(Debug, Clone, PartialEq)]
+pub struct HawkError {
+    pub kind: ErrorKind,
+    pub message: String,
+    /// File the code is from, or `None` for the REPL
+    pub file: Option<String>,
+    pub span: Span,
+    /// Extra information shown after the error, e.g. how to fix it
+    pub notes: Vec<String>,
+    /// The line of code the error is on. Filled in once the source code is known
+    pub source_line: Option<String>,
+    /// The function calls that led to the error, outermost first. It is boxed to keep `HawkError` small, and is
+    /// `None` if the error didn't happen in a function
+    pub trace: Option<Box<Vec<Frame>>>,
+}
+
+impl HawkError {
+    pub fn new(kind: ErrorKind, message: String, span: impl Into<Span>) -> Self {
+        HawkError { kind, message, file: None, span: span.into(), notes: Vec::new(), source_line: None, trace: None }
+    }
+
+    pub fn syntax(message: String, span: impl Into<Span>) -> Self {
+        HawkError::new(ErrorKind::Syntax, message, span)
+    }
+
+    pub fn runtime(message: String, span: impl Into<Span>) -> Self {
+        HawkError::new(ErrorKind::Runtime, message, span)
+    }
+
+    pub fn io(message: String, span: impl Into<Span>) -> Self {
+        HawkError::new(ErrorKind::Io, message, span)
+    }
+
+    pub fn line(&self) -> usize {
+        self.span.line as usize
+    }
+
+    pub fn with_note(mut self, note: String) -> Self {
+        self.notes.push(note);
+        self
+    }
+
+    /// Sets the function calls that led to the error, unless a call further in already set them
+    pub fn with_trace(mut self, trace: &[Frame]) -> Self {
+        if self.trace.is_none() && !trace.is_empty() {
+            self.trace = Some(Box::new(trace.to_vec()))
+        }
+        self
+    }
+
+    /// The function calls that led to the error, outermost first, or nothing if it didn't happen in a function
+    pub fn frames(&self) -> &[Frame] {
+        self.trace.as_deref().map_or(&[], Vec::as_slice)
+    }
+
+    /// Describes the function calls that led to the error, most recent last, e.g.
+    ///
+    /// ```text
+    /// traceback (most recent call last):
+    ///     mean, called from line 12
+    ///     sum, called from line 4
+    /// ```
+    ///
+    /// This is empty if the error didn't happen in a function
+    pub fn traceback(&self) -> String {
+        if self.frames().is_empty() {
+            return String::new()
+        }
+        let mut text = String::from("traceback (most recent call last):\n");
+        for frame in self.frames() {
+            text += &format!("    {frame}\n");
+        }
+        text
+    }
+
+    /// Sets the line of an error that was made without knowing where it is
+    pub fn at_line(mut self, line: usize) -> Self {
+        if self.span.line == 0 {
+            self.span = Span::from(line)
+        }
+        self
+    }
+
+    /// Sets the file the error is in, unless it is already known (e.g. for an error in an imported file)
+    pub fn in_file(mut self, file: String) -> Self {
+        if self.file.is_none() {
+            self.file = Some(file)
+        }
+        self
+    }
+
+    /// Keeps the line of `source` the error is on, so that it can be shown with the error
+    pub fn with_source(mut self, source: &str) -> Self {
+        if self.source_line.is_none() && self.span.line > 0 {
+            self.source_line = source.lines().nth(self.span.line as usize - 1).map(String::from)
+        }
+        self
+    }
+}
+
+/// A short, one line description of the error. `hawk_cli_io::error::render` shows it with the code it is in
+impl Display for HawkError {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        if self.span.line == 0 {
+            write!(f, "Error: {}", self.message)
+        } else {
+            write!(f, "Error on line {}: {}", self.span.line, self.message)
+        }
+    }
+}
+
+#[test]
+fn error_source() {
+    let error = HawkError::syntax(String::from("Expected ')'"), Span::new(2, 5, 1))
+        .with_source("let a = 1\nlet b = (2\nprint b")
+        .in_file(String::from("a.hawk"))
+        .in_file(String::from("b.hawk"));
+
+    assert_eq!(error.source_line, Some(String::from("let b = (2")));
+    assert_eq!(error.file, Some(String::from("a.hawk")));
+    assert_eq!(error.to_string(), "Error on line 2: Expected ')'");
+    assert_eq!(HawkError::runtime(String::from("x"), 0).at_line(4).line(), 4);
+}
+
+#[test]
+fn error_traceback() {
+    let outer = [Frame { function: String::from("mean"), line: 12, file: Some(String::from("a.hawk")) }];
+    let inner = [outer[0].clone(), Frame { function: String::from("sum"), line: 4, file: None }];
+
+    // The innermost call that sees the error sets the trace
+    let error = HawkError::runtime(String::from("Can't add"), 4).with_trace(&inner).with_trace(&outer);
+
+    assert_eq!(error.frames(), inner);
+    assert_eq!(error.traceback(), "traceback (most recent call last):
+    mean, called from a.hawk:12
+    sum, called from line 4
+");
+    assert_eq!(HawkError::runtime(String::from("x"), 1).traceback(), "");
+}
@@ -1,5 +1,11 @@
+pub mod class;
+pub mod convert;
+pub mod environment;
+pub mod error;
 pub mod object;
 pub mod token;
 pub mod tree;
+pub mod uncertainty;
+pub mod units;
 
 pub use float_cmp;
\ No newline at end of file
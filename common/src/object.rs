@@ -1,14 +1,20 @@
-use std::{fmt::{Display, Error, Formatter, self}, i128};
+use std::{collections::HashMap, fmt::{Display, Error, Formatter, self}, i128};
 
 use rust_decimal::prelude::*;
+use rust_decimal_macros::dec;
 
 use term_table::row::Row;
 use term_table::{Table, TableStyle};
 
+use crate::class::Class;
+use crate::error::HawkError;
 use crate::tree::{Statement, Expression};
+use crate::uncertainty::ErrorSources;
+use crate::units::Unit;
 
 /// The structure that stores literals through all stages of the interpreter (from lexing to evaluating)
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Object {
     /// Null object
     Null,
@@ -19,11 +25,15 @@ pub enum Object {
     String(String),
     /// A boolean (given by comparison operators or the keywords `true` and `false`)
     Boolean(bool),
-    /// A number stored as 64 bit float with an uncertainty (also stored as 64 bit float)
-    Uncertain{value: Decimal, uncertainty: Decimal},
+    /// A number stored as 64 bit float with an uncertainty (also stored as 64 bit float). `sources` records which
+    /// independent error sources the uncertainty comes from, so that correlated values (e.g. `x - x`) cancel
+    Uncertain{value: Decimal, uncertainty: Decimal, sources: ErrorSources},
+    /// A number (`Int`, `Decimal` or `Uncertain`) with a physical unit, e.g. `9.81 m/s^2`
+    Quantity{value: Box<Object>, unit: Unit},
     /// A function object that is stored in the scope where the function is defined, with parameter
-    /// names as a vector of strings and the code of the actual function as a Statement
-    Function{params: Vec<String>, block: Box<Statement>},
+    /// names as a vector of strings and the code of the actual function as a Statement. `captured` holds the local
+    /// variables from where the function was defined, so that closures (e.g. `(x) => x * k`) can use them later
+    Function{params: Vec<String>, block: Box<Statement>, captured: HashMap<String, Object>},
     Finder(Vec<(Expression, Expression)>),
     /// An array of any other kind of object (types can be mixed)
     Array(Vec<Object>),
@@ -32,7 +42,14 @@ pub enum Object {
     /// A column of data in a datatable
     Column(Vec<Object>),
     /// A datatable, created by processing a CSV file
-    DataTable{names: Vec<String>, data:Vec<Object>}
+    DataTable{names: Vec<String>, data:Vec<Object>},
+    /// A class, which creates an instance when called like a function
+    Class(Class),
+    /// An instance of a class, with the current values of its fields
+    Instance{class: Box<Class>, fields: HashMap<String, Object>},
+    /// An unevaluated formula, e.g. `sym(x^2 + 2 * x)`, where variables are symbols rather than values. It is kept
+    /// simplified by `hawk_cas`
+    Symbolic(Box<Expression>)
 }
 
 // Display object to Rust source code, used to build the standard library
@@ -44,15 +61,18 @@ impl Display for Object {
             Self::Decimal(x) => write!(f, "Object::Decimal(dec!({}))", x.normalize()),
             Self::String(x) => write!(f, "Object::String(\"{}\".to_owned())", x),
             Self::Boolean(x) => write!(f, "Object::Boolean({})", x),
-            Self::Uncertain{value, uncertainty} => write!(f, "Object::Uncertain{{value: {}, uncertainty: {}}}", value, uncertainty),
-            Self::Function{params, block} => {
-                if params.len() != 0 {write!(f, "Object::Function{{params: vec!{:?}.iter().map(std::string::ToString::to_string).collect(), block: Box::new({})}}", params, block)}
-                else {write!(f, "Object::Function{{params: Vec::new(), block: Box::new({})}}", block)}},
+            Self::Uncertain{value, uncertainty, sources: _} => write!(f, "Object::Uncertain{{value: dec!({}), uncertainty: dec!({}), sources: Default::default()}}", value, uncertainty),
+            Self::Quantity{value, unit} => write!(f, "Object::Quantity{{value: Box::new({}), unit: hawk_common::units::Unit(vec!{:?}.into_iter().map(|(symbol, power): (&str, i32)| (symbol.to_owned(), power)).collect())}}", value, unit.0),
+            Self::Function{params, block, ..} => {
+                if params.len() != 0 {write!(f, "Object::Function{{params: vec!{:?}.iter().map(std::string::ToString::to_string).collect(), block: Box::new({}), captured: std::collections::HashMap::new()}}", params, block)}
+                else {write!(f, "Object::Function{{params: Vec::new(), block: Box::new({}), captured: std::collections::HashMap::new()}}", block)}},
             Self::Array(x) => write!(f, "Object::Array({})", Objects(x.clone())),
             Self::Identifier(x) => write!(f, "Object::Identifier(\"{}\".to_owned())", x),
             Self::Column(data) => write!(f, "Object::Column({})", Objects(data.clone())),
             Self::DataTable{names, data} => write!(f, "Object::DataTable{{ names: vec!{:?}.iter().map(|x| x.to_owned()).collect(), data: {} }}", names, Objects(data.clone())),
             Self::Finder(equations) => write!(f, "Object::Finder(vec![{}])", Equations(equations.to_owned())),
+            Self::Class(_) | Self::Instance{..} => write!(f, "CAN'T USE CLASSES IN STD YET"),
+            Self::Symbolic(expr) => write!(f, "Object::Symbolic(Box::new({}))", expr),
         }
     }
 }
@@ -98,16 +118,22 @@ impl PartialEq for Object {
                 let dec: &rust_decimal::Decimal = &(*a as i64).into();
                 dec == b
             },
-            (Uncertain { value, uncertainty }, Int(i)) 
-            | (Int(i), Uncertain { value, uncertainty }) => (value + uncertainty) > (*i as i64).into() && (value - uncertainty) < (*i as i64).into(),
+            (Uncertain { value, uncertainty, .. }, Int(i)) 
+            | (Int(i), Uncertain { value, uncertainty, .. }) => (value + uncertainty) > (*i as i64).into() && (value - uncertainty) < (*i as i64).into(),
             (String(a), String(b)) => a == b,
-            (Uncertain { value: v1, uncertainty: u1 }, Uncertain { value: v2, uncertainty: u2 }) => v1 + u1 > v2 - u2 && v2 + u2 > v1 - u1,
+            (Boolean(a), Boolean(b)) => a == b,
+            (Identifier(a), Identifier(b)) => a == b,
+            (Uncertain { value: v1, uncertainty: u1, .. }, Uncertain { value: v2, uncertainty: u2, .. }) => v1 + u1 > v2 - u2 && v2 + u2 > v1 - u1,
+            (Quantity { value: v1, unit: u1 }, Quantity { value: v2, unit: u2 }) => v1 == v2 && u1 == u2,
             (Array(a), Array(b)) => {
                 compare_vec_obj(a, b)
             },
             (Column(a), Column(b)) => {
                 compare_vec_obj(a, b)
             },
+            (Class(a), Class(b)) => a == b,
+            (Instance { class: c1, fields: f1 }, Instance { class: c2, fields: f2 }) => c1.name == c2.name && f1 == f2,
+            (Symbolic(a), Symbolic(b)) => a == b,
             (Null, Null) => true,
             _ => false
         }
@@ -124,6 +150,27 @@ fn compare_vec_obj(a: &Vec<Object>, b: &Vec<Object>) -> bool {
     x
 }
 
+/// Formats a number for output, switching to scientific notation (e.g. `1.38e-23`) when it is very large or very small
+pub fn format_decimal(x: Decimal) -> String {
+    let x = x.normalize();
+    let magnitude = x.abs();
+
+    if !magnitude.is_zero() && (magnitude >= dec!(1e10) || magnitude < dec!(1e-5)) {
+        let digits = x.mantissa().unsigned_abs().to_string();
+        let exponent = digits.len() as i64 - 1 - x.scale() as i64;
+        let significant = digits.trim_end_matches('0');
+        let sign = if x.is_sign_negative() {"-"} else {""};
+
+        if significant.len() > 1 {
+            format!("{sign}{}.{}e{exponent}", &significant[..1], &significant[1..])
+        } else {
+            format!("{sign}{significant}e{exponent}")
+        }
+    } else {
+        format!("{}", x)
+    }
+}
+
 /// A utility struct to work around inability to `impl Display for Vec<Object>`
 pub struct Objects(Vec<Object>);
 
@@ -132,46 +179,91 @@ pub struct Equations(Vec<(Expression, Expression)>);
 
 
 impl Object {
+    /// Creates an uncertain value whose uncertainty is a new independent error source
+    pub fn uncertain(value: Decimal, uncertainty: Decimal) -> Self {
+        Self::Uncertain{value, uncertainty: uncertainty.abs(), sources: ErrorSources::independent(uncertainty)}
+    }
+
+    /// Name of the kind of value this is, for errors
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Self::Null => "null",
+            Self::Int(_) => "integer",
+            Self::Decimal(_) => "decimal",
+            Self::String(_) => "string",
+            Self::Boolean(_) => "boolean",
+            Self::Uncertain { .. } => "uncertain value",
+            Self::Quantity { .. } => "quantity",
+            Self::Function { .. } => "function",
+            Self::Finder(_) => "finder",
+            Self::Array(_) => "array",
+            Self::Identifier(_) => "identifier",
+            Self::Column(_) => "column",
+            Self::DataTable { .. } => "data table",
+            Self::Class(_) => "class",
+            Self::Instance { .. } => "instance",
+            Self::Symbolic(_) => "symbolic expression",
+        }
+    }
+
     /// Nicely formatted output for displaying objects with `print`
-    pub fn user_print(&self, line: usize) -> Result<String, (String, usize)> {
+    pub fn user_print(&self, line: usize) -> Result<String, HawkError> {
         match self.clone() {
             Self::Boolean(x) => Ok(format!("{x}")),
-            Self::Decimal(x) => Ok(format!("{}", x.normalize())),
+            Self::Decimal(x) => Ok(format_decimal(x)),
             Self::Int(x) => Ok(format!("{x}")),
             Self::String(x) => Ok(format!("{x}")),
             Self::Identifier(x) => Ok(format!("{x}")),
-            Self::Function{params, block} => Ok(format!("Function: params: {:?}, block: {block}", params)),
+            Self::Function{params, block, ..} => match block.returned_expression() {
+                Some(expr) => Ok(format!("({}) => {}", params.join(", "), expr.source())),
+                None => Ok(format!("function({})", params.join(", "))),
+            },
             Self::Array(x) => {
                 Self::user_print_array(x, line)
             },
             Self::Null => Ok(String::from("Null")),
-            Self::Uncertain{value, uncertainty} => Ok(format!("{} ± {}", value.normalize(), uncertainty.normalize())),
+            Self::Uncertain{value, uncertainty, sources: _} => Ok(format!("{} ± {}", format_decimal(value), format_decimal(uncertainty))),
+            Self::Quantity{value, unit} => match *value {
+                Self::Uncertain{..} => Ok(format!("({}) {unit}", value.user_print(line)?)),
+                _ => Ok(format!("{} {unit}", value.user_print(line)?)),
+            },
             Self::Column(x) => {
                 Self::user_print_column(x, line)
             },
             Self::DataTable{names, data} => {
                 Self::user_print_datatable(names, data, line)
             },
-            Self::Finder(_) => Ok(format!("finder function"))
+            Self::Finder(_) => Ok(format!("finder function")),
+            Self::Class(class) => Ok(format!("class {}", class.name)),
+            Self::Instance{class, fields} => {
+                let mut names: Vec<&String> = fields.keys().collect();
+                names.sort();
+                let mut values: Vec<String> = Vec::new();
+                for name in names {
+                    values.push(format!("{name}: {}", fields[name].user_print(line)?))
+                }
+                Ok(format!("{} {{{}}}", class.name, values.join(", ")))
+            },
+            Self::Symbolic(expr) => Ok(expr.source()),
         }
     }
 
-    fn user_print_datatable(names: Vec<String>, data: Vec<Object>, line: usize) -> Result<String, (String, usize)> {
+    fn user_print_datatable(names: Vec<String>, data: Vec<Object>, line: usize) -> Result<String, HawkError> {
         let mut table = Table::new();
         table.style = TableStyle::extended();
-        if let Object::Column(_) = data[0].clone() {
+        if let Object::Column(first) = data[0].clone() {
             let mut title_row = Vec::new();
             for name in names {
                 title_row.push(name)
             }
             table.add_row(Row::new(title_row));
-            for i in 0..data.len() {
+            for i in 0..first.len() {
                 let mut row = Vec::new();
                 for column in data.clone() {
                     if let Object::Column(objs) = column {
                         row.push(objs[i].user_print(line)?)
                     } else {
-                        return Err((format!("Expected column found {}", column), line));
+                        return Err(HawkError::runtime(format!("Expected column found {}", column), line));
                     }
                 }
                 table.add_row(Row::new(row))
@@ -180,7 +272,7 @@ impl Object {
         Ok(table.render())
     }
 
-    fn user_print_column(x: Vec<Object>, line: usize) -> Result<String, (String, usize)> {
+    fn user_print_column(x: Vec<Object>, line: usize) -> Result<String, HawkError> {
         let mut str = String::from("[");
         for (idx, obj) in x.iter().enumerate() {
             if idx < x.len() - 1 {
@@ -192,7 +284,7 @@ impl Object {
         Ok(format!("{str}]"))
     }
 
-    fn user_print_array(x: Vec<Object>, line: usize) -> Result<String, (String, usize)> {
+    fn user_print_array(x: Vec<Object>, line: usize) -> Result<String, HawkError> {
         let mut str = String::from("[");
         for (idx, obj) in x.iter().enumerate() {
             if idx < x.len() - 1 {
@@ -203,4 +295,41 @@ impl Object {
         }
         Ok(format!("{str}]"))
     }
-}
\ No newline at end of file
+}
+
+#[test]
+fn scientific_output() {
+    assert_eq!(format_decimal(dec!(0.0000000000000000000000138)), "1.38e-23");
+    assert_eq!(format_decimal(dec!(602200000000000000000000)), "6.022e23");
+    assert_eq!(format_decimal(dec!(1.50)), "1.5");
+    assert_eq!(format_decimal(dec!(-0.00025)), "-0.00025");
+    assert_eq!(format_decimal(dec!(-0.000002)), "-2e-6");
+}
+
+#[test]
+fn boolean_equality() {
+    assert_eq!(Object::Boolean(true), Object::Boolean(true));
+    assert_ne!(Object::Boolean(true), Object::Boolean(false));
+    assert_ne!(Object::Boolean(true), Object::Int(1));
+}
+
+#[test]
+fn identifier_equality() {
+    let identifier = |name: &str| Object::Identifier(String::from(name));
+
+    assert_eq!(identifier("x"), identifier("x"));
+    assert_ne!(identifier("x"), identifier("y"));
+    assert_ne!(identifier("x"), Object::String(String::from("x")));
+}
+
+#[test]
+fn function_printing() {
+    use crate::token::TokenType;
+
+    let x = Box::new(Expression::Literal(Object::Identifier(String::from("x"))));
+    let square = Expression::Binary { operand1: x.clone(), operand2: Box::new(Expression::Literal(Object::Int(2))), operator: TokenType::Caret };
+    let function = |block| Object::Function { params: vec![String::from("x"), String::from("y")], block: Box::new(block), captured: HashMap::new() };
+
+    assert_eq!(function(Statement::Return(Box::new(square.clone()))).user_print(0).unwrap(), "(x, y) => x^2");
+    assert_eq!(function(Statement::Block(vec![Statement::Print(x), Statement::Return(Box::new(square))])).user_print(0).unwrap(), "function(x, y)");
+}
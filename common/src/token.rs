@@ -1,8 +1,10 @@
 use std::fmt::{Display, Error, Formatter, Result};
 
+use crate::error::Span;
 use crate::object::Object;
 
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// Differentiate between types of tokens
 pub enum TokenType {
     // Keywords
@@ -12,6 +14,8 @@ pub enum TokenType {
     While,
     For,
     Break,
+    Continue,
+    In,
     Class,
     Super,
     This,
@@ -25,12 +29,18 @@ pub enum TokenType {
     Finder,
     Find,
     Equation,
+    /// `expect` or its synonym `assert`
+    Expect,
+    Try,
+    Catch,
 
     // Literals
     Identifier,
     String,
     Int,
     Decimal,
+    /// A unit written after a number, e.g. the `m/s^2` in `9.81 m/s^2`
+    Unit,
     True,
     False,
 
@@ -43,6 +53,8 @@ pub enum TokenType {
     BracketRight,
     Comma,
     Dot,
+    /// `..` in a range, e.g. `0..10`
+    DotDot,
     Minus,
     Plus,
     Semicolon,
@@ -80,12 +92,21 @@ impl Display for TokenType {
 pub struct Token {
     pub token_type: TokenType,
     pub line: usize,
+    /// Column the token starts at, counting from 1
+    pub column: usize,
+    /// Number of characters in the token
+    pub length: usize,
     pub literal: Option<Object>
 }
 
 impl Token {
-    pub fn new(token_type: TokenType, line: usize, literal: Option<Object>) -> Self {
-        Token {token_type, line, literal}
+    pub fn new(token_type: TokenType, line: usize, column: usize, length: usize, literal: Option<Object>) -> Self {
+        Token {token_type, line, column, length, literal}
+    }
+
+    /// Where the token is in the source code, for errors
+    pub fn span(&self) -> Span {
+        Span::new(self.line, self.column, self.length)
     }
 }
 
@@ -101,9 +122,7 @@ impl Display for Tokens {
     fn fmt(&self, f: &mut Formatter) -> Result {
         let mut problem = false;
         for token in self.0.iter() {
-            if let Ok(_) = writeln!(f, "{}", token) {
-                ()
-            } else {
+            if writeln!(f, "{}", token).is_err() {
                 problem = true
             }
         }
@@ -118,63 +137,70 @@ pub trait UserPrint {
 impl UserPrint for TokenType {
     fn user_print(&self) -> String {
         match self {
-            TokenType::Abs => format!("|"),
-            TokenType::And => format!("and"),
-            TokenType::Assign => format!("="),
-            TokenType::Asterisk => format!("*"),
-            TokenType::BraceLeft => format!("{{"),
-            TokenType::BraceRight => format!("}}"),
-            TokenType::BracketLeft => format!("["),
-            TokenType::BracketRight => format!("]"),
-            TokenType::Break => format!("break"),
-            TokenType::Caret => format!("^"),
-            TokenType::Class => format!("class"),
-            TokenType::Colon => format!(":"),
-            TokenType::Comma => format!(","),
-            TokenType::Decimal => format!("Decimal"),
-            TokenType::Dot => format!("."),
-            TokenType::E => format!("E"),
-            TokenType::EOF => format!("end of file"),
-            TokenType::Else => format!("else"),
-            TokenType::EqualEqual => format!("=="),
-            TokenType::Equation => format!("equation"),
-            TokenType::False => format!("false"),
-            TokenType::FatArrow => format!("=>"),
-            TokenType::Find => format!("find"),
-            TokenType::Finder => format!("finder"),
-            TokenType::For => format!("for"),
-            TokenType::Function => format!("function"),
-            TokenType::GreaterThan => format!(">"),
-            TokenType::GreaterThanEqual => format!(">="),
-            TokenType::Identifier => format!("identifier"),
-            TokenType::If => format!("if"),
-            TokenType::Import => format!("import"),
-            TokenType::Int => format!("Integer"),
-            TokenType::LessThan => format!("<"),
-            TokenType::LessThanEqual => format!("<="),
-            TokenType::Let => format!("let"),
-            TokenType::Loop => format!("loop"),
-            TokenType::Minus => format!("-"),
-            TokenType::NewLine => format!("newline"),
-            TokenType::Not => format!("not"),
-            TokenType::NotEqual => format!("!="),
-            TokenType::Null => format!("null"),
-            TokenType::Or => format!("or"),
-            TokenType::ParenthesisLeft => format!("("),
-            TokenType::ParenthesisRight => format!(")"),
-            TokenType::Plus => format!("+"),
-            TokenType::PlusMinus => format!("±"),
-            TokenType::Print => format!("print"),
-            TokenType::Process => format!("process"),
-            TokenType::QuestionMark => format!("?"),
-            TokenType::Return => format!("return"),
-            TokenType::Semicolon => format!(";"),
-            TokenType::Slash => format!("/"),
-            TokenType::String => format!("String"),
-            TokenType::Super => format!("super"),
-            TokenType::This => format!("this"),
-            TokenType::True => format!("true"),
-            TokenType::While => format!("while"),
+            TokenType::Abs => String::from("|"),
+            TokenType::And => String::from("and"),
+            TokenType::Assign => String::from("="),
+            TokenType::Asterisk => String::from("*"),
+            TokenType::BraceLeft => String::from("{"),
+            TokenType::BraceRight => String::from("}"),
+            TokenType::BracketLeft => String::from("["),
+            TokenType::BracketRight => String::from("]"),
+            TokenType::Break => String::from("break"),
+            TokenType::Continue => String::from("continue"),
+            TokenType::Caret => String::from("^"),
+            TokenType::Catch => String::from("catch"),
+            TokenType::Class => String::from("class"),
+            TokenType::Colon => String::from(":"),
+            TokenType::Comma => String::from(","),
+            TokenType::Decimal => String::from("Decimal"),
+            TokenType::Dot => String::from("."),
+            TokenType::DotDot => String::from(".."),
+            TokenType::E => String::from("E"),
+            TokenType::EOF => String::from("end of file"),
+            TokenType::Else => String::from("else"),
+            TokenType::EqualEqual => String::from("=="),
+            TokenType::Equation => String::from("equation"),
+            TokenType::Expect => String::from("expect"),
+            TokenType::False => String::from("false"),
+            TokenType::FatArrow => String::from("=>"),
+            TokenType::Find => String::from("find"),
+            TokenType::Finder => String::from("finder"),
+            TokenType::For => String::from("for"),
+            TokenType::Function => String::from("function"),
+            TokenType::GreaterThan => String::from(">"),
+            TokenType::GreaterThanEqual => String::from(">="),
+            TokenType::Identifier => String::from("identifier"),
+            TokenType::If => String::from("if"),
+            TokenType::Import => String::from("import"),
+            TokenType::In => String::from("in"),
+            TokenType::Int => String::from("Integer"),
+            TokenType::LessThan => String::from("<"),
+            TokenType::LessThanEqual => String::from("<="),
+            TokenType::Let => String::from("let"),
+            TokenType::Loop => String::from("loop"),
+            TokenType::Minus => String::from("-"),
+            TokenType::NewLine => String::from("newline"),
+            TokenType::Not => String::from("not"),
+            TokenType::NotEqual => String::from("!="),
+            TokenType::Null => String::from("null"),
+            TokenType::Or => String::from("or"),
+            TokenType::ParenthesisLeft => String::from("("),
+            TokenType::ParenthesisRight => String::from(")"),
+            TokenType::Plus => String::from("+"),
+            TokenType::PlusMinus => String::from("±"),
+            TokenType::Print => String::from("print"),
+            TokenType::Process => String::from("process"),
+            TokenType::QuestionMark => String::from("?"),
+            TokenType::Return => String::from("return"),
+            TokenType::Semicolon => String::from(";"),
+            TokenType::Slash => String::from("/"),
+            TokenType::String => String::from("String"),
+            TokenType::Super => String::from("super"),
+            TokenType::This => String::from("this"),
+            TokenType::True => String::from("true"),
+            TokenType::Try => String::from("try"),
+            TokenType::Unit => String::from("unit"),
+            TokenType::While => String::from("while"),
         }
     }
 }
\ No newline at end of file
@@ -1,10 +1,11 @@
 use std::collections::HashMap;
 use std::fmt::{Display, Error, Formatter, Result};
 
-use crate::object::Object;
-use crate::token::TokenType;
+use crate::object::{format_decimal, Object};
+use crate::token::{TokenType, UserPrint};
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Statement {
     /// Print statement
     Print(Box<Expression>),
@@ -21,8 +22,12 @@ pub enum Statement {
     While{condition: Box<Expression>, block: Box<Statement>},
     /// Loop
     Loop(Box<Statement>),
+    /// For loop, with one variable (`for x in arr`) or several that each item is unpacked into (`for (i, x) in ...`)
+    For{variables: Vec<String>, iterable: Iterable, block: Box<Statement>},
     /// Break loop
     Break,
+    /// Skip to the next iteration of a loop
+    Continue,
     /// If statement (no else)
     If{condition: Box<Expression>, block: Box<Statement>},
     /// If statement with `else`
@@ -30,14 +35,41 @@ pub enum Statement {
     /// A statement that adds the parsed function to the top scope
     Function{identifier: String, params: Vec<String>, block: Box<Statement>},
     Finder{identifier: String, equations: Vec<(Expression, Expression)>},
+    /// A class declaration, with the class it inherits from, its fields and its methods (as `Statement::Function`s)
+    Class{identifier: String, parent: Option<String>, fields: Vec<(String, Expression)>, methods: Vec<Statement>},
+    /// Sets a field of an instance (`obj.field = value`)
+    FieldAssign{object: String, field: String, value: Box<Expression>},
     /// Returns function
     Return(Box<Expression>),
     /// Runs code from another file, importing functions and global variables
     Import(Box<Expression>),
     /// Process block to process and analyze data
     Process{readfile: Box<Expression>, writefile: Box<Expression>, block: Box<Statement>},
-    /// Indicates new line
-    Line,
+    /// Warns if the condition is false, optionally with a message (`expect x == 1, "message"`)
+    Expect{condition: Box<Expression>, message: Option<Box<Expression>>},
+    /// Runs `block`, and if it has an error, runs `catch` with the error stored in the variable `error` (if given)
+    Try{block: Box<Statement>, error: Option<String>, catch: Box<Statement>},
+    /// Sets the line the following statements are on. It is 0 in standard library code, so that errors in it point
+    /// to where it was called
+    Line(usize),
+}
+
+impl Statement {
+    /// The expression a function body returns, if it is just a `return` (e.g. `(x) => x^2`)
+    pub fn returned_expression(&self) -> Option<&Expression> {
+        match self {
+            Self::Return(expr) => Some(expr),
+            Self::Block(statements) => {
+                // Blocks mark the line each statement is on
+                let mut statements = statements.iter().filter(|statement| !matches!(statement, Self::Line(_)));
+                match (statements.next(), statements.next()) {
+                    (Some(statement), None) => statement.returned_expression(),
+                    _ => None,
+                }
+            },
+            _ => None,
+        }
+    }
 }
 
 // Implemented to print statements to Rust code for standard library compilation in `build.rs`
@@ -46,11 +78,14 @@ impl Display for Statement {
         match &self {
             Self::Block(x) => writeln!(f, "Statement::Block(vec![{}])", Statements(x.clone())),
             Self::Break => writeln!(f, "Statement::Break"),
+            Self::Continue => writeln!(f, "Statement::Continue"),
+            Self::For{variables, iterable, block} => writeln!(f, "Statement::For{{variables: vec!{:?}.iter().map(std::string::ToString::to_string).collect(), iterable: {}, block: Box::new({})}}", variables, iterable, block),
             Self::EOF => writeln!(f, "Statement::EOF"),
             Self::Definition{name, value} => writeln!(f, "Statement::Definition{{name: \"{}\".to_owned(), value: Box::new({})}}", name, value),
             Self::ArrayAssign{name, idx, value} => writeln!(f, "Statement::ArrayAssign{{name: \"{}\".to_owned(), idx: Box::new({}), value: Box::new({})}}", name, idx, value),
             Self::Function{identifier, params, block} => writeln!(f, "Statement::Function{{identifier: \"{}\".to_owned(), params: vec!{:?}.iter().map(|x| x.to_owned()).collect(), block: Box::new({})}}", identifier, params, block),
             Self::Import(x) => writeln!(f, "Statement::Import(Box::new({}))", x),
+            Self::FieldAssign{object, field, value} => writeln!(f, "Statement::FieldAssign{{object: \"{}\".to_owned(), field: \"{}\".to_owned(), value: Box::new({})}}", object, field, value),
             Self::Process{readfile, writefile, block} => writeln!(f, "Statement::Process{{readfile: Box::new({readfile}), readfile: Box::new({writefile}), Box::new({block})}}"),
             Self::If{condition, block} => writeln!(f, "Statement::If{{condition: Box::new({}), block: Box::new({})}}", condition, block),
             Self::IfElse{condition, if_block, else_block} => writeln!(f, "Statement::IfElse{{condition: Box::new({}), if_block: Box::new({}), else_block: Box::new({})}}", condition, if_block, else_block),
@@ -59,13 +94,39 @@ impl Display for Statement {
             Self::While{condition, block} => writeln!(f, "Statement::While{{condition: Box::new({}), block: Box::new({})}}", condition, block),
             Self::Loop(x) => writeln!(f, "Statement::Loop(Box::new({}))", x),
             Self::Expression(x) => writeln!(f, "Statement::Expression(Box::new({}))", x),
-            Self::Line => writeln!(f, "Statement::Line"),
+            Self::Expect{condition, message: Some(message)} => writeln!(f, "Statement::Expect{{condition: Box::new({}), message: Some(Box::new({}))}}", condition, message),
+            Self::Expect{condition, message: None} => writeln!(f, "Statement::Expect{{condition: Box::new({}), message: None}}", condition),
+            Self::Try{block, error: Some(error), catch} => writeln!(f, "Statement::Try{{block: Box::new({}), error: Some(\"{}\".to_owned()), catch: Box::new({})}}", block, error, catch),
+            Self::Try{block, error: None, catch} => writeln!(f, "Statement::Try{{block: Box::new({}), error: None, catch: Box::new({})}}", block, catch),
+            // Lines of the standard library aren't in the user's code
+            Self::Line(_) => writeln!(f, "Statement::Line(0)"),
             _ => writeln!(f, "")
         }.unwrap();
         Ok(())
     }
 }
 
+/// What a `for` loop iterates over
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Iterable {
+    /// Numbers from `start` up to (but not including) `end`, e.g. `0..10` or `0..10 step 2`
+    Range{start: Box<Expression>, end: Box<Expression>, step: Option<Box<Expression>>},
+    /// The items of an array or column, or the rows of a datatable
+    Collection(Box<Expression>),
+}
+
+// Print to Rust code for standard library compilation in `build.rs`
+impl Display for Iterable {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match &self {
+            Self::Range{start, end, step: Some(step)} => write!(f, "Iterable::Range{{start: Box::new({}), end: Box::new({}), step: Some(Box::new({}))}}", start, end, step),
+            Self::Range{start, end, step: None} => write!(f, "Iterable::Range{{start: Box::new({}), end: Box::new({}), step: None}}", start, end),
+            Self::Collection(x) => write!(f, "Iterable::Collection(Box::new({}))", x),
+        }
+    }
+}
+
 // For printing a block of statements as Rust code
 impl Display for Statements {
     fn fmt(&self, f: &mut Formatter) -> Result {
@@ -82,6 +143,7 @@ impl Display for Statements {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Expression {
     /// Literal expression
     Literal(Object),
@@ -101,12 +163,89 @@ pub enum Expression {
     /// Calls function, evaluates to return value of function
     FunctionCall{identifier: String, args: Vec<Box<Expression>>},
     FinderCall{identifier: String, given: HashMap<String, Expression>, to_find: String},
-    /// Calls method, evaluates to return value of method
-    MethodCall{object: String, method: String, args: Vec<Box<Expression>>},
+    /// Calls method on the value of an expression, evaluates to return value of method
+    MethodCall{object: Box<Expression>, method: String, args: Vec<Box<Expression>>},
+    /// Gets a field of an instance
+    Field{object: Box<Expression>, field: String},
     /// Gets item from given index of array
     ArrayIndex{identifier: String, index: Box<Expression>},
     /// Defines array
-    Array(Vec<Box<Expression>>)
+    Array(Vec<Box<Expression>>),
+    /// Anonymous function, e.g. `(x) => x^2 * k`. An expression body is stored as a `return` statement
+    Lambda{params: Vec<String>, body: Box<Statement>},
+    /// Defines a record with named fields, e.g. `{x: 3, y: 2}`
+    Record(Vec<(String, Box<Expression>)>)
+}
+
+impl Expression {
+    /// Prints the expression as Hawk code, with only the parentheses that are needed, e.g. for symbolic expressions
+    pub fn source(&self) -> String {
+        match self {
+            Self::Literal(Object::String(x)) => format!("\"{x}\""),
+            Self::Literal(Object::Decimal(x)) => format_decimal(*x),
+            Self::Literal(x) => x.user_print(0).unwrap_or_default(),
+            Self::Unary{operand, operator: TokenType::Minus} => format!("-{}", operand.source_within(5, false)),
+            Self::Unary{operand, operator} => format!("{} {}", operator.user_print(), operand.source_within(5, false)),
+            Self::Binary{operand1, operand2, operator} => {
+                let precedence = Self::binary_precedence(*operator);
+                let operator = match operator {
+                    TokenType::Caret => String::from("^"),
+                    operator => format!(" {} ", operator.user_print()),
+                };
+                format!("{}{operator}{}", operand1.source_within(precedence, false), operand2.source_within(precedence, true))
+            },
+            Self::Parenthesized(expr) => format!("({})", expr.source()),
+            Self::FunctionCall{identifier, args} => {
+                let args: Vec<String> = args.iter().map(|arg| arg.source()).collect();
+                format!("{identifier}({})", args.join(", "))
+            },
+            Self::MethodCall{object, method, args} => {
+                let args: Vec<String> = args.iter().map(|arg| arg.source()).collect();
+                format!("{}.{method}({})", object.source(), args.join(", "))
+            },
+            Self::Field{object, field} => format!("{}.{field}", object.source()),
+            Self::ArrayIndex{identifier, index} => format!("{identifier}[{}]", index.source()),
+            Self::Array(items) => {
+                let items: Vec<String> = items.iter().map(|item| item.source()).collect();
+                format!("[{}]", items.join(", "))
+            },
+            Self::Record(fields) => {
+                let fields: Vec<String> = fields.iter().map(|(name, value)| format!("{name}: {}", value.source())).collect();
+                format!("{{{}}}", fields.join(", "))
+            },
+            Self::FinderCall{identifier, ..} => format!("find {identifier}(...)"),
+            Self::Lambda{params, ..} => format!("({}) => ...", params.join(", ")),
+        }
+    }
+
+    /// How tightly an operator binds, following the parser: unary operators bind tightest, and `^` is left associative
+    fn binary_precedence(operator: TokenType) -> u8 {
+        match operator {
+            TokenType::PlusMinus => 4,
+            TokenType::Caret => 3,
+            TokenType::Asterisk | TokenType::Slash => 2,
+            TokenType::Plus | TokenType::Minus => 1,
+            _ => 0,
+        }
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            Self::Binary{operator, ..} => Self::binary_precedence(*operator),
+            Self::Unary{..} => 5,
+            _ => 6,
+        }
+    }
+
+    /// Prints an operand of an operator with the given precedence, in parentheses if it binds less tightly. Operators
+    /// are left associative, so the right operand also needs them if it binds as tightly
+    fn source_within(&self, precedence: u8, right: bool) -> String {
+        if self.precedence() < precedence || (right && self.precedence() == precedence) {
+            format!("({})", self.source())
+        } else {
+            self.source()
+        }
+    }
 }
 
 // Print statements to Rust code, intended for compiling std in `build.rs`
@@ -119,8 +258,14 @@ impl Display for Expression {
             Self::Unary{operand, operator} => write!(f, "Expression::Unary{{operand: Box::new({}), operator: {}}}", operand, operator),
             Self::Binary{operand1, operand2, operator} => write!(f, "Expression::Binary{{operand1: Box::new({}), operand2: Box::new({}), operator: {}}}", operand1, operand2, operator),
             Self::FunctionCall{identifier, args} => write!(f, "Expression::FunctionCall{{identifier: \"{}\".to_owned(), args: vec![{}]}}", identifier, Expressions(args.clone())),
-            Self::MethodCall{object, method, args} => write!(f, "Expression::FunctionCall{{object: \"{}\".to_owned(), method: \"{}\".to_owned(), args: vec![{}]}}", object, method, Expressions(args.clone())),
+            Self::MethodCall{object, method, args} => write!(f, "Expression::MethodCall{{object: Box::new({}), method: \"{}\".to_owned(), args: vec![{}]}}", object, method, Expressions(args.clone())),
+            Self::Lambda{params, body} => write!(f, "Expression::Lambda{{params: vec!{:?}.iter().map(std::string::ToString::to_string).collect(), body: Box::new({})}}", params, body),
+            Self::Field{object, field} => write!(f, "Expression::Field{{object: Box::new({}), field: \"{}\".to_owned()}}", object, field),
             Self::ArrayIndex{identifier, index} => write!(f, "Expression::ArrayIndex{{identifier: \"{}\".to_owned(), index: Box::new({})}}", identifier, index),
+            Self::Record(fields) => {
+                let fields: Vec<String> = fields.iter().map(|(name, value)| format!("(\"{}\".to_owned(), Box::new({})),", name, value)).collect();
+                write!(f, "Expression::Record(vec![{}])", fields.join(""))
+            },
             _ => write!(f, "CAN'T USE FINDERS IN STD YET")
         }.unwrap();
         Ok(())
@@ -148,4 +293,24 @@ pub struct Statements(pub Vec<Statement>);
 
 /// A utility struct to work around inability to `impl Display for Vec<Box::Expression>`
 
-pub struct Expressions(pub Vec<Box<Expression>>);
\ No newline at end of file
+pub struct Expressions(pub Vec<Box<Expression>>);
+#[test]
+fn source_printing() {
+    let symbol = |name: &str| Box::new(Expression::Literal(Object::Identifier(String::from(name))));
+    let binary = |operand1, operand2, operator| Box::new(Expression::Binary { operand1, operand2, operator });
+
+    // (a + b) * c^2
+    let expr = binary(binary(symbol("a"), symbol("b"), TokenType::Plus), binary(symbol("c"), symbol("2"), TokenType::Caret), TokenType::Asterisk);
+    assert_eq!(expr.source(), "(a + b) * c^2");
+
+    // a - (b - c), and a / (b * c)
+    assert_eq!(binary(symbol("a"), binary(symbol("b"), symbol("c"), TokenType::Minus), TokenType::Minus).source(), "a - (b - c)");
+    assert_eq!(binary(symbol("a"), binary(symbol("b"), symbol("c"), TokenType::Asterisk), TokenType::Slash).source(), "a / (b * c)");
+
+    // The minus sign binds tighter than `^`
+    let negated = Expression::Unary { operand: binary(symbol("x"), symbol("2"), TokenType::Caret), operator: TokenType::Minus };
+    assert_eq!(negated.source(), "-(x^2)");
+
+    let call = Expression::FunctionCall { identifier: String::from("sin"), args: vec![binary(symbol("x"), symbol("y"), TokenType::Plus)] };
+    assert_eq!(call.source(), "sin(x + y)");
+}
@@ -0,0 +1,147 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use rust_decimal::prelude::*;
+
+/// Ids below this are used for the uncertainties of physical constants, which are the same wherever they're used
+const CONSTANT_SOURCES: usize = 1024;
+
+/// Every independent error source (e.g. each use of `±`) gets its own id, so that quantities derived from the same
+/// measurement can be recognised as correlated
+static NEXT_SOURCE_ID: AtomicUsize = AtomicUsize::new(CONSTANT_SOURCES);
+
+/// The identity of an uncertain value: the independent error sources it depends on, each stored with its
+/// contribution to the uncertainty (the partial derivative with respect to the source times the source's uncertainty).
+/// Contributions are kept sorted by source id.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ErrorSources(pub Vec<(usize, Decimal)>);
+
+impl ErrorSources {
+    /// Creates a new independent error source with the given uncertainty
+    pub fn independent(uncertainty: Decimal) -> Self {
+        let id = NEXT_SOURCE_ID.fetch_add(1, Ordering::Relaxed);
+        Self(vec![(id, uncertainty.abs())])
+    }
+
+    /// The error source of a physical constant, given by its index in the list of constants
+    pub fn constant(index: usize, uncertainty: Decimal) -> Self {
+        assert!(index < CONSTANT_SOURCES);
+        Self(vec![(index, uncertainty.abs())])
+    }
+
+    /// Makes sure ids handed out from now on don't clash with sources created elsewhere (e.g. in a saved session)
+    pub fn reserve_ids(&self) {
+        if let Some(max) = self.0.iter().map(|(id, _)| *id).max() {
+            NEXT_SOURCE_ID.fetch_max(max + 1, Ordering::Relaxed);
+        }
+    }
+
+    /// Multiplies every contribution by a partial derivative
+    pub fn scale(&self, derivative: Decimal) -> Self {
+        Self(self.0.iter().map(|(id, contribution)| (*id, contribution * derivative)).collect())
+    }
+
+    /// Linear combination `a * self + b * other`, adding up contributions from shared sources so that they can cancel
+    pub fn combine(&self, a: Decimal, other: &Self, b: Decimal) -> Self {
+        let mut combined: Vec<(usize, Decimal)> = Vec::new();
+        let (mut i, mut j) = (0, 0);
+
+        while i < self.0.len() || j < other.0.len() {
+            let next = match (self.0.get(i), other.0.get(j)) {
+                (Some((id1, c1)), Some((id2, c2))) if id1 == id2 => {
+                    i += 1;
+                    j += 1;
+                    (*id1, a * c1 + b * c2)
+                },
+                (Some((id1, c1)), Some((id2, _))) if id1 < id2 => {
+                    i += 1;
+                    (*id1, a * c1)
+                },
+                (Some(_), Some((id2, c2))) | (None, Some((id2, c2))) => {
+                    j += 1;
+                    (*id2, b * c2)
+                },
+                (Some((id1, c1)), None) => {
+                    i += 1;
+                    (*id1, a * c1)
+                },
+                (None, None) => break
+            };
+
+            if !next.1.is_zero() {
+                combined.push(next)
+            }
+        }
+
+        Self(combined)
+    }
+
+    /// Worst case uncertainty: the sum of the magnitudes of all contributions
+    pub fn worst_case(&self) -> Decimal {
+        self.0.iter().map(|(_, contribution)| contribution.abs()).sum()
+    }
+
+    /// Gaussian uncertainty: the contributions added in quadrature
+    pub fn quadrature(&self) -> Decimal {
+        let variance: Decimal = self.0.iter().map(|(_, contribution)| contribution * contribution).sum();
+        variance.sqrt().unwrap_or_default()
+    }
+}
+
+/// How uncertainties are combined when doing arithmetic with uncertain values
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Propagation {
+    /// Linear (worst case) propagation: contributions of independent sources are added up
+    #[default]
+    WorstCase,
+    /// Gaussian propagation: contributions of independent sources are added in quadrature
+    Quadrature,
+    /// Interval arithmetic: the uncertainty is half the range of results over the bounds of the operands
+    MinMax,
+}
+
+impl Propagation {
+    /// Gets a propagation mode from the name used in Hawk code
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "worst_case" | "worstcase" | "linear" => Some(Self::WorstCase),
+            "quadrature" | "gaussian" => Some(Self::Quadrature),
+            "minmax" | "min_max" | "bounds" => Some(Self::MinMax),
+            _ => None
+        }
+    }
+
+    /// The name used in Hawk code
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::WorstCase => "worst_case",
+            Self::Quadrature => "quadrature",
+            Self::MinMax => "minmax",
+        }
+    }
+
+    /// Combines the contributions of error sources into a single uncertainty. Min/max bounding has no notion of
+    /// independent sources, so it falls back to the worst case, which is the bound for linear functions
+    pub fn uncertainty(&self, sources: &ErrorSources) -> Decimal {
+        match self {
+            Self::WorstCase | Self::MinMax => sources.worst_case(),
+            Self::Quadrature => sources.quadrature(),
+        }
+    }
+}
+
+#[test]
+fn correlated_sources() {
+    use rust_decimal_macros::dec;
+
+    let x = ErrorSources::independent(dec!(0.1));
+    let y = ErrorSources::independent(dec!(0.2));
+
+    // x - x
+    assert_eq!(x.combine(dec!(1), &x, dec!(-1)), ErrorSources(Vec::new()));
+    // x + x
+    assert_eq!(x.combine(dec!(1), &x, dec!(1)).worst_case(), dec!(0.2));
+    // x + y - x
+    assert_eq!(x.combine(dec!(1), &y, dec!(1)).combine(dec!(1), &x, dec!(-1)), y);
+    assert_eq!(x.combine(dec!(3), &y, dec!(2)).quadrature(), dec!(0.5));
+}
@@ -0,0 +1,244 @@
+use std::fmt::{self, Display, Formatter};
+
+use rust_decimal::prelude::*;
+use rust_decimal_macros::dec;
+
+use crate::object::Object;
+
+/// Symbols of the SI base units, in the order their powers are stored in a `Dimension`
+const BASE_UNITS: [&str; 7] = ["m", "kg", "s", "A", "K", "mol", "cd"];
+
+/// Prefixes that can be put in front of any unit, with the power of 10 they multiply it by
+const PREFIXES: [(&str, i32); 14] = [("y", -24), ("z", -21), ("a", -18), ("f", -15), ("p", -12), ("n", -9), ("µ", -6), ("μ", -6), ("u", -6), ("m", -3), ("c", -2), ("k", 3), ("M", 6), ("G", 9)];
+
+/// The powers of the SI base units (m, kg, s, A, K, mol, cd) that a quantity is made of
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Dimension(pub [i32; 7]);
+
+impl Dimension {
+    fn multiply(&self, other: &Self) -> Self {
+        let mut powers = self.0;
+        for (power, other) in powers.iter_mut().zip(other.0) {
+            *power += other
+        }
+        Self(powers)
+    }
+
+    fn pow(&self, n: i32) -> Self {
+        Self(self.0.map(|power| power * n))
+    }
+
+    pub fn is_dimensionless(&self) -> bool {
+        self.0.iter().all(|power| *power == 0)
+    }
+}
+
+/// Shows a dimension in SI base units, e.g. `kg*m/s^2`
+impl Display for Dimension {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let unit = Unit(BASE_UNITS.iter().zip(self.0).map(|(symbol, power)| (symbol.to_string(), power)).collect());
+        write!(f, "{}", unit.simplify())
+    }
+}
+
+/// Looks up a unit without a prefix, giving its size in SI base units and its dimension
+fn lookup_unit(symbol: &str) -> Option<(Decimal, Dimension)> {
+    match symbol {
+        "m" => Some((dec!(1), Dimension([1, 0, 0, 0, 0, 0, 0]))),
+        "g" => Some((dec!(0.001), Dimension([0, 1, 0, 0, 0, 0, 0]))),
+        "s" => Some((dec!(1), Dimension([0, 0, 1, 0, 0, 0, 0]))),
+        "A" => Some((dec!(1), Dimension([0, 0, 0, 1, 0, 0, 0]))),
+        "K" => Some((dec!(1), Dimension([0, 0, 0, 0, 1, 0, 0]))),
+        "mol" => Some((dec!(1), Dimension([0, 0, 0, 0, 0, 1, 0]))),
+        "cd" => Some((dec!(1), Dimension([0, 0, 0, 0, 0, 0, 1]))),
+        "N" => Some((dec!(1), Dimension([1, 1, -2, 0, 0, 0, 0]))),
+        "J" => Some((dec!(1), Dimension([2, 1, -2, 0, 0, 0, 0]))),
+        "W" => Some((dec!(1), Dimension([2, 1, -3, 0, 0, 0, 0]))),
+        "Pa" => Some((dec!(1), Dimension([-1, 1, -2, 0, 0, 0, 0]))),
+        "Hz" => Some((dec!(1), Dimension([0, 0, -1, 0, 0, 0, 0]))),
+        "C" => Some((dec!(1), Dimension([0, 0, 1, 1, 0, 0, 0]))),
+        "V" => Some((dec!(1), Dimension([2, 1, -3, -1, 0, 0, 0]))),
+        "ohm" | "Ω" => Some((dec!(1), Dimension([2, 1, -3, -2, 0, 0, 0]))),
+        "F" => Some((dec!(1), Dimension([-2, -1, 4, 2, 0, 0, 0]))),
+        "T" => Some((dec!(1), Dimension([0, 1, -2, -1, 0, 0, 0]))),
+        "Wb" => Some((dec!(1), Dimension([2, 1, -2, -1, 0, 0, 0]))),
+        "H" => Some((dec!(1), Dimension([2, 1, -2, -2, 0, 0, 0]))),
+        "L" => Some((dec!(0.001), Dimension([3, 0, 0, 0, 0, 0, 0]))),
+        "min" => Some((dec!(60), Dimension([0, 0, 1, 0, 0, 0, 0]))),
+        "h" => Some((dec!(3600), Dimension([0, 0, 1, 0, 0, 0, 0]))),
+        "eV" => Some((dec!(0.0000000000000000001602176634), Dimension([2, 1, -2, 0, 0, 0, 0]))),
+        _ => None
+    }
+}
+
+/// Looks up a unit that may have a prefix (e.g. `km`), giving its size in SI base units and its dimension
+pub fn lookup(symbol: &str) -> Option<(Decimal, Dimension)> {
+    if let Some(unit) = lookup_unit(symbol) {
+        return Some(unit)
+    }
+
+    for (prefix, exponent) in PREFIXES {
+        if let Some((size, dimension)) = symbol.strip_prefix(prefix).and_then(lookup_unit) {
+            let factor = if exponent < 0 {Decimal::new(1, exponent.unsigned_abs())} else {Decimal::from(10_i64.pow(exponent as u32))};
+            // A prefix can make a unit that is already small, like `eV`, too small to store
+            let size = size.checked_mul(factor).filter(|size| !size.is_zero())?;
+            return Some((size, dimension))
+        }
+    }
+
+    None
+}
+
+/// A unit as it was written, e.g. `kg*m/s^2`, stored as each symbol with its power
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Unit(pub Vec<(String, i32)>);
+
+impl Unit {
+    /// Parses a unit such as `m/s^2`, `kg*m^2/s^2` or `1/s`. Returns `None` if any symbol isn't a known unit
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let mut unit = Self(Vec::new());
+        let mut sign = 1;
+
+        let mut rest = if let Some(rest) = text.strip_prefix("1/") {
+            sign = -1;
+            rest
+        } else {
+            text
+        };
+
+        loop {
+            let (part, remainder) = match rest.find(['*', '/']) {
+                Some(index) => (&rest[..index], Some(&rest[index..])),
+                None => (rest, None),
+            };
+
+            let (symbol, power) = match part.split_once('^') {
+                Some((symbol, power)) => (symbol.trim(), power.trim().parse::<i32>().ok()?),
+                None => (part.trim(), 1),
+            };
+
+            lookup(symbol)?;
+            unit = unit.multiply(&Self(vec![(symbol.to_owned(), sign * power)]));
+
+            match remainder {
+                Some(remainder) => {
+                    sign = if remainder.starts_with('/') {-1} else {1};
+                    rest = &remainder[1..]
+                },
+                None => break
+            }
+        }
+
+        Some(unit)
+    }
+
+    /// The size of the unit in SI base units, e.g. `1000` for `km`
+    pub fn factor(&self) -> Decimal {
+        let (numerator, denominator) = self.fraction();
+        numerator / denominator
+    }
+
+    /// The size of the unit in SI base units as a numerator and denominator, e.g. `(1000, 3600)` for `km/h`, so that
+    /// conversions can divide once instead of using sizes that are already rounded
+    pub fn fraction(&self) -> (Decimal, Decimal) {
+        let (mut numerator, mut denominator) = (dec!(1), dec!(1));
+        for (symbol, power) in self.0.iter() {
+            let (size, _) = lookup(symbol).unwrap_or((dec!(1), Dimension::default()));
+            for _ in 0..power.unsigned_abs() {
+                if *power > 0 {numerator *= size} else {denominator *= size}
+            }
+        }
+        (numerator, denominator)
+    }
+
+    pub fn dimension(&self) -> Dimension {
+        self.0.iter().fold(Dimension::default(), |dimension, (symbol, power)| {
+            let (_, unit_dimension) = lookup(symbol).unwrap_or_default();
+            dimension.multiply(&unit_dimension.pow(*power))
+        })
+    }
+
+    /// Multiplies two units, adding up the powers of symbols that appear in both
+    pub fn multiply(&self, other: &Self) -> Self {
+        let mut parts = self.0.clone();
+        for (symbol, power) in other.0.iter() {
+            if let Some(part) = parts.iter_mut().find(|(existing, _)| existing == symbol) {
+                part.1 += power
+            } else {
+                parts.push((symbol.clone(), *power))
+            }
+        }
+        Self(parts).simplify()
+    }
+
+    pub fn divide(&self, other: &Self) -> Self {
+        self.multiply(&other.pow(-1))
+    }
+
+    pub fn pow(&self, n: i32) -> Self {
+        Self(self.0.iter().map(|(symbol, power)| (symbol.clone(), power * n)).collect()).simplify()
+    }
+
+    /// Removes symbols whose powers have cancelled out
+    fn simplify(&self) -> Self {
+        Self(self.0.iter().filter(|(_, power)| *power != 0).cloned().collect())
+    }
+}
+
+impl Display for Unit {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let format_part = |symbol: &str, power: i32| if power == 1 {symbol.to_owned()} else {format!("{symbol}^{power}")};
+
+        let numerator: Vec<String> = self.0.iter().filter(|(_, power)| *power > 0).map(|(symbol, power)| format_part(symbol, *power)).collect();
+        let denominator: Vec<String> = self.0.iter().filter(|(_, power)| *power < 0).map(|(symbol, power)| format_part(symbol, -power)).collect();
+
+        if numerator.is_empty() {
+            write!(f, "1")?
+        } else {
+            write!(f, "{}", numerator.join("*"))?
+        }
+        for part in denominator {
+            write!(f, "/{part}")?
+        }
+        Ok(())
+    }
+}
+
+/// Multiplies a number (which may be uncertain) by a conversion factor
+pub fn scale(value: &Object, factor: Decimal) -> Option<Object> {
+    match value {
+        Object::Int(x) if factor == dec!(1) => Some(Object::Int(*x)),
+        Object::Int(x) => Some(Object::Decimal(Decimal::from_i128(*x)? * factor)),
+        Object::Decimal(x) => Some(Object::Decimal(x * factor)),
+        Object::Uncertain{value, uncertainty, sources} => Some(Object::Uncertain{
+            value: value * factor,
+            uncertainty: uncertainty * factor.abs(),
+            sources: sources.scale(factor),
+        }),
+        _ => None
+    }
+}
+
+#[test]
+fn parse_units() {
+    let acceleration = Unit::parse("m/s^2").unwrap();
+    assert_eq!(acceleration, Unit(vec![("m".to_owned(), 1), ("s".to_owned(), -2)]));
+    assert_eq!(acceleration.to_string(), "m/s^2");
+    assert_eq!(acceleration.dimension(), Dimension([1, 0, -2, 0, 0, 0, 0]));
+
+    let speed = Unit::parse("km/h").unwrap();
+    assert_eq!(speed.factor().round_dp(10), dec!(0.2777777778));
+    assert_eq!(speed.fraction(), (dec!(1000), dec!(3600)));
+    assert_eq!(speed.dimension(), Unit::parse("m/s").unwrap().dimension());
+
+    assert_eq!(Unit::parse("N").unwrap().dimension(), Unit::parse("kg*m/s^2").unwrap().dimension());
+    assert_eq!(Unit::parse("1/s").unwrap().to_string(), "1/s");
+    assert_eq!(Unit::parse("m/s").unwrap().multiply(&Unit::parse("s").unwrap()).to_string(), "m");
+    assert_eq!(Unit::parse("m/x"), None);
+
+    // Small prefixes, unless the unit would be too small to store
+    assert_eq!(Unit::parse("yg").unwrap().factor(), dec!(0.000000000000000000000000001));
+    assert_eq!(Unit::parse("yeV"), None);
+}
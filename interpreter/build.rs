@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::fs::{self, DirEntry};
 use std::path::Path;
 
@@ -8,7 +9,7 @@ use hawk_lib::*;
 fn main() {
     let dest_path = Path::new("./src/standard_lib/").join("standard_lib_hawk.rs");
     let contents = String::from(
-        "use hawk_common::{object::Object, token::TokenType, tree::{Expression, Statement}};
+        "use hawk_common::{object::Object, token::TokenType, tree::{Expression, Iterable, Statement}};
 
 pub fn get_std_hawk_fn(identifier: String) -> Option<Object> {
 match identifier.as_str() {",
@@ -47,8 +48,8 @@ fn create_match_fn() -> String {
 
 fn create_match_arms_fn(path: DirEntry) -> String {
     let source = fs::read_to_string(path.path()).unwrap();
-    let lexed = lexer::Lexer::lex(source.as_str());
-    let parsed = parser::Parser::parse(&lexed, |_, _| ());
+    let lexed = lexer::Lexer::lex(source.as_str(), &mut |_, _| ()).unwrap();
+    let parsed = parser::Parser::parse(&lexed, &mut |_, _| ());
 
     let mut matcharms = String::new();
 
@@ -67,7 +68,7 @@ fn create_match_arms_fn(path: DirEntry) -> String {
 }
 
 fn create_match_arm_fn(params: Vec<String>, block: Box<Statement>, identifier: String) -> String {
-    format!("\"{identifier}\" => Some({}),", Object::Function { params, block })
+    format!("\"{identifier}\" => Some({}),", Object::Function { params, block, captured: HashMap::new() })
 }
 
 fn create_match_find() -> String {
@@ -83,8 +84,8 @@ fn create_match_find() -> String {
 
 fn create_match_arms_find(path: DirEntry) -> String {
     let source = fs::read_to_string(path.path()).unwrap();
-    let lexed = lexer::Lexer::lex(source.as_str());
-    let parsed = parser::Parser::parse(&lexed, |_, _| ());
+    let lexed = lexer::Lexer::lex(source.as_str(), &mut |_, _| ()).unwrap();
+    let parsed = parser::Parser::parse(&lexed, &mut |_, _| ());
 
     let mut matcharms = String::new();
 
@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+
+use crate::{ControlFlow, Interpreter};
+
+use hawk_common::class::Class;
+use hawk_common::error::{ErrorKind, HawkError};
+use hawk_common::object::Object;
+use hawk_common::tree::{Expression, Statement};
+
+/// Name of the class of the objects errors are given to `catch` blocks as
+const ERROR_CLASS: &str = "Error";
+
+impl Interpreter<'_> {
+    /// Runs `block`, and if it has an error, runs `catch` with the error stored in the variable `error`, which is removed
+    /// afterwards. Scopes and calls that were running when the error happened are cleaned up first, e.g. the scope of a
+    /// `process` block
+    pub fn run_try(
+        &mut self, block: Box<Statement>, error: Option<String>, catch: Box<Statement>,
+    ) -> Result<ControlFlow, HawkError> {
+        let scopes = self.scopes.len();
+        let calls = self.call_stack.len();
+
+        match self.run_statement(*block) {
+            Ok(flow) => Ok(flow),
+            Err(caught) => {
+                self.scopes.truncate(scopes);
+                self.call_stack.truncate(calls);
+
+                let mut scope = HashMap::new();
+                if let Some(name) = &error {
+                    scope.insert(name.clone(), Self::error_object(&caught));
+                }
+                self.scopes.push(scope);
+                let result = self.run_statement(*catch);
+
+                // Blocks don't have their own scopes, so only the error goes away with the `catch` block
+                let scope = self.scopes.pop().unwrap_or_default();
+                for (name, value) in scope {
+                    if error.as_ref() != Some(&name) {
+                        self.insert_top_scope(name, value)?
+                    }
+                }
+                result
+            }
+        }
+    }
+
+    /// Makes the error given by `error(message)` or `throw(message)`. Caught errors can also be thrown again
+    pub fn throw(&self, args: Vec<Object>) -> HawkError {
+        match args.get(0) {
+            Some(Object::String(message)) => HawkError::runtime(message.clone(), self.line),
+            Some(Object::Instance { class, fields }) if class.name == ERROR_CLASS => {
+                let kind = match fields.get("kind") {
+                    Some(Object::String(kind)) => ErrorKind::from_id(kind).unwrap_or(ErrorKind::Runtime),
+                    _ => ErrorKind::Runtime,
+                };
+                let message = match fields.get("message") {
+                    Some(Object::String(message)) => message.clone(),
+                    _ => String::new(),
+                };
+                let line = match fields.get("line") {
+                    Some(Object::Int(line)) if *line > 0 => *line as usize,
+                    _ => self.line,
+                };
+                HawkError::new(kind, message, line)
+            },
+            Some(x) => match x.user_print(self.line) {
+                Ok(message) => HawkError::runtime(message, self.line),
+                Err(error) => error,
+            },
+            None => HawkError::runtime(String::from("Expected an error message"), self.line),
+        }
+    }
+
+    /// Turns an error into an object with its `message`, `kind` (e.g. `"io"`) and `line`, for a `catch` block
+    fn error_object(error: &HawkError) -> Object {
+        let mut fields: HashMap<String, Object> = HashMap::new();
+        fields.insert(String::from("message"), Object::String(error.message.clone()));
+        fields.insert(String::from("kind"), Object::String(String::from(error.kind.id())));
+        fields.insert(String::from("line"), Object::Int(error.line() as i128));
+
+        let class = Class {
+            name: String::from(ERROR_CLASS),
+            parent: None,
+            fields: fields.keys().map(|name| (name.clone(), Expression::Literal(Object::Null))).collect(),
+            methods: HashMap::new(),
+        };
+        Object::Instance { class: Box::new(class), fields }
+    }
+}
+
+#[test]
+fn try_catch() {
+    use hawk_common::environment::Memory;
+
+    let source = String::from("let message = null
+let kind = null
+try {
+    let table = read(\"missing.csv\")
+} catch err {
+    message = err.message
+    kind = err.kind
+}
+
+let thrown = null
+function check(x) {
+    if x < 0 {
+        error(\"x can't be negative\")
+    }
+    return x
+}
+try {
+    check(-1)
+} catch err {
+    thrown = err
+}
+
+let rethrown = null
+try {
+    try {
+        throw(\"inner\")
+    } catch err {
+        throw(err)
+    }
+} catch err {
+    rethrown = err.message
+}
+
+let cleaned_up = false
+try {
+    process \"missing.csv\" \"out.csv\" {
+        let x = 1
+    }
+} catch {
+    cleaned_up = true
+}
+let after = 1
+
+let division = null
+try {
+    let x = 1 / 0
+} catch err {
+    division = err.message
+}
+try {
+    let x = 1.5 / (0 ± 0.1)
+} catch {}");
+    let globals = crate::run::run(
+        source, HashMap::new(), false, &mut Memory::new(),
+    ).unwrap();
+
+    assert!(matches!(globals.get("message"), Some(Object::String(_))));
+    assert_eq!(globals.get("kind"), Some(&Object::String(String::from("io"))));
+    assert_eq!(globals.get("rethrown"), Some(&Object::String(String::from("inner"))));
+    assert_eq!(globals.get("cleaned_up"), Some(&Object::Boolean(true)));
+    assert_eq!(globals.get("division"), Some(&Object::String(String::from("Can't divide by 0"))));
+
+    // The caught error is only in the scope of the `catch` block
+    assert_eq!(globals.get("err"), None);
+
+    // The scope of the `process` block was removed, so `after` is a global
+    assert_eq!(globals.get("after"), Some(&Object::Int(1)));
+
+    if let Some(Object::Instance { fields, .. }) = globals.get("thrown") {
+        assert_eq!(fields.get("message"), Some(&Object::String(String::from("x can't be negative"))));
+        assert_eq!(fields.get("kind"), Some(&Object::String(String::from("runtime"))));
+        assert_eq!(fields.get("line"), Some(&Object::Int(13)));
+    } else {
+        panic!("expected the caught error to be stored")
+    }
+}
@@ -0,0 +1,195 @@
+use std::collections::HashMap;
+
+use crate::Interpreter;
+
+use hawk_common::class::Class;
+use hawk_common::error::HawkError;
+use hawk_common::object::Object;
+use hawk_common::tree::{Expression, Statement};
+
+impl Interpreter<'_> {
+    /// Defines a class in the current scope. The class it inherits from is looked up now, so later changes to it
+    /// don't affect this class
+    pub fn run_class(
+        &mut self, identifier: String, parent: Option<String>, fields: Vec<(String, Expression)>, methods: Vec<Statement>,
+    ) -> Result<(), HawkError> {
+        let parent = match parent {
+            Some(parent) => match self.get_variable(parent.clone()) {
+                Object::Class(class) => Some(Box::new(class)),
+                _ => return Err(HawkError::runtime(format!("Can't inherit from {parent}, which isn't a class"), self.line)),
+            },
+            None => None,
+        };
+
+        let mut class_methods: HashMap<String, Object> = HashMap::new();
+        for method in methods {
+            if let Statement::Function { identifier, params, block } = method {
+                class_methods.insert(identifier, Object::Function { params, block, captured: HashMap::new() });
+            }
+        }
+
+        self.insert_top_scope(identifier.clone(), Object::Class(Class { name: identifier, parent, fields, methods: class_methods }))
+    }
+
+    /// Creates an instance of a class, setting its fields to their initial values and then calling its `init` method
+    /// (if it has one) with the arguments
+    pub fn construct(&mut self, class: Class, args: Vec<Object>) -> Result<Object, HawkError> {
+        let mut fields: HashMap<String, Object> = HashMap::new();
+        for (name, value) in class.all_fields() {
+            let value = self.eval_expression(Box::new(value))?;
+            fields.insert(name, value);
+        }
+        let instance = Object::Instance { class: Box::new(class.clone()), fields };
+
+        if class.find_method("init").is_some() {
+            let (_, instance) = self.call_method(&class, instance, "init", args)?;
+            Ok(instance)
+        } else if args.is_empty() {
+            Ok(instance)
+        } else {
+            Err(HawkError::runtime(format!("{} has no init method, so it can't be given arguments", class.name), self.line))
+        }
+    }
+
+    /// Evaluates `object.method(args)`. Methods can change the object (e.g. the fields of `this`, or `arr.push(x)`), so
+    /// it is stored back in the variable afterwards
+    pub fn eval_method_call(&mut self, object: Box<Expression>, method: String, args: Vec<Box<Expression>>) -> Result<Object, HawkError> {
+        let mut evaled_args: Vec<Object> = Vec::new();
+        for arg in args {
+            evaled_args.push(self.eval_expression(arg)?)
+        }
+
+        // Methods can change the object they are called on, which is kept if it is a variable
+        let variable = Self::variable_name(&object);
+        match self.eval_expression(object)? {
+            // `super.method()` calls the version of the method in the parent class on `this`
+            Object::Class(class) if variable.as_deref() == Some("super") => {
+                let this = self.get_variable(String::from("this"));
+                let (result, this) = self.call_method(&class, this, &method, evaled_args)?;
+                self.set_variable(String::from("this"), this);
+                Ok(result)
+            },
+            Object::Instance { class, fields } => {
+                let (result, instance) = self.call_method(&class.clone(), Object::Instance { class, fields }, &method, evaled_args)?;
+                if let Some(name) = variable {
+                    self.set_variable(name, instance);
+                }
+                Ok(result)
+            },
+            // Methods of arrays, strings, columns and datatables
+            x => {
+                let (result, changed) = self.call_builtin_method(x, &method, evaled_args)?;
+                if let (Some(changed), Some(name)) = (changed, variable) {
+                    self.set_variable(name, changed);
+                }
+                Ok(result)
+            },
+        }
+    }
+
+    /// The name of the variable an expression is, if it is just a variable
+    fn variable_name(expression: &Expression) -> Option<String> {
+        match expression {
+            Expression::Literal(Object::Identifier(name)) => Some(name.clone()),
+            _ => None,
+        }
+    }
+
+    /// Calls a method with `this` set to `instance`, returning the result and the instance (which the method may
+    /// have changed). Inside the method, `super` is the parent of the class the method is defined in
+    fn call_method(
+        &mut self, class: &Class, instance: Object, method: &str, args: Vec<Object>,
+    ) -> Result<(Object, Object), HawkError> {
+        let (function, owner) = class.find_method(method)
+            .ok_or(HawkError::runtime(format!("{} has no method {method}", class.name), self.line))?;
+
+        if let Object::Function { params, block, .. } = function.clone() {
+            if params.len() != args.len() {
+                return Err(HawkError::runtime(format!(
+                    "{}.{method} expects {} arguments, found {}", class.name, params.len(), args.len()
+                ), self.line))
+            }
+
+            let mut scope: HashMap<String, Object> = HashMap::new();
+            scope.insert(String::from("this"), instance);
+            if let Some(parent) = &owner.parent {
+                scope.insert(String::from("super"), Object::Class(*parent.clone()));
+            }
+            for (param, arg) in params.into_iter().zip(args) {
+                scope.insert(param, arg);
+            }
+
+            self.scopes.push(scope);
+            let result = self.in_frame(format!("{}.{method}", class.name), |interpreter| interpreter.run_statement(*block));
+            let scope = self.scopes.pop().unwrap_or_default();
+
+            Ok((
+                self.returned(result?)?,
+                scope.get("this").cloned().unwrap_or(Object::Null),
+            ))
+        } else {
+            Err(HawkError::runtime(format!("{}.{method} is not a function", class.name), self.line))
+        }
+    }
+
+    /// Evaluates `object.field`
+    pub fn eval_field(&mut self, object: Box<Expression>, field: String) -> Result<Object, HawkError> {
+        match self.eval_expression(object)? {
+            Object::Instance { class, fields } => fields.get(&field).cloned()
+                .ok_or(HawkError::runtime(format!("{} has no field {field}", class.name), self.line)),
+            x => Err(HawkError::runtime(format!("Can't get field {field} of {}", x.user_print(self.line)?), self.line)),
+        }
+    }
+
+    /// Runs `object.field = value`
+    pub fn run_field_assign(&mut self, object: String, field: String, value: Box<Expression>) -> Result<(), HawkError> {
+        let value = self.eval_expression(value)?;
+        match self.get_variable(object.clone()) {
+            Object::Instance { class, mut fields } => {
+                fields.insert(field, value);
+                self.set_variable(object, Object::Instance { class, fields });
+                Ok(())
+            },
+            x => Err(HawkError::runtime(format!("Can't set field {field} of {}", x.user_print(self.line)?), self.line)),
+        }
+    }
+}
+
+#[test]
+fn classes() {
+    use hawk_common::environment::Memory;
+
+    let source = String::from("class Counter {
+    let count = 0
+    function add(n) {
+        this.count = this.count + n
+        return this.count
+    }
+}
+class StepCounter(Counter) {
+    let step = 1
+    function init(step) {
+        this.step = step
+    }
+    function add(n) {
+        return super.add(n * this.step)
+    }
+}
+let counter = StepCounter(10)
+counter.add(1)
+let total = counter.add(2)
+counter.step = 0
+let count = counter.count");
+    let globals = crate::run::run(
+        source, HashMap::new(), false, &mut Memory::new(),
+    ).unwrap();
+
+    assert_eq!(globals.get("total"), Some(&Object::Int(30)));
+    assert_eq!(globals.get("count"), Some(&Object::Int(30)));
+    if let Some(Object::Instance { class, fields }) = globals.get("counter") {
+        assert_eq!(class.name, "StepCounter");
+        assert_eq!(fields.get("step"), Some(&Object::Int(0)));
+    } else {
+        panic!("Expected counter to be an instance")
+    }
+}
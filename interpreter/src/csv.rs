@@ -1,38 +1,47 @@
 use unicode_segmentation::UnicodeSegmentation;
 
-use hawk_common::object::Object;
+use hawk_common::environment::Environment;
+use hawk_common::error::HawkError;
+use hawk_common::object::{self, Object};
 use hawk_common::token::{Token, TokenType};
+use hawk_common::units::Unit;
+use hawk_lib::lexer::apply_exponent;
 
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 
-pub fn csv_to_datatable(filename: String, line: usize, filein_fn: fn(String) -> Result<String, String>) -> Result<Object, (String, usize)> {
-    if let Ok(csvfile) = filein_fn(filename.clone()) {
-        let tokens = Lexer::lex(csvfile.as_str());
+pub fn csv_to_datatable(filename: String, line: usize, env: &mut dyn Environment) -> Result<Object, HawkError> {
+    if let Ok(csvfile) = env.read_file(&filename) {
+        let tokens = Lexer::lex(csvfile.as_str(), &mut |message, line| env.warn(message, line))
+            .map_err(|message| HawkError::runtime(format!("In CSV file {filename}: {message}"), line))?;
         parse_csv(tokens, line)
     } else {
-        Err((format!("Couldn't read file: {}", filename), line))
+        Err(HawkError::io(format!("Couldn't read file: {}", filename), line))
     }
 }
 
-fn parse_csv(tokens: Vec<Token>, line: usize) -> Result<Object, (String, usize)> {
-    let (i, titles) = parse_titles(&tokens, line)?;
+fn parse_csv(tokens: Vec<Token>, line: usize) -> Result<Object, HawkError> {
+    let (i, titles, units) = parse_titles(&tokens, line)?;
 
     let values = parse_values(&tokens, i);
 
-    let columns = values_to_columns(&titles, values);
+    let columns = values_to_columns(&titles, &units, values);
     
     Ok(Object::DataTable{names: titles, data: columns})
 }
 
-fn values_to_columns(titles: &Vec<String>, values: Vec<Vec<Object>>) -> Vec<Object> {
+fn values_to_columns(titles: &Vec<String>, units: &Vec<Option<Unit>>, values: Vec<Vec<Object>>) -> Vec<Object> {
     let mut columns: Vec<Object> = Vec::new();
     let num_cols = titles.len();
     let num_rows = values.len();
     for j in 0..num_cols {
         let mut vals = Vec::new();
         for k in 0..num_rows {
-            vals.push(values[k][j].clone());
+            if let Some(unit) = units[j].clone() {
+                vals.push(Object::Quantity{value: Box::new(values[k][j].clone()), unit})
+            } else {
+                vals.push(values[k][j].clone());
+            }
         }
         columns.push(Object::Column(vals));
     }
@@ -60,19 +69,28 @@ fn parse_values(tokens: &Vec<Token>, mut i: usize) -> Vec<Vec<Object>> {
     values
 }
 
-fn parse_titles(tokens: &Vec<Token>, line: usize) -> Result<(usize, Vec<String>), (String, usize)> {
+/// Parses the titles of the columns, along with their units if they're given in brackets (e.g. `t (s)`)
+fn parse_titles(tokens: &Vec<Token>, line: usize) -> Result<(usize, Vec<String>, Vec<Option<Unit>>), HawkError> {
     let mut titles: Vec<String> = Vec::new();
+    let mut units: Vec<Option<Unit>> = Vec::new();
     let mut i = 0;
 
     while tokens[i].token_type != TokenType::NewLine {
-        if let Some(Object::String(title)) = tokens[i].literal.clone() {
-            titles.push(title)
+        if let (TokenType::Unit, Some(Object::String(unit))) = (tokens[i].token_type, tokens[i].literal.clone()) {
+            match (Unit::parse(&unit), units.last_mut()) {
+                (Some(unit), Some(last)) => *last = Some(unit),
+                (None, _) => return Err(HawkError::runtime(format!("Unknown unit {} in CSV file", unit), line)),
+                (_, None) => return Err(HawkError::runtime(format!("Expected title of CSV column before unit {}", unit), line)),
+            }
+        } else if let Some(Object::String(title)) = tokens[i].literal.clone() {
+            titles.push(title);
+            units.push(None)
         } else if tokens[i].token_type == TokenType::Comma {
             
         } else if let Some(x) = tokens[i].literal.clone() {
-            return Err((format!("Expected identifier as title of CSV column, found {}", x), line));
+            return Err(HawkError::runtime(format!("Expected identifier as title of CSV column, found {}", x), line));
         } else {
-            return Err(("Expected a literal, found None (there's probably 2 commas without a value in between in your CSV)".to_string(), line));
+            return Err(HawkError::runtime("Expected a literal, found None (there's probably 2 commas without a value in between in your CSV)".to_string(), line));
         }
 
         i += 1
@@ -80,7 +98,7 @@ fn parse_titles(tokens: &Vec<Token>, line: usize) -> Result<(usize, Vec<String>)
 
     i += 1;
 
-    Ok((i, titles))
+    Ok((i, titles, units))
 }
 
 struct Lexer<'a> {
@@ -88,16 +106,22 @@ struct Lexer<'a> {
     num_chars: usize,
     pub tokens: Vec<Token>,
     line: usize,
-    index: usize
+    index: usize,
+    warn: &'a mut dyn FnMut(String, usize),
+    /// The first value that couldn't be represented
+    error: Option<String>,
 }
 
 impl<'a> Lexer<'a> {
-    pub fn lex(source: &'a str) -> Vec<Token> {
-        let mut code_lexer = Lexer {characters: source.graphemes(true).collect::<Vec<&str>>(), num_chars: 0, tokens: Vec::new(), line: 1, index: 0};
+    pub fn lex(source: &'a str, warn: &'a mut dyn FnMut(String, usize)) -> Result<Vec<Token>, String> {
+        let mut code_lexer = Lexer {characters: source.graphemes(true).collect::<Vec<&str>>(), num_chars: 0, tokens: Vec::new(), line: 1, index: 0, warn, error: None};
 
         code_lexer.lex_csv();
 
-        code_lexer.tokens
+        match code_lexer.error {
+            Some(error) => Err(error),
+            None => Ok(code_lexer.tokens),
+        }
     }
 
     pub fn lex_csv(&mut self) {
@@ -122,6 +146,12 @@ impl<'a> Lexer<'a> {
                 self.add_token(TokenType::NewLine, None)
             },
             "," | "\t" => self.add_token(TokenType::Comma, None),
+            "(" => self.unit(),
+            "-" if self.char_at(self.index).parse::<usize>().is_ok() => {
+                self.consume_char();
+                self.parse_number();
+                self.negate_last_token()
+            },
             _ => {
                 if let Ok(_) = c.parse::<usize>() {
                     self.parse_number()
@@ -134,6 +164,18 @@ impl<'a> Lexer<'a> {
         }
     }
 
+    /// Lexes the unit of a column, given in brackets after its title
+    fn unit(&mut self) {
+        let start = self.index;
+        while self.index < self.num_chars && self.characters[self.index] != ")" && self.characters[self.index] != "\n" {
+            self.consume_char()
+        }
+        self.add_token(TokenType::Unit, Some(Object::String(self.characters[start..self.index].join(""))));
+        if self.char_at(self.index) == ")" {
+            self.consume_char()
+        }
+    }
+
     fn column_name(&mut self, c: char) {
         if c.is_alphabetic() {
             let mut current_token = String::from(c);
@@ -159,11 +201,30 @@ impl<'a> Lexer<'a> {
             (is_float, int) = self.parse_int(is_float, int, num);
         }
 
-        if is_float {   // Get decimal part of number
-            self.parse_float(int);
+        let mantissa = if is_float {   // Get decimal part of number
+            self.parse_float(int)
+        } else {
+            self.index = self.index - 1;
+            Decimal::from(int as i64)
+        };
+
+        if let Some(exponent) = self.parse_exponent() {
+            let number = match apply_exponent(mantissa, exponent) {
+                Ok((number, Some(warning))) => {
+                    (self.warn)(format!("In CSV file: {warning}"), self.line);
+                    number
+                },
+                Ok((number, None)) => number,
+                Err(message) => {
+                    self.error.get_or_insert(format!("{message} (line {})", self.line));
+                    Decimal::ZERO
+                },
+            };
+            self.add_token(TokenType::Decimal, Some(Object::Decimal(number)));
+        } else if is_float {
+            self.add_token(TokenType::Decimal, Some(Object::Decimal(mantissa)));
         } else {
             self.add_token(TokenType::Int, Some(Object::Int(int as i128)));
-            self.index = self.index - 1
         }
     }
 
@@ -180,7 +241,7 @@ impl<'a> Lexer<'a> {
         (is_float, int)
     }
 
-    fn parse_float(&mut self, int: usize) {
+    fn parse_float(&mut self, int: usize) -> Decimal {
         let mut decimal: Decimal = dec!(0);
         let mut decimal_digits: Vec<usize> = Vec::new();
 
@@ -193,9 +254,48 @@ impl<'a> Lexer<'a> {
             decimal = (decimal + Decimal::from(*i as i64)) / dec!(10);
         }
 
-        let number = Decimal::from(int as i64) + decimal;
+        Decimal::from(int as i64) + decimal
+    }
+
+    /// Parses the exponent of a cell in scientific notation, e.g. the `E-23` in `1.38E-23`
+    fn parse_exponent(&mut self) -> Option<i64> {
+        let (sign, digits_start) = match (self.char_at(self.index), self.char_at(self.index + 1)) {
+            ("e" | "E", "-") => (-1, self.index + 2),
+            ("e" | "E", "+") => (1, self.index + 2),
+            ("e" | "E", _) => (1, self.index + 1),
+            _ => return None
+        };
+
+        if let Err(_) = self.char_at(digits_start).parse::<usize>() {
+            return None
+        }
 
-        self.add_token(TokenType::Decimal, Some(Object::Decimal(number)));
+        self.index = digits_start;
+        let mut exponent: i64 = 0;
+        while let Ok(num) = self.char_at(self.index).parse::<i64>() {
+            exponent = exponent.saturating_mul(10).saturating_add(num);
+            self.consume_char()
+        }
+
+        Some(sign * exponent)
+    }
+
+    fn char_at(&self, index: usize) -> &'a str {
+        if index < self.num_chars {
+            self.characters[index]
+        } else {
+            ""
+        }
+    }
+
+    fn negate_last_token(&mut self) {
+        if let Some(token) = self.tokens.last_mut() {
+            token.literal = match token.literal.take() {
+                Some(Object::Int(x)) => Some(Object::Int(-x)),
+                Some(Object::Decimal(x)) => Some(Object::Decimal(-x)),
+                literal => literal
+            }
+        }
     }
 
     fn consume_char(&mut self) {
@@ -203,76 +303,94 @@ impl<'a> Lexer<'a> {
     }
 
     fn add_token(&mut self, token_type: TokenType, literal: Option<Object>) {
-        self.tokens.push(Token::new(token_type, self.line, literal))
+        self.tokens.push(Token::new(token_type, self.line, 0, 0, literal))
     }
 }
 
 /// Writes an `Object::DataTable` to a `.csv` file
-pub fn datatable_to_csv(filename: String, datatable: Object, line: usize, fileout_fn: fn(String, String) -> Result<(), ()>) -> Result<(), (String, usize)> {
+pub fn datatable_to_csv(filename: String, datatable: Object, line: usize, env: &mut dyn Environment) -> Result<(), HawkError> {
     if let Object::DataTable{names: _, data: _} = &datatable {
         let str = datatable.format_for_csv(line)?;
-        if let Ok(()) = fileout_fn(filename.clone(), str) {
+        if let Ok(()) = env.write_file(&filename, &str) {
             ()
         } else {
-            return Err((format!("Couldn't write to file {}", filename), line));
+            return Err(HawkError::io(format!("Couldn't write to file {}", filename), line));
         }
     } else {
-        return Err((format!("Expected datatable, found {}", datatable.user_print(line)?), line));
+        return Err(HawkError::runtime(format!("Expected datatable, found {}", datatable.user_print(line)?), line));
     }
 
     Ok(())
 }
 
 pub trait CSV {
-    fn format_for_csv(&self, line: usize) -> Result<String, (String, usize)>;
-    fn format_datatable_csv(names: Vec<String>, data: Vec<Object>, line: usize) -> Result<String, (String, usize)>;
-    fn format_datatable_csv_data(data: Vec<Object>, str: String, len: usize, line: usize) -> Result<String, (String, usize)>;
-    fn format_datatable_csv_column_names(names: Vec<String>, str: String) -> Result<String, (String, usize)>;
+    fn format_for_csv(&self, line: usize) -> Result<String, HawkError>;
+    fn format_datatable_csv(names: Vec<String>, data: Vec<Object>, line: usize) -> Result<String, HawkError>;
+    fn format_datatable_csv_data(data: Vec<Object>, str: String, len: usize, line: usize) -> Result<String, HawkError>;
+    fn format_datatable_csv_column_names(names: Vec<String>, str: String) -> Result<String, HawkError>;
 }
 
 impl CSV for Object {
     /// Generates a string representation of the Object that is suitable for a `.csv` file
-    fn format_for_csv(&self, line: usize) -> Result<String, (String, usize)> {
+    fn format_for_csv(&self, line: usize) -> Result<String, HawkError> {
         match self.clone() {
             Self::Boolean(x) => Ok(format!("{x}")),
-            Self::Decimal(x) => Ok(format!("{x}")),
+            Self::Decimal(x) => Ok(object::format_decimal(x)),
             Self::Int(x) => Ok(format!("{x}")),
             Self::String(x) => Ok(format!("{x}")),
-            Self::Uncertain{value, uncertainty: _} => Ok(format!("{value}")),
+            Self::Uncertain{value, ..} => Ok(object::format_decimal(value)),
+            Self::Quantity{value, ..} => value.format_for_csv(line),
             Self::DataTable{names, data} => {
                 Self::format_datatable_csv(names, data, line)
             },
             _ => {
-                Err((format!("Can't write {} to CSV", self), line))
+                Err(HawkError::runtime(format!("Can't write {} to CSV", self), line))
             }
         }
     }
 
-    fn format_datatable_csv(names: Vec<String>, data: Vec<Object>, line: usize) -> Result<String, (String, usize)> {
+    fn format_datatable_csv(names: Vec<String>, data: Vec<Object>, line: usize) -> Result<String, HawkError> {
         let mut str = String::from("");
 
         let mut names = names.clone();
 
+        // Units go in the titles of the columns, e.g. `t (s)`
+        let mut data = data.clone();
+        for (i, col) in data.iter_mut().enumerate() {
+            if let Object::Column(vals) = col {
+                if let Some(Object::Quantity{unit, ..}) = vals.get(0).cloned() {
+                    for val in vals.iter_mut() {
+                        if let Object::Quantity{value, unit: val_unit} = val {
+                            *val = crate::Interpreter::convert(*value.clone(), val_unit, &unit, line)?
+                        } else {
+                            return Err(HawkError::runtime(format!("Expected every value in column {} to have units, found {}", names[i], val.user_print(line)?), line))
+                        }
+                    }
+                    names[i] = format!("{} ({})", names[i], unit)
+                }
+            }
+        }
+
         let mut new_data = data.clone();
 
         let len: usize;
         if let Object::Column(vals) = data[0].clone() {
             len = vals.len()
         } else {
-            return Err((format!("Expected column, found {}", data[0].user_print(line)?), line))
+            return Err(HawkError::runtime(format!("Expected column, found {}", data[0].user_print(line)?), line))
         }
 
         let mut added_columns = 0;
 
         for (i, col) in data.iter().enumerate() {
             if let Object::Column(a) = col {
-                if let Object::Uncertain{value: _, uncertainty: _} = a[0] {
+                if let Object::Uncertain{..} = a[0] {
                     names.insert(i + added_columns + 1, format!("uncertainty_{}", names[i + added_columns]));
 
                     let mut vals = Vec::new();
                     let mut uncerts = Vec::new();
                     for obj in a {
-                        if let Object::Uncertain{value, uncertainty} = obj {
+                        if let Object::Uncertain{value, uncertainty, ..} = obj {
                             vals.push(Object::Decimal(*value));
                             uncerts.push(Object::Decimal(*uncertainty));   
                         }
@@ -283,7 +401,7 @@ impl CSV for Object {
                     added_columns += 1;
                 }
             } else {
-                return Err((format!("Expected column, found {}", data[0].user_print(line)?), line));
+                return Err(HawkError::runtime(format!("Expected column, found {}", data[0].user_print(line)?), line));
             }
         }
 
@@ -292,7 +410,7 @@ impl CSV for Object {
         Ok(str)
     }
 
-    fn format_datatable_csv_data(data: Vec<Object>, mut str: String, len: usize, line: usize) -> Result<String, (String, usize)> {
+    fn format_datatable_csv_data(data: Vec<Object>, mut str: String, len: usize, line: usize) -> Result<String, HawkError> {
         for i in 0..len {
             str = format!("{str}\n");
             for (idx, column) in data.iter().enumerate() {
@@ -308,7 +426,7 @@ impl CSV for Object {
         Ok(str)
     }
 
-    fn format_datatable_csv_column_names(names: Vec<String>, mut str: String) -> Result<String, (String, usize)> {
+    fn format_datatable_csv_column_names(names: Vec<String>, mut str: String) -> Result<String, HawkError> {
         for (idx, name) in names.iter().enumerate() {
             if idx < names.len() - 1 {
                 str = format!("{str}{name}, ");
@@ -318,4 +436,39 @@ impl CSV for Object {
         }
         Ok(str)
     }
-}
\ No newline at end of file
+}
+#[test]
+fn csv_units() {
+    let tokens = Lexer::lex("t (s), v (m/s)\n1, 2.5", &mut |_, _| ()).unwrap();
+    let seconds = Unit::parse("s").unwrap();
+
+    if let Ok(Object::DataTable { names, data }) = parse_csv(tokens, 0) {
+        assert_eq!(names, vec!["t".to_owned(), "v".to_owned()]);
+        assert_eq!(data[0], Object::Column(vec![Object::Quantity{value: Box::new(Object::Int(1)), unit: seconds.clone()}]));
+
+        let csv = Object::DataTable { names, data }.format_for_csv(0);
+        assert_eq!(csv, Ok("t (s), v (m/s)\n1, 2.5".to_owned()));
+    } else {
+        panic!("Couldn't parse CSV")
+    }
+}
+
+#[test]
+fn csv_scientific_notation() {
+    let tokens = Lexer::lex("t, q\n1.5e3, -1.602176634E-19\n2, 3e+2", &mut |_, _| ()).unwrap();
+
+    if let Ok(Object::DataTable { names, data }) = parse_csv(tokens, 0) {
+        assert_eq!(names, vec!["t".to_owned(), "q".to_owned()]);
+        assert_eq!(data, vec![
+            Object::Column(vec![Object::Decimal(dec!(1500)), Object::Int(2)]),
+            Object::Column(vec![Object::Decimal(dec!(-0.0000000000000000001602176634)), Object::Decimal(dec!(300))]),
+        ]);
+    } else {
+        panic!("Couldn't parse CSV")
+    }
+
+    assert_eq!(
+        Lexer::lex("q\n1e-40", &mut |_, _| ()).unwrap_err(),
+        "1e-40 is too small to be represented, numbers can only have 28 decimal places (line 2)",
+    );
+}
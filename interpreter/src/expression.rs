@@ -1,19 +1,23 @@
 use std::collections::HashMap;
 
 use crate::Interpreter;
-use crate::placeholder_cas::GetVars;
+use crate::standard_lib::standard_lib_hawk::get_std_hawk_fn;
+use hawk_cas::equation::Equation;
+use hawk_cas::ToCASExpr;
+use hawk_common::error::HawkError;
 use hawk_common::object::Object;
+use hawk_common::uncertainty::ErrorSources;
 
 // Common types used throughout the interpreter
 use hawk_common::token::TokenType;
-use hawk_common::tree::Expression;
+use hawk_common::tree::{Expression, Statement};
 
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 
-impl Interpreter {
+impl Interpreter<'_> {
     /// Traverses an expression tree to evaluate it and return an Object
-    pub fn eval_expression(&mut self, expression: Box<Expression>) -> Result<Object, (String, usize)> {
+    pub fn eval_expression(&mut self, expression: Box<Expression>) -> Result<Object, HawkError> {
         match *expression {
             Expression::Binary {
                 operand1,
@@ -30,46 +34,37 @@ impl Interpreter {
             },
             Expression::Array(exprs) => self.eval_array_literal(exprs),
             Expression::ArrayIndex { identifier, index } => self.eval_arrayindex(identifier, index),
-            _ => Ok(Object::Null),
+            Expression::MethodCall { object, method, args } => self.eval_method_call(object, method, args),
+            Expression::Field { object, field } => self.eval_field(object, field),
+            Expression::Parenthesized(expr) => self.eval_expression(expr),
+            Expression::Lambda { params, body } => Ok(Object::Function { params, block: body, captured: self.capture() }),
+            Expression::Record(fields) => {
+                let mut values: HashMap<String, Object> = HashMap::new();
+                for (name, value) in fields {
+                    values.insert(name, self.eval_expression(value)?);
+                }
+                Ok(Object::from(values))
+            },
         }
     }
 
     /// Match operator and call method to evaluate binary operation
     fn eval_binary(
         &mut self, operand1: Box<Expression>, operand2: Box<Expression>, operator: TokenType,
-    ) -> Result<Object, (String, usize)> {
+    ) -> Result<Object, HawkError> {
         let operand1 = self.eval_expression(operand1)?;
         let operand2 = self.eval_expression(operand2)?;
 
-        match operator {
-            TokenType::Plus => Self::add(operand1, operand2, self.line),
-            TokenType::Minus => Self::subtract(operand1, operand2, self.line),
-            TokenType::Asterisk => Self::multiply(operand1, operand2, self.line),
-            TokenType::Slash => Self::divide(operand1, operand2, self.line),
-            TokenType::Caret => Self::exponent(operand1, operand2, self.line),
-            TokenType::PlusMinus => Self::make_uncertain(operand1, operand2, self.line),
-            TokenType::EqualEqual => Self::equalequal(operand1, operand2, self.line),
-            TokenType::NotEqual => Self::notequal(operand1, operand2, self.line),
-            TokenType::Or => Self::or(operand1, operand2, self.line),
-            TokenType::And => Self::and(operand1, operand2, self.line),
-            TokenType::LessThan => Self::lessthan(operand1, operand2, self.line),
-            TokenType::LessThanEqual => Self::lessthanequal(operand1, operand2, self.line),
-            TokenType::GreaterThan => Self::greaterthan(operand1, operand2, self.line),
-            TokenType::GreaterThanEqual => Self::greaterthanequal(operand1, operand2, self.line),
-            _ => Err((
-                "Couldn't evaluate binary expression: operator does not match any binary operator".to_string(),
-                self.line,
-            )),
-        }
+        Self::binary_operation(operand1, operand2, operator, self.propagation, self.line)
     }
 
     /// Match operator and call method to evaluate unary expression
-    fn eval_unary(&mut self, operand: Box<Expression>, operator: TokenType) -> Result<Object, (String, usize)> {
+    fn eval_unary(&mut self, operand: Box<Expression>, operator: TokenType) -> Result<Object, HawkError> {
         let eval_op = self.eval_expression(operand)?;
         match operator {
             TokenType::Minus => Self::negate(eval_op, self.line),
             TokenType::Not => Self::not(eval_op, self.line),
-            _ => Err((
+            _ => Err(HawkError::runtime(
                 format!("Error: expected binary operator, instead found {:?}", operator),
                 self.line,
             )),
@@ -77,7 +72,7 @@ impl Interpreter {
     }
 
     /// Evaluates literal expression
-    fn eval_literal(&mut self, obj: Object) -> Result<Object, (String, usize)> {
+    fn eval_literal(&mut self, obj: Object) -> Result<Object, HawkError> {
         if let Object::Identifier(identifier) = obj {
             Ok(self.get_variable(identifier)) // Dereference if `obj` is an identifier
         } else {
@@ -86,33 +81,44 @@ impl Interpreter {
     }
 
     /// Calls function, taking into account uncertainties and columns in order to
-    fn eval_function_call(&mut self, identifier: String, args: Vec<Box<Expression>>) -> Result<Object, (String, usize)> {
-        let mut uncertain_index = 0;
-        let mut has_uncertain = false;
+    fn eval_function_call(&mut self, identifier: String, args: Vec<Box<Expression>>) -> Result<Object, HawkError> {
+        if let Some(function) = Self::get_std_special_fn(&identifier) {
+            if !matches!(self.get_variable(identifier.clone()), Object::Function { .. }) {
+                return function(self, &identifier, args)
+            }
+        }
+
         let mut evaled_args: Vec<Object> = Vec::new();
+        for arg in args {
+            evaled_args.push(self.eval_expression(arg)?)
+        }
+
+        // Calling a class creates an instance of it
+        if let Object::Class(class) = self.get_variable(identifier.clone()) {
+            return self.construct(class, evaled_args)
+        }
+
+        self.call_function_with_args(identifier, evaled_args)
+    }
+
+    /// Calls a function with evaluated arguments. Columns are handled by calling the function once per row, and
+    /// uncertain arguments by propagating their uncertainty through the function
+    fn call_function_with_args(&mut self, identifier: String, evaled_args: Vec<Object>) -> Result<Object, HawkError> {
+        let mut uncertain_indices: Vec<usize> = Vec::new();
         let mut columns: Vec<usize> = Vec::new();
 
         let mut arr_of_cols = -1;
 
-        for (index, arg) in args.iter().enumerate() {
-            let arg = self.eval_expression(arg.clone())?;
-            if let Object::Uncertain { value, uncertainty } = arg {
-                if has_uncertain {
-                    return Err(("Functions can only have one argument with an uncertainty".to_string(), self.line));
-                }
-                has_uncertain = true;
-                uncertain_index = index;
-                evaled_args.push(Object::Uncertain { value, uncertainty })
-            } else if let Object::Column(vals) = arg {
-                columns.push(index);
-                evaled_args.push(Object::Column(vals))
-            } else if let Object::Array(vals) = arg.clone() {
-                if let Object::Column(_) = vals[0] {
-                    arr_of_cols = index as isize;
-                    evaled_args.push(arg)
-                }
-            } else {
-                evaled_args.push(arg)
+        for (index, arg) in evaled_args.iter().enumerate() {
+            match arg {
+                Object::Uncertain { .. } => uncertain_indices.push(index),
+                Object::Column(_) => columns.push(index),
+                Object::Array(vals) => {
+                    if let Some(Object::Column(_)) = vals.get(0) {
+                        arr_of_cols = index as isize;
+                    }
+                },
+                _ => ()
             }
         }
 
@@ -125,7 +131,7 @@ impl Interpreter {
                     }
                 }
             } else {
-                return Err((format!("Unreachable error"), self.line))
+                return Err(HawkError::runtime(format!("Unreachable error"), self.line))
             }
 
             let mut results: Vec<Object> = Vec::new();
@@ -135,27 +141,43 @@ impl Interpreter {
                 for col in final_params.iter() {
                     args.push(col[i].clone())
                 }
-                let result = self.eval_function_call(identifier.clone(), vec![Box::new(Expression::Literal(Object::Array(args)))])?;
+                let result = self.call_function_with_args(identifier.clone(), vec![Object::Array(args)])?;
                 results.push(result)
             }
 
             Ok(Object::Column(results))
-        }
-        else if columns.len() > 0 {
-            Ok(Object::Null)
-        } else if has_uncertain {
-            self.call_function_with_uncertainty(identifier, evaled_args, uncertain_index)
-        } else {
-            let mut evaled_args: Vec<Object> = Vec::new();
-            for arg in args {
-                evaled_args.push(self.eval_expression(arg)?)
+        } else if columns.len() > 0 {
+            let mut rows = usize::MAX;
+            for index in columns.iter() {
+                if let Object::Column(vals) = &evaled_args[*index] {
+                    rows = rows.min(vals.len())
+                }
+            }
+
+            let mut results: Vec<Object> = Vec::new();
+
+            for i in 0..rows {
+                let mut args: Vec<Object> = Vec::new();
+                for arg in evaled_args.iter() {
+                    if let Object::Column(vals) = arg {
+                        args.push(vals[i].clone())
+                    } else {
+                        args.push(arg.clone())
+                    }
+                }
+                results.push(self.call_function_with_args(identifier.clone(), args)?)
             }
+
+            Ok(Object::Column(results))
+        } else if uncertain_indices.len() > 0 {
+            self.call_function_with_uncertainty(identifier, evaled_args, uncertain_indices)
+        } else {
             self.call_function(identifier, evaled_args)
         }
     }
 
     /// Calls function, taking into account uncertainties and columns in order to
-    fn eval_finder_call(&mut self, identifier: String, given: HashMap<String, Expression>, to_find: String) -> Result<Object, (String, usize)> {
+    fn eval_finder_call(&mut self, identifier: String, given: HashMap<String, Expression>, to_find: String) -> Result<Object, HawkError> {
         let finder;
         if let Some(x) = crate::standard_lib::standard_lib_hawk::get_std_finder(identifier.clone()) {
             finder = x
@@ -164,48 +186,64 @@ impl Interpreter {
         }
 
         if let Object::Finder(equations) = finder {
-            self.scopes.push(HashMap::new());
-            let mut viable_eq = (Expression::Literal(Object::Null), Expression::Literal(Object::Null));
-            let mut found_eq = false;
-
-            for equation in equations {
-                let mut lhs_contains = equation.0.get_variables()?;
-                let mut rhs_contains = equation.1.get_variables()?;
-
-                lhs_contains.append(&mut rhs_contains);
-
-                for var in lhs_contains {
-                    if !(given.contains_key(&var) || var == to_find) {
-                        continue;
-                    } else {
-                        viable_eq = equation;
-                        found_eq = true;
-                        break
-                    }
+            // Equations with the unknown in them, rearranged by the CAS
+            let mut candidates: Vec<Equation> = Vec::new();
+            for (lhs, rhs) in equations {
+                match (lhs.to_cas_expr(), rhs.to_cas_expr()) {
+                    (Some(lhs), Some(rhs)) => {
+                        if lhs.contains(to_find.clone()) || rhs.contains(to_find.clone()) {
+                            candidates.push(Equation::new(lhs, rhs))
+                        }
+                    },
+                    _ => return Err(HawkError::runtime("Expression not supported in finder".to_string(), self.line))
                 }
             }
 
-            if !found_eq {
-                return Err(("No viable equation found".to_string(), self.line));
+            if candidates.is_empty() {
+                return Err(HawkError::runtime("No viable equation found".to_string(), self.line));
             }
 
-            for (key, value) in given {
-                let value = self.eval_expression(Box::new(value))?;
-                self.insert_top_scope(key, value)?;
-            }
+            // Use an equation where every other variable is given, or say what is missing from the closest one
+            let missing = |equation: &Equation| -> Vec<String> {
+                equation.symbols().into_iter().filter(|var| *var != to_find && !given.contains_key(var)).collect()
+            };
+            let viable = match candidates.iter().position(|equation| missing(equation).is_empty()) {
+                Some(viable) => viable,
+                None => {
+                    let missing = candidates.iter().map(missing).min_by_key(Vec::len).unwrap_or_default();
+                    return Err(HawkError::runtime(format!("Can't find {to_find} without {}", missing.join(", ")), self.line))
+                }
+            };
 
-            let result = self.eval_expression(Box::new(crate::placeholder_cas::Equation::solve_for(viable_eq.0, viable_eq.1, to_find)?))?;
+            let solved = candidates.remove(viable).solve_for(to_find)
+                .map_err(|message| HawkError::runtime(message, self.line))?;
 
+            self.scopes.push(HashMap::new());
+            let result = self.eval_finder_solution(given, Expression::from_cas_expr(solved));
             self.scopes.pop();
 
-            Ok(result)
+            result
         } else {
-            Err((format!("Expected finder, instead got {finder}"), self.line))
+            Err(HawkError::runtime(format!("Expected finder, instead got {finder}"), self.line))
         }
     }
 
+    /// Evaluates the rearranged equation of a finder with the given values. Rearranging divides values that were
+    /// multiplied, so integers are given as decimals to avoid integer division
+    fn eval_finder_solution(&mut self, given: HashMap<String, Expression>, solved: Expression) -> Result<Object, HawkError> {
+        for (key, value) in given {
+            let value = match self.eval_expression(Box::new(value))? {
+                Object::Int(x) => Object::Decimal(Decimal::from(x as i64)),
+                value => value,
+            };
+            self.insert_top_scope(key, value)?;
+        }
+
+        self.eval_expression(Box::new(solved))
+    }
+
     /// Turns array literal into array object
-    fn eval_array_literal(&mut self, exprs: Vec<Box<Expression>>) -> Result<Object, (String, usize)> {
+    fn eval_array_literal(&mut self, exprs: Vec<Box<Expression>>) -> Result<Object, HawkError> {
         let mut vals: Vec<Object> = Vec::new();
         for expr in exprs {
             vals.push(self.eval_expression(expr)?);
@@ -214,7 +252,7 @@ impl Interpreter {
     }
 
     /// Gets index of array
-    fn eval_arrayindex(&mut self, identifier: String, index: Box<Expression>) -> Result<Object, (String, usize)> {
+    fn eval_arrayindex(&mut self, identifier: String, index: Box<Expression>) -> Result<Object, HawkError> {
         let index = self.eval_expression(index)?; // Evaluate the array index
 
         if let Object::Int(index) = index {
@@ -223,97 +261,225 @@ impl Interpreter {
                 if let Object::Array(array) | Object::Column(array) = array {
                     Ok(array[index as usize].clone())
                 } else {
-                    Err(("Can only index an array or column".to_string(), self.line))
+                    Err(HawkError::runtime("Can only index an array or column".to_string(), self.line))
                 }
             } else {
-                Err(("Index must be 0 or above".to_string(), self.line))
+                Err(HawkError::runtime("Index must be 0 or above".to_string(), self.line))
             }
         } else {
-            Err(("Index must be an int".to_string(), self.line))
+            Err(HawkError::runtime("Index must be an int".to_string(), self.line))
         }
     }
 
-    /// Calls a function where one argument is an `Uncertain`, using a maximum and minimum value to find the uncertainty
+    /// Calls a function where arguments are `Uncertain`. The error sources of the arguments are propagated with the
+    /// partial derivative with respect to each uncertain argument (see `partial_derivative`), so the result stays
+    /// correlated with the arguments
     fn call_function_with_uncertainty(
-        &mut self, identifier: String, mut evaled_args: Vec<Object>, uncertain_index: usize,
-    ) -> Result<Object, (String, usize)> {
-        if let Object::Uncertain { value, uncertainty } = evaled_args[uncertain_index].clone() {
-            // Change uncertain arg to `value + uncertainty` to find max
-            evaled_args[uncertain_index] = Object::Decimal(value + uncertainty);
-            let max;
-            match self.call_function(identifier.clone(), evaled_args.clone())? {
-                Object::Decimal(x) => max = x,
-                Object::Int(x) => max = Decimal::from(x as i64),
-                x => {
-                    return Err((format!("Expected Decimal or Int, got {x}"), self.line));
-                }
+        &mut self, identifier: String, evaled_args: Vec<Object>, uncertain_indices: Vec<usize>,
+    ) -> Result<Object, HawkError> {
+        let mut uncertain_args: Vec<(usize, Decimal, ErrorSources)> = Vec::new();
+        let mut args = evaled_args;
+
+        // Call the function with the values of the arguments to find the value
+        for index in uncertain_indices {
+            if let Object::Uncertain { value, uncertainty, sources } = args[index].clone() {
+                uncertain_args.push((index, uncertainty, sources));
+                args[index] = Object::Decimal(value);
             }
+        }
 
-            // Change uncertain arg to `value - uncertainty` to find min
-            evaled_args[uncertain_index] = Object::Decimal(value - uncertainty);
-            let min;
-            match self.call_function(identifier.clone(), evaled_args.clone())? {
-                Object::Decimal(x) => min = x,
-                Object::Int(x) => min = Decimal::from(x as i64),
-                x => {
-                    return Err((format!("Expected Decimal or Int, got {x}"), self.line));
-                }
-            }
+        let result = self.call_function(identifier.clone(), args.clone())?;
+        let (value, mut sources) = if let Some(parts) = Self::uncertain_parts(&result) {
+            parts
+        } else {
+            return Err(HawkError::runtime(format!("Expected Decimal or Int, got {result}"), self.line));
+        };
 
-            // Change uncertain arg to `value` to find value
-            evaled_args[uncertain_index] = Object::Decimal(value);
-            let val;
-            match self.call_function(identifier, evaled_args)? {
-                Object::Decimal(x) => val = x,
-                Object::Int(x) => val = Decimal::from(x as i64),
-                x => {
-                    return Err((format!("Expected Decimal or Int, got {x}"), self.line));
-                }
+        for (index, uncertainty, arg_sources) in uncertain_args {
+            if uncertainty.is_zero() {
+                continue;
             }
-            Ok(Object::Uncertain {
-                value: val,
-                uncertainty: ((max - min) / dec!(2)).abs(),
-            })
-        } else {
-            Err(("`AAAAH why in the world is this not an uncertain that's literally impossible Rust just forced me to include this error here don't mind me. Most likely explanation is that your computer was struck by lightning or particles from outer space.".to_string(), self.line))
+
+            let derivative = self.partial_derivative(&identifier, &args, index)?;
+            sources = sources.combine(dec!(1), &arg_sources, derivative);
         }
+
+        Ok(Object::Uncertain {
+            value,
+            uncertainty: self.propagation.uncertainty(&sources),
+            sources,
+        })
     }
 
-    pub fn call_function(&mut self, identifier: String, args: Vec<Object>) -> Result<Object, (String, usize)> {
-        if let Object::Function { params, block } = self.get_variable(identifier.clone()) {
-            self.scopes.push(HashMap::new());
-            for (index, param) in params.iter().enumerate() {
-                let arg = args[index].clone();
-                self.insert_top_scope(param.clone(), arg)?;
-            }
-            self.run_statement(*block)?;
-            let result = self.get_variable(String::from("return"));
-            self.scopes.pop();
-            Ok(result)
+    pub fn call_function(&mut self, identifier: String, args: Vec<Object>) -> Result<Object, HawkError> {
+        if let Object::Function { params, block, captured } = self.get_variable(identifier.clone()) {
+            self.call_function_object(identifier, params, block, captured, args)
+        } else if let Some(function) = self.natives.get(&identifier) {
+            function.clone().call(args, self.line)
         } else {
-            let check_std = self.run_fn_std(identifier.clone(), args.clone())?; // Check if function exists in standard library
-            if let Some(Object::Function { params, block }) = check_std.clone() {
-                self.globals.insert(
-                    identifier.clone(),
-                    Object::Function {
-                        params: params.clone(),
-                        block: block.clone(),
-                    },
-                );
-                self.scopes.push(HashMap::new());
-                for (index, param) in params.iter().enumerate() {
-                    let val = args[index].clone();
-                    self.insert_top_scope(param.clone(), val)?;
-                }
-                self.run_statement(*block)?;
-                let result = self.get_variable(String::from("return"));
-                self.scopes.pop();
-                Ok(result)
-            } else if let Some(x) = check_std {
+            // Check if function exists in standard library. Functions written in Rust may return functions (e.g.
+            // `simplify`), so only those written in Hawk are kept as globals and called
+            if let Some(x) = self.get_std_rs_fn(identifier.clone(), args.clone())? {
                 Ok(x)
+            } else if let Some(function @ Object::Function { .. }) = get_std_hawk_fn(identifier.clone()) {
+                self.globals.insert(identifier.clone(), function);
+                self.call_function(identifier, args)
             } else {
-                Err((format!("The variable {identifier} does not appear to be a function. Did you define it? Is it in a file you haven't imported?"), self.line))
+                Err(HawkError::runtime(format!("The variable {identifier} does not appear to be a function. Did you define it? Is it in a file you haven't imported?"), self.line))
             }
         }
     }
+
+    /// Copies the variables in local scopes, for a function defined here to use later. Globals aren't copied, since
+    /// they can always be reached
+    pub fn capture(&self) -> HashMap<String, Object> {
+        let mut captured: HashMap<String, Object> = HashMap::new();
+        for scope in self.scopes.iter() {
+            captured.extend(scope.iter().map(|(name, value)| (name.clone(), value.clone())));
+        }
+        captured
+    }
+
+    /// Calls a function given as an object: either a user defined function, or the name of a function as a string
+    /// (so that standard library functions can be passed to e.g. `map`)
+    pub fn call(&mut self, function: &Object, args: Vec<Object>) -> Result<Object, HawkError> {
+        match function {
+            Object::Function { params, block, captured } => self.call_function_object(
+                String::from("anonymous function"), params.clone(), block.clone(), captured.clone(), args,
+            ),
+            Object::String(identifier) => self.call_function(identifier.clone(), args),
+            x => Err(HawkError::runtime(format!("Expected a function, found {}", x.user_print(self.line)?), self.line)),
+        }
+    }
+
+    /// Runs the code of a function called `name` in a new scope with its captured variables and its parameters set
+    /// to the arguments, returning the value it returns
+    fn call_function_object(
+        &mut self, name: String, params: Vec<String>, block: Box<Statement>, captured: HashMap<String, Object>, args: Vec<Object>,
+    ) -> Result<Object, HawkError> {
+        if params.len() != args.len() {
+            return Err(HawkError::runtime(format!("Expected {} arguments, found {}", params.len(), args.len()), self.line))
+        }
+
+        let mut scope = captured;
+        scope.extend(params.into_iter().zip(args));
+        self.scopes.push(scope);
+        let result = self.in_frame(name, |interpreter| interpreter.run_statement(*block));
+        self.scopes.pop();
+
+        self.returned(result?)
+    }
+}
+
+#[test]
+fn closures() {
+    use hawk_common::environment::Memory;
+
+    let source = String::from("let k = 3
+let scale = (x) => x * k
+function adder(n) {
+    return (x) => x + n
+}
+let add5 = adder(5)
+let n = 100
+let added = add5(1)
+let scaled = map([1, 2], scale)
+let total = reduce([1, 2, 3], (a, b) => a + b, 0)
+let sorted = sort_by([3, -5, 1], x => x^2)");
+    let globals = crate::run::run(
+        source, HashMap::new(), false, &mut Memory::new(),
+    ).unwrap();
+
+    assert_eq!(globals.get("added"), Some(&Object::Int(6)));
+    assert_eq!(globals.get("scaled"), Some(&Object::Array(vec![Object::Int(3), Object::Int(6)])));
+    assert_eq!(globals.get("total"), Some(&Object::Int(6)));
+    assert_eq!(globals.get("sorted"), Some(&Object::Array(vec![Object::Int(1), Object::Int(3), Object::Int(-5)])));
+}
+
+#[test]
+fn traceback() {
+    use hawk_common::environment::Memory;
+    use hawk_common::error::Frame;
+
+    let source = String::from("function inner(x) {
+    let y = x + 1
+    return y / zero_div
+}
+function outer(x) {
+    return inner(x)
+}
+let zero_div = \"text\"
+
+print outer(1)");
+    let error = crate::run::run(
+        source, HashMap::new(), false, &mut Memory::new(),
+    ).unwrap_err().remove(0);
+
+    // The error is on the line in `inner` where it happened, and the traceback shows the calls that led there
+    assert_eq!(error.line(), 3);
+    assert_eq!(error.source_line, Some(String::from("    return y / zero_div")));
+    assert_eq!(error.frames(), [
+        Frame { function: String::from("outer"), line: 10, file: None },
+        Frame { function: String::from("inner"), line: 6, file: None },
+    ]);
+
+    // Errors after a function returns are on the right line
+    let source = String::from("function f(x) {
+    return x
+}
+let a = f(1)
+let b = a + \"text\"");
+    let error = crate::run::run(
+        source, HashMap::new(), false, &mut Memory::new(),
+    ).unwrap_err().remove(0);
+    assert_eq!(error.line(), 5);
+    assert!(error.frames().is_empty());
+}
+
+#[test]
+fn finders() {
+    use hawk_common::environment::Memory;
+
+    let source = String::from("finder pendulum {
+    equation t = 2 * pi() * (l / g)^0.5
+}
+finder motion {
+    equation s = u * t + a * t^2 / 2
+    equation v = u + a * t
+}
+finder decay {
+    equation n = n0 * e()^(-k * t)
+}
+let force = find force(f: 3 ± 0.3, m?, a: 1.5)
+let length = find pendulum(t: 2, g: 9.81, l?)
+let speed = find motion(v: 10, a: 2, t: 3, u?)
+let rate = find decay(n: 50, n0: 100, t: 2, k?)");
+    let globals = crate::run::run(
+        source, HashMap::new(), false, &mut Memory::new(),
+    ).unwrap();
+
+    let close = |name: &str, expected: Decimal| match globals.get(name) {
+        Some(Object::Decimal(x)) => (x - expected).abs() < dec!(0.0001),
+        Some(Object::Int(x)) => Decimal::from(*x) == expected,
+        _ => false,
+    };
+    assert!(close("length", dec!(0.99396)));
+    assert!(close("speed", dec!(4)));
+    assert!(close("rate", dec!(0.34657)));
+    if let Some(Object::Uncertain { value, uncertainty, .. }) = globals.get("force") {
+        assert_eq!((*value, *uncertainty), (dec!(2), dec!(0.2)));
+    } else {
+        panic!("expected the uncertainty to be propagated through the finder")
+    }
+
+    // Every other variable in the equation has to be given
+    let source = String::from("finder motion {
+    equation s = u * t + a * t^2 / 2
+    equation v = u + a * t
+}
+let speed = find motion(v: 10, u?)");
+    let error = crate::run::run(
+        source, HashMap::new(), false, &mut Memory::new(),
+    ).unwrap_err().remove(0);
+    assert_eq!(error.message, "Can't find u without a, t");
 }
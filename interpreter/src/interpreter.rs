@@ -1,59 +1,145 @@
 use std::collections::HashMap;
 
 // Common types used throughout the interpreter
+use hawk_common::environment::Environment;
+use hawk_common::error::{Frame, HawkError};
 use hawk_common::object::Object;
 use hawk_common::tree::Statement;
+use hawk_common::uncertainty::Propagation;
+
+use crate::monte_carlo::MonteCarlo;
+use crate::native::NativeFunction;
+use crate::propagate::CachedDerivative;
+
+
+/// How a statement finished running. Anything other than `Normal` skips the rest of the enclosing blocks until it
+/// reaches the loop or function call that handles it
+#[derive(Debug, Clone)]
+pub enum ControlFlow {
+    Normal,
+    Break,
+    Continue,
+    Return(Object),
+}
 
 /// Runs parsed code from the list of statements returned by the parser
-pub struct Interpreter {
+pub struct Interpreter<'a> {
     /// Parsed code to execute
     pub statements: Vec<Statement>,
     /// Stores variables in the global scope
     pub globals: HashMap<String, Object>,
     /// A stack variables in local scopes are stored
     pub scopes: Vec<HashMap<String, Object>>,
-    /// Contains number of nested loops in order to handle `break`
-    pub loops: usize,
     /// Current line number (updated by `Line` statement)
     pub line: usize,
+    /// File being run, or `None` for the REPL
+    pub file: Option<String>,
+    /// Function calls that are running, outermost first
+    pub call_stack: Vec<Frame>,
+    /// How uncertainties are combined in arithmetic
+    pub propagation: Propagation,
+    /// Settings and random number generator used by `montecarlo`
+    pub monte_carlo: MonteCarlo,
+    /// Number of `expect` statements that have failed, so that test runners can check it
+    pub failed_expects: usize,
     pub in_repl: bool,
-    pub filein_fn: fn(String) -> Result<String, String>,
-    pub fileout_fn: fn(String, String) -> Result<(), ()>,
-    pub warn_fn: fn(String, usize) -> (),
-    pub err_fn: fn(String, usize) -> (),
-    pub output_fn: fn(String) -> (),
+    /// Reads and writes files, and shows output, warnings and errors
+    pub env: &'a mut dyn Environment,
+    /// Rust functions registered by the program running Hawk, by name
+    pub natives: HashMap<String, NativeFunction>,
+    /// Symbolic derivatives used by `propagate` and `process` blocks, by function name, arity and parameter
+    pub(crate) derivatives: HashMap<(String, usize, usize), CachedDerivative>,
 }
 
-impl Interpreter {
+impl<'a> Interpreter<'a> {
     /// Create an `Interpreter` and run code. `global_state` is used to store the state of the REPL.
     pub fn interpret(
         statements: Vec<Statement>,
         global_state: HashMap<String, Object>,
+        file: Option<String>,
         in_repl: bool,
-        filein_fn: fn(String) -> Result<String, String>,
-        fileout_fn: fn(String, String) -> Result<(), ()>,
-        warn_fn: fn(String, usize) -> (),
-        err_fn: fn(String, usize) -> (),
-        output_fn: fn(String) -> (),
-    ) -> Result<HashMap<String, Object>, (String, usize)> {
-        let mut interpreter = Interpreter {
-            statements,
+        env: &'a mut dyn Environment,
+    ) -> Result<HashMap<String, Object>, HawkError> {
+        let mut interpreter = Interpreter::new(global_state, file, in_repl, env);
+        interpreter.run(statements)?;
+        Ok(interpreter.globals)
+    }
+
+    /// Create an `Interpreter` without running any code, e.g. to register native functions first
+    pub fn new(
+        global_state: HashMap<String, Object>,
+        file: Option<String>,
+        in_repl: bool,
+        env: &'a mut dyn Environment,
+    ) -> Self {
+        Interpreter {
+            statements: Vec::new(),
             globals: global_state,
-            loops: 0,
             scopes: Vec::new(),
             line: 1,
+            file,
+            call_stack: Vec::new(),
+            propagation: Propagation::default(),
+            monte_carlo: MonteCarlo::default(),
+            failed_expects: 0,
             in_repl,
-            filein_fn,
-            fileout_fn,
-            warn_fn,
-            err_fn,
-            output_fn,
-        };
-
-        for index in 0..interpreter.statements.len() {
-            interpreter.run_statement(interpreter.statements[index].clone())?
+            env,
+            natives: HashMap::new(),
+            derivatives: HashMap::new(),
         }
+    }
 
-        Ok(interpreter.globals)
+    /// Runs code, keeping the variables it defines in `globals`
+    pub fn run(&mut self, statements: Vec<Statement>) -> Result<(), HawkError> {
+        self.statements = statements;
+
+        for index in 0..self.statements.len() {
+            match self.run_statement(self.statements[index].clone())? {
+                ControlFlow::Normal => (),
+                ControlFlow::Break | ControlFlow::Continue => return Err(HawkError::runtime(
+                    String::from("Can't use break or continue outside of a loop"), self.line
+                )),
+                ControlFlow::Return(_) => return Err(HawkError::runtime(
+                    String::from("Can't use return outside of a function"), self.line
+                )),
+            }
+        }
+        Ok(())
+    }
+
+    /// Lets Hawk code call a Rust function. It replaces a standard library function with the same name, but not a
+    /// function defined in Hawk code
+    pub fn register(&mut self, function: NativeFunction) {
+        self.natives.insert(function.name.clone(), function);
+    }
+
+    /// Runs `call`, which runs the code of `function`, with it on the call stack. Errors in it get the call stack as
+    /// their traceback, and the line is put back to where it was called from afterwards
+    pub fn in_frame<T>(
+        &mut self, function: String, call: impl FnOnce(&mut Self) -> Result<T, HawkError>,
+    ) -> Result<T, HawkError> {
+        let line = self.line;
+        self.call_stack.push(Frame { function, line, file: self.file.clone() });
+
+        let result = call(self).map_err(|error| error.with_trace(&self.call_stack));
+
+        self.call_stack.pop();
+        self.line = line;
+        result
+    }
+
+    /// Changes how uncertainties are combined from now on
+    pub fn set_propagation(&mut self, propagation: Propagation) {
+        self.propagation = propagation;
+    }
+
+    /// Records that an `expect` statement failed
+    pub fn fail_expect(&mut self) {
+        self.failed_expects += 1;
+    }
+
+    /// Changes the settings used by `montecarlo`, reseeding the random number generator
+    pub fn set_monte_carlo(&mut self, monte_carlo: MonteCarlo) {
+        self.monte_carlo = monte_carlo;
     }
 }
@@ -1,15 +1,20 @@
+mod catch;
+mod class;
 mod expression;
 pub mod interpreter;
 mod operations;
+mod propagate;
+pub mod monte_carlo;
+pub mod native;
+mod quantity;
 mod statement;
-mod placeholder_cas;
 
 mod standard_lib;
 
 mod csv;
 pub mod run;
 
-pub use interpreter::Interpreter;
+pub use interpreter::{ControlFlow, Interpreter};
 
 #[cfg(test)]
 mod test;
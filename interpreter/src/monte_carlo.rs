@@ -0,0 +1,258 @@
+use std::collections::HashMap;
+
+use crate::Interpreter;
+use hawk_common::error::HawkError;
+use hawk_common::object::Object;
+use hawk_common::tree::Expression;
+use hawk_common::units::Unit;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rust_decimal::{prelude::FromPrimitive, Decimal, MathematicalOps};
+
+/// The distribution that samples of an uncertain value are drawn from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Distribution {
+    /// The uncertainty is the standard deviation
+    Normal,
+    /// The uncertainty is the half width, so samples lie between `value - uncertainty` and `value + uncertainty`
+    Uniform,
+}
+
+impl Distribution {
+    /// Gets a distribution from the name used in Hawk code
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "normal" | "gaussian" => Some(Self::Normal),
+            "uniform" => Some(Self::Uniform),
+            _ => None
+        }
+    }
+
+    /// The name used in Hawk code
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Normal => "normal",
+            Self::Uniform => "uniform",
+        }
+    }
+}
+
+/// Settings and random number generator for Monte Carlo uncertainty propagation
+#[derive(Debug, Clone)]
+pub struct MonteCarlo {
+    /// Number of times the function is evaluated
+    pub samples: usize,
+    /// Seed of the random number generator, so that results are reproducible
+    pub seed: u64,
+    pub distribution: Distribution,
+    rng: StdRng,
+}
+
+impl Default for MonteCarlo {
+    fn default() -> Self {
+        Self::new(10000, 0, Distribution::Normal)
+    }
+}
+
+impl MonteCarlo {
+    pub fn new(samples: usize, seed: u64, distribution: Distribution) -> Self {
+        Self {
+            samples,
+            seed,
+            distribution,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Draws a random deviation: a number of standard deviations for a normal distribution (using the Box-Muller
+    /// transform), or a fraction of the half width for a uniform distribution
+    fn deviation(&mut self) -> Decimal {
+        let deviation = match self.distribution {
+            Distribution::Normal => {
+                let radius = (-2.0 * (1.0 - self.rng.gen::<f64>()).ln()).sqrt();
+                radius * (2.0 * std::f64::consts::PI * self.rng.gen::<f64>()).cos()
+            },
+            Distribution::Uniform => self.rng.gen_range(-1.0..=1.0),
+        };
+        Decimal::from_f64(deviation).unwrap_or_default()
+    }
+
+    /// Replaces every `Uncertain` argument, or quantity with an uncertain value, with a random sample. Each error
+    /// source is only sampled once, so arguments that share a source (e.g. `x` and `2 * x`) stay correlated
+    pub fn sample(&mut self, args: &[Object]) -> Vec<Object> {
+        let mut deviations: HashMap<usize, Decimal> = HashMap::new();
+        args.iter().map(|arg| self.sample_value(arg, &mut deviations)).collect()
+    }
+
+    /// Samples one value, using the deviations already drawn for its error sources
+    fn sample_value(&mut self, arg: &Object, deviations: &mut HashMap<usize, Decimal>) -> Object {
+        match arg {
+            Object::Uncertain { value, sources, .. } => {
+                let mut sample = *value;
+                for (id, contribution) in sources.0.iter() {
+                    let deviation = match deviations.get(id) {
+                        Some(deviation) => *deviation,
+                        None => {
+                            let deviation = self.deviation();
+                            deviations.insert(*id, deviation);
+                            deviation
+                        }
+                    };
+                    sample += contribution * deviation
+                }
+                Object::Decimal(sample)
+            },
+            Object::Quantity { value, unit } => Object::Quantity {
+                value: Box::new(self.sample_value(value, deviations)),
+                unit: unit.clone(),
+            },
+            arg => arg.clone(),
+        }
+    }
+}
+
+impl Interpreter<'_> {
+    /// Evaluates `montecarlo(f, args...)` and `montecarlo_samples(f, args...)`. The function is given by name, so that
+    /// both user defined and standard library functions can be used
+    pub fn eval_monte_carlo_call(&mut self, identifier: String, args: Vec<Box<Expression>>) -> Result<Object, HawkError> {
+        let mut args = args.into_iter();
+        let function = self.eval_function_name(&identifier, args.next())?;
+
+        let mut evaled_args: Vec<Object> = Vec::new();
+        for arg in args {
+            evaled_args.push(self.eval_expression(arg)?)
+        }
+
+        let (results, unit) = self.monte_carlo(function, evaled_args)?;
+        // A function giving a quantity gives results with its unit
+        let with_unit = |value: Object| match &unit {
+            Some(unit) => Object::Quantity { value: Box::new(value), unit: unit.clone() },
+            None => value,
+        };
+
+        if identifier == "montecarlo_samples" {
+            Ok(Object::Array(results.into_iter().map(|x| with_unit(Object::Decimal(x))).collect()))
+        } else {
+            let n = Decimal::from(results.len());
+            let mean = results.iter().sum::<Decimal>() / n;
+            let variance = results.iter().map(|x| (x - mean) * (x - mean)).sum::<Decimal>() / (n - Decimal::ONE).max(Decimal::ONE);
+
+            Ok(with_unit(Object::uncertain(mean, variance.sqrt().unwrap_or_default())))
+        }
+    }
+
+    /// Gets the name of the function passed as the first argument of e.g. `montecarlo`, either as an identifier or a
+    /// string
+    pub(crate) fn eval_function_name(&mut self, identifier: &str, arg: Option<Box<Expression>>) -> Result<String, HawkError> {
+        match arg.map(|arg| *arg) {
+            Some(Expression::Literal(Object::Identifier(function))) => Ok(function),
+            Some(arg) => match self.eval_expression(Box::new(arg))? {
+                Object::String(function) => Ok(function),
+                x => Err(HawkError::runtime(format!("Expected function as first argument of {identifier}, found {x}"), self.line)),
+            },
+            None => Err(HawkError::runtime(format!("Expected function as first argument of {identifier}"), self.line)),
+        }
+    }
+
+    /// Calls a function once for every sample of its uncertain arguments, returning the results, and their unit if
+    /// the function gives a quantity
+    fn monte_carlo(&mut self, identifier: String, args: Vec<Object>) -> Result<(Vec<Decimal>, Option<Unit>), HawkError> {
+        let mut results: Vec<Decimal> = Vec::new();
+        let mut unit: Option<Unit> = None;
+
+        for index in 0..self.monte_carlo.samples.max(1) {
+            let sample = self.monte_carlo.sample(&args);
+            let (result, result_unit) = match self.call_function(identifier.clone(), sample)? {
+                Object::Quantity { value, unit } => (*value, Some(unit)),
+                result => (result, None),
+            };
+            if index > 0 && result_unit != unit {
+                return Err(HawkError::runtime(format!("{identifier} gave results with different units"), self.line))
+            }
+            unit = result_unit;
+
+            match result {
+                Object::Decimal(x) | Object::Uncertain { value: x, .. } => results.push(x),
+                Object::Int(x) => results.push(Decimal::from(x as i64)),
+                x => return Err(HawkError::runtime(format!("Expected Decimal or Int, got {x}"), self.line)),
+            }
+        }
+
+        Ok((results, unit))
+    }
+}
+
+#[test]
+fn correlated_samples() {
+    use rust_decimal_macros::dec;
+
+    let x = Object::uncertain(dec!(2.0), dec!(0.1));
+    let y = Object::uncertain(dec!(5.0), dec!(0.2));
+
+    let mut monte_carlo = MonteCarlo::new(1000, 42, Distribution::Uniform);
+    let mut repeated = MonteCarlo::new(1000, 42, Distribution::Uniform);
+
+    for _ in 0..monte_carlo.samples {
+        let sample = monte_carlo.sample(&[x.clone(), y.clone(), x.clone()]);
+        assert_eq!(sample, repeated.sample(&[x.clone(), y.clone(), x.clone()]));
+        assert_eq!(sample[0], sample[2]);
+
+        if let [Object::Decimal(x), Object::Decimal(y), _] = sample.as_slice() {
+            assert!(*x >= dec!(1.9) && *x <= dec!(2.1));
+            assert!(*y >= dec!(4.8) && *y <= dec!(5.2));
+        } else {
+            panic!("Expected samples to be Decimal")
+        }
+    }
+}
+
+#[test]
+fn settings() {
+    use hawk_common::environment::Memory;
+
+    let mut env = Memory::new();
+    let builder = crate::run::Builder::new().monte_carlo(MonteCarlo::new(50, 7, Distribution::Uniform));
+    let mut interpreter = builder.interpreter(HashMap::new(), &mut env);
+
+    interpreter.run_source("let samples = montecarlo_samples(sin, 1 ± 0.1)").unwrap();
+    assert!(matches!(interpreter.globals.get("samples"), Some(Object::Array(samples)) if samples.len() == 50));
+
+    // The settings are kept between runs, but aren't a variable
+    interpreter.run_source("montecarlo_settings(20)").unwrap();
+    assert_eq!((interpreter.monte_carlo.samples, interpreter.monte_carlo.seed), (20, 7));
+    assert_eq!(interpreter.globals.len(), 1);
+}
+
+#[test]
+fn quantities() {
+    use hawk_common::environment::Memory;
+    use rust_decimal_macros::dec;
+
+    let mut env = Memory::new();
+    let builder = crate::run::Builder::new().monte_carlo(MonteCarlo::new(200, 3, Distribution::Uniform));
+    let mut interpreter = builder.interpreter(HashMap::new(), &mut env);
+
+    // Quantities are sampled, and the result keeps the unit the function gives
+    interpreter.run_source("let area = (w) => w * w
+let side = 2 ± 0.1m
+let sampled = montecarlo(area, side)
+let samples = montecarlo_samples(area, side)").unwrap();
+
+    match interpreter.globals.get("sampled") {
+        Some(Object::Quantity { value, unit }) => match &**value {
+            Object::Uncertain { value, uncertainty, .. } => {
+                assert!(*value > dec!(3.9) && *value < dec!(4.1), "found {value}");
+                assert!(*uncertainty > dec!(0) && *uncertainty < dec!(0.4), "found {uncertainty}");
+                assert_eq!(unit.to_string(), "m^2");
+            },
+            x => panic!("Expected an uncertain value, found {x:?}"),
+        },
+        x => panic!("Expected a quantity, found {x:?}"),
+    }
+    match interpreter.globals.get("samples") {
+        Some(Object::Array(samples)) => assert!(samples.iter().all(|sample| matches!(
+            sample, Object::Quantity { value, .. } if matches!(**value, Object::Decimal(x) if x >= dec!(3.61) && x <= dec!(4.41))
+        ))),
+        x => panic!("Expected an array of samples, found {x:?}"),
+    }
+}
@@ -0,0 +1,184 @@
+use std::rc::Rc;
+
+use hawk_common::convert::{ConversionError, Measurement};
+use hawk_common::error::HawkError;
+use hawk_common::object::Object;
+
+use rust_decimal::prelude::*;
+
+/// A Rust function that Hawk code can call, registered by the program running Hawk (e.g. to read from an
+/// instrument). Like standard library functions, it is called once per row for columns, and uncertainties are
+/// propagated through it, so it only needs to handle single values
+#[derive(Clone)]
+pub struct NativeFunction {
+    pub name: String,
+    /// Number of arguments it takes, or `None` for any number
+    pub arity: Option<usize>,
+    function: Rc<dyn Fn(Vec<Object>) -> Result<Object, String>>,
+}
+
+impl NativeFunction {
+    /// Makes a function that takes its arguments as `Object`s. Errors are given as a message, and are shown on the
+    /// line the function was called from
+    pub fn new(name: &str, arity: Option<usize>, function: impl Fn(Vec<Object>) -> Result<Object, String> + 'static) -> Self {
+        NativeFunction { name: String::from(name), arity, function: Rc::new(function) }
+    }
+
+    /// Makes a function from a closure with typed arguments, e.g. `|volts: Decimal, gain: i64| volts * Decimal::from(gain)`.
+    /// Arguments are converted from `Object`s with `TryFrom`, and the result with `Into`, see `hawk_common::convert`
+    pub fn typed<Args>(name: &str, function: impl IntoNative<Args>) -> Self {
+        function.into_native(name)
+    }
+
+    pub fn call(&self, args: Vec<Object>, line: usize) -> Result<Object, HawkError> {
+        if let Some(arity) = self.arity {
+            if args.len() != arity {
+                return Err(HawkError::runtime(format!("{} expects {arity} arguments, found {}", self.name, args.len()), line))
+            }
+        }
+        (self.function)(args).map_err(|message| HawkError::runtime(message, line))
+    }
+}
+
+impl std::fmt::Debug for NativeFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "NativeFunction({}, {:?})", self.name, self.arity)
+    }
+}
+
+/// What a typed native function returns: either a value that converts to an `Object`, or a `Result` with an error
+/// message
+pub trait NativeResult {
+    fn into_result(self) -> Result<Object, String>;
+}
+
+impl<T: Into<Object>> NativeResult for Result<T, String> {
+    fn into_result(self) -> Result<Object, String> {
+        self.map(Into::into)
+    }
+}
+
+impl<T: Into<Object>> NativeResult for Vec<T> {
+    fn into_result(self) -> Result<Object, String> {
+        Ok(self.into())
+    }
+}
+
+macro_rules! native_result {
+    ($($type:ty),*) => {
+        $(
+            impl NativeResult for $type {
+                fn into_result(self) -> Result<Object, String> {
+                    Ok(self.into())
+                }
+            }
+        )*
+    };
+}
+
+native_result!(Object, Decimal, f64, bool, String, &str, (), Measurement, i32, i64, i128, u32, u64, usize);
+
+/// A closure with typed arguments that can be made into a `NativeFunction`. `Args` is a tuple of the types of the
+/// arguments, which lets closures taking different numbers of arguments have different implementations
+pub trait IntoNative<Args> {
+    fn into_native(self, name: &str) -> NativeFunction;
+}
+
+/// Converts an argument of a native function, with an error saying which argument is wrong
+fn convert<T>(name: &str, index: usize, arg: &Object) -> Result<T, String>
+where
+    T: TryFrom<Object>,
+    ConversionError: From<T::Error>,
+{
+    T::try_from(arg.clone()).map_err(|error| {
+        let error = ConversionError::from(error);
+        format!(
+            "Argument {} of {name} should be {}, found {}",
+            index + 1,
+            error.expected,
+            error.found.user_print(0).unwrap_or_default(),
+        )
+    })
+}
+
+macro_rules! into_native {
+    ($arity:expr; $($arg:ident: $type:ident),*) => {
+        impl<F, R, $($type),*> IntoNative<($($type,)*)> for F
+        where
+            F: Fn($($type),*) -> R + 'static,
+            R: NativeResult,
+            $($type: TryFrom<Object>, ConversionError: From<$type::Error>,)*
+        {
+            #[allow(unused_variables, unused_mut)]
+            fn into_native(self, name: &str) -> NativeFunction {
+                let function_name = String::from(name);
+                NativeFunction::new(name, Some($arity), move |args: Vec<Object>| {
+                    // `call` has checked that there are enough arguments
+                    let mut args = args.iter().enumerate();
+                    $(
+                        let (index, arg) = args.next().unwrap();
+                        let $arg: $type = convert(&function_name, index, arg)?;
+                    )*
+                    (self)($($arg),*).into_result()
+                })
+            }
+        }
+    };
+}
+
+into_native!(0;);
+into_native!(1; a: A);
+into_native!(2; a: A, b: B);
+into_native!(3; a: A, b: B, c: C);
+into_native!(4; a: A, b: B, c: C, d: D);
+
+#[test]
+fn native_functions() {
+    use std::cell::Cell;
+    use std::collections::HashMap;
+
+    use hawk_common::environment::Memory;
+    use rust_decimal_macros::dec;
+
+    let readings = Rc::new(Cell::new(0));
+    let counter = readings.clone();
+
+    let builder = crate::run::Builder::new()
+        .function(NativeFunction::typed("gain", |volts: Decimal, factor: i64| volts * Decimal::from(factor)))
+        .function(NativeFunction::typed("read_voltage", move || {
+            counter.set(counter.get() + 1);
+            dec!(1.5)
+        }))
+        .function(NativeFunction::typed("lookup", |name: String| match name.as_str() {
+            "g" => Ok(dec!(9.81)),
+            _ => Err(format!("No entry for {name}")),
+        }))
+        .function(NativeFunction::new("count", None, |args| Ok(Object::Int(args.len() as i128))));
+
+    let source = String::from("let v = read_voltage()
+let doubled = gain(v, 2)
+let table = read(\"data.csv\")
+let column = gain(table.column(\"v\"), 3)
+let uncertain = gain(2 ± 0.1, 2)
+let g = lookup(\"g\")
+let counted = count(1, 2, 3)");
+    let globals = builder.run(source, HashMap::new(), &mut Memory::new().with_file("data.csv", "v\n1\n2")).unwrap();
+
+    assert_eq!(readings.get(), 1);
+    assert_eq!(globals.get("doubled"), Some(&Object::Decimal(dec!(3))));
+    assert_eq!(globals.get("column"), Some(&Object::Column(vec![Object::Decimal(dec!(3)), Object::Decimal(dec!(6))])));
+    assert_eq!(globals.get("g"), Some(&Object::Decimal(dec!(9.81))));
+    assert_eq!(globals.get("counted"), Some(&Object::Int(3)));
+    if let Some(Object::Uncertain { value, uncertainty, .. }) = globals.get("uncertain") {
+        assert_eq!((*value, *uncertainty), (dec!(4), dec!(0.2)));
+    } else {
+        panic!("expected an uncertain result")
+    }
+
+    let errors = builder.run(String::from("lookup(\"c\")"), HashMap::new(), &mut Memory::new()).unwrap_err();
+    assert_eq!(errors[0].message, "No entry for c");
+    let errors = builder.run(String::from("gain(\"a\", 2)"), HashMap::new(), &mut Memory::new()).unwrap_err();
+    assert_eq!(errors[0].message, "Argument 1 of gain should be a number, found a");
+    let errors = builder.run(String::from("gain(1)"), HashMap::new(), &mut Memory::new()).unwrap_err();
+    assert_eq!(errors[0].message, "gain expects 2 arguments, found 1");
+}
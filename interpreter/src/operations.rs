@@ -1,62 +1,40 @@
 use crate::Interpreter;
+use crate::standard_lib::symbolic;
 
 // Common types used throughout the interpreter
+use hawk_common::error::HawkError;
 use hawk_common::object::Object;
+use hawk_common::token::TokenType;
+use hawk_common::uncertainty::{ErrorSources, Propagation};
 
-use rust_decimal::{Decimal, MathematicalOps};
+use rust_decimal::{Decimal, MathematicalOps, prelude::ToPrimitive};
 
 #[allow(unused_imports)]
 use rust_decimal_macros::dec;
 
-impl Interpreter {
+impl Interpreter<'_> {
     /// Adds two numbers or strings
-    pub fn add(operand1: Object, operand2: Object, line: usize) -> Result<Object, (String, usize)> {
+    pub fn add(operand1: Object, operand2: Object, propagation: Propagation, line: usize) -> Result<Object, HawkError> {
+        if let Some(result) = Self::quantity_operation(&operand1, &operand2, TokenType::Plus, propagation, line)? {
+            return Ok(result)
+        }
+
+        if let Some(result) = Self::propagate_uncertainty(&operand1, &operand2, propagation, line, |x, y| Some((x.checked_add(y)?, dec!(1), dec!(1))))? {
+            return Ok(result)
+        }
+
         match operand1 {
             Object::Int(x) => match operand2 {
-                Object::Int(y) => Ok(Object::Int(x + y)),
-                Object::Decimal(y) => Ok(Object::Decimal(Decimal::from(x as i64) + y)),
-                Object::Uncertain {
-                    value: y,
-                    uncertainty: u,
-                } => Ok(Object::Uncertain {
-                    value: Decimal::from(x as i64) + y,
-                    uncertainty: u,
-                }),
-                _ => Err((format!("Can't add Int to {}", operand2.user_print(line)?), line)),
+                Object::Int(y) => x.checked_add(y).map(Object::Int).ok_or_else(|| Self::too_large(line)),
+                Object::Decimal(y) => Decimal::from(x as i64).checked_add(y).map(Object::Decimal).ok_or_else(|| Self::too_large(line)),
+                _ => Err(HawkError::runtime(format!("Can't add Int to {}", operand2.user_print(line)?), line)),
             },
             Object::Decimal(x) => match operand2 {
-                Object::Int(y) => Ok(Object::Decimal(x + Decimal::from(y as i64))),
-                Object::Decimal(y) => Ok(Object::Decimal(x + y)),
-                Object::Uncertain {
-                    value: y,
-                    uncertainty: u,
-                } => Ok(Object::Uncertain {
-                    value: x + y,
-                    uncertainty: u,
-                }),
-                _ => Err((format!("Can't add Decimal to {}", operand2.user_print(line)?), line)),
-            },
-            Object::Uncertain {
-                value: x,
-                uncertainty: u1,
-            } => match operand2 {
-                Object::Int(y) => Ok(Object::Uncertain {
-                    value: x + (Decimal::from(y as i64)),
-                    uncertainty: u1,
-                }),
-                Object::Decimal(y) => Ok(Object::Uncertain {
-                    value: x + y,
-                    uncertainty: u1,
-                }),
-                Object::Uncertain {
-                    value: y,
-                    uncertainty: u2,
-                } => Ok(Object::Uncertain {
-                    value: x + y,
-                    uncertainty: u1 + u2,
-                }),
-                _ => Err((format!("Can't add Uncertain to {}", operand2.user_print(line)?), line)),
+                Object::Int(y) => x.checked_add(Decimal::from(y as i64)).map(Object::Decimal).ok_or_else(|| Self::too_large(line)),
+                Object::Decimal(y) => x.checked_add(y).map(Object::Decimal).ok_or_else(|| Self::too_large(line)),
+                _ => Err(HawkError::runtime(format!("Can't add Decimal to {}", operand2.user_print(line)?), line)),
             },
+            Object::Uncertain { .. } => Err(HawkError::runtime(format!("Can't add Uncertain to {}", operand2.user_print(line)?), line)),
             Object::Column(operand1_data) => {
                 let mut results: Vec<Object> = Vec::new();
                 if let Object::Column(operand2_data) = operand2 {
@@ -64,72 +42,47 @@ impl Interpreter {
                         results.push(Interpreter::add(
                             operand1.clone(),
                             operand2_data[index].clone(),
+                            propagation,
                             line,
                         )?)
                     }
                 } else {
                     for operand1 in operand1_data {
-                        results.push(Interpreter::add(operand1, operand2.clone(), line)?)
+                        results.push(Interpreter::add(operand1, operand2.clone(), propagation, line)?)
                     }
                 }
                 Ok(Object::Column(results))
             }
             Object::String(x) => match operand2 {
                 Object::String(y) => Ok(Object::String(format!("{x}{y}"))),
-                _ => Err((format!("Can't add String to {}", operand2.user_print(line)?), line)),
+                _ => Err(HawkError::runtime(format!("Can't add String to {}", operand2.user_print(line)?), line)),
             },
-            _ => Err((format!("Can't add {}", operand1.user_print(line)?), line)),
+            _ => Err(HawkError::runtime(format!("Can't add {}", operand1.user_print(line)?), line)),
         }
     }
 
     /// Subtracts two numbers
-    pub fn subtract(operand1: Object, operand2: Object, line: usize) -> Result<Object, (String, usize)> {
+    pub fn subtract(operand1: Object, operand2: Object, propagation: Propagation, line: usize) -> Result<Object, HawkError> {
+        if let Some(result) = Self::quantity_operation(&operand1, &operand2, TokenType::Minus, propagation, line)? {
+            return Ok(result)
+        }
+
+        if let Some(result) = Self::propagate_uncertainty(&operand1, &operand2, propagation, line, |x, y| Some((x.checked_sub(y)?, dec!(1), dec!(-1))))? {
+            return Ok(result)
+        }
+
         match operand1 {
             Object::Int(x) => match operand2 {
-                Object::Int(y) => Ok(Object::Int(x - y)),
-                Object::Decimal(y) => Ok(Object::Decimal(Decimal::from(x as i64) - y)),
-                Object::Uncertain {
-                    value: y,
-                    uncertainty: u,
-                } => Ok(Object::Uncertain {
-                    value: (Decimal::from(x as i64)) - y,
-                    uncertainty: u,
-                }),
-                _ => Err((format!("Can't subtract {} from Int", operand2.user_print(line)?), line)),
+                Object::Int(y) => x.checked_sub(y).map(Object::Int).ok_or_else(|| Self::too_large(line)),
+                Object::Decimal(y) => Decimal::from(x as i64).checked_sub(y).map(Object::Decimal).ok_or_else(|| Self::too_large(line)),
+                _ => Err(HawkError::runtime(format!("Can't subtract {} from Int", operand2.user_print(line)?), line)),
             },
             Object::Decimal(x) => match operand2 {
-                Object::Int(y) => Ok(Object::Decimal(x - (Decimal::from(y as i64)))),
-                Object::Decimal(y) => Ok(Object::Decimal(x - y)),
-                Object::Uncertain {
-                    value: y,
-                    uncertainty: u,
-                } => Ok(Object::Uncertain {
-                    value: x - y,
-                    uncertainty: u,
-                }),
-                _ => Err((format!("Can't subtract {} from Decimal", operand2.user_print(line)?), line)),
-            },
-            Object::Uncertain {
-                value: x,
-                uncertainty: u1,
-            } => match operand2 {
-                Object::Int(y) => Ok(Object::Uncertain {
-                    value: x - (Decimal::from(y as i64)),
-                    uncertainty: u1,
-                }),
-                Object::Decimal(y) => Ok(Object::Uncertain {
-                    value: x - y,
-                    uncertainty: u1,
-                }),
-                Object::Uncertain {
-                    value: y,
-                    uncertainty: u2,
-                } => Ok(Object::Uncertain {
-                    value: x - y,
-                    uncertainty: u1 + u2,
-                }),
-                _ => Err((format!("Can't subtract {} from Uncertain", operand2.user_print(line)?), line)),
+                Object::Int(y) => x.checked_sub(Decimal::from(y as i64)).map(Object::Decimal).ok_or_else(|| Self::too_large(line)),
+                Object::Decimal(y) => x.checked_sub(y).map(Object::Decimal).ok_or_else(|| Self::too_large(line)),
+                _ => Err(HawkError::runtime(format!("Can't subtract {} from Decimal", operand2.user_print(line)?), line)),
             },
+            Object::Uncertain { .. } => Err(HawkError::runtime(format!("Can't subtract {} from Uncertain", operand2.user_print(line)?), line)),
             Object::Column(operand1_data) => {
                 let mut results: Vec<Object> = Vec::new();
                 if let Object::Column(operand2_data) = operand2 {
@@ -137,62 +90,43 @@ impl Interpreter {
                         results.push(Interpreter::subtract(
                             minuend.clone(),
                             operand2_data[index].clone(),
+                            propagation,
                             line,
                         )?)
                     }
                 } else {
                     for operand1 in operand1_data {
-                        results.push(Interpreter::subtract(operand1, operand2.clone(), line)?)
+                        results.push(Interpreter::subtract(operand1, operand2.clone(), propagation, line)?)
                     }
                 }
                 Ok(Object::Column(results))
             }
-            _ => Err((format!("Can't subtract {}", operand1.user_print(line)?), line)),
+            _ => Err(HawkError::runtime(format!("Can't subtract {}", operand1.user_print(line)?), line)),
         }
     }
 
     /// Multiplies two numbers
-    pub fn multiply(operand1: Object, operand2: Object, line: usize) -> Result<Object, (String, usize)> {
+    pub fn multiply(operand1: Object, operand2: Object, propagation: Propagation, line: usize) -> Result<Object, HawkError> {
+        if let Some(result) = Self::quantity_operation(&operand1, &operand2, TokenType::Asterisk, propagation, line)? {
+            return Ok(result)
+        }
+
+        if let Some(result) = Self::propagate_uncertainty(&operand1, &operand2, propagation, line, |x, y| Some((x.checked_mul(y)?, y, x)))? {
+            return Ok(result)
+        }
+
         match operand1 {
             Object::Int(x) => match operand2 {
-                Object::Int(y) => Ok(Object::Int(x * y)),
-                Object::Decimal(y) => Ok(Object::Decimal(Decimal::from(x as i64) * y)),
-                Object::Uncertain { value, uncertainty } => Ok(Object::Uncertain {
-                    value: value * (Decimal::from(x as i64)),
-                    uncertainty: uncertainty * (Decimal::from(x as i64)),
-                }),
-                _ => Err((format!("Can't multiply Int by {}", operand2.user_print(line)?), line)),
+                Object::Int(y) => x.checked_mul(y).map(Object::Int).ok_or_else(|| Self::too_large(line)),
+                Object::Decimal(y) => Decimal::from(x as i64).checked_mul(y).map(Object::Decimal).ok_or_else(|| Self::too_large(line)),
+                _ => Err(HawkError::runtime(format!("Can't multiply Int by {}", operand2.user_print(line)?), line)),
             },
             Object::Decimal(x) => match operand2 {
-                Object::Int(y) => Ok(Object::Decimal(x * (Decimal::from(y as i64)))),
-                Object::Decimal(y) => Ok(Object::Decimal(x * y)),
-                Object::Uncertain { value, uncertainty } => Ok(Object::Uncertain {
-                    value: value * x,
-                    uncertainty: uncertainty * x,
-                }),
-                _ => Err((format!("Can't multiply Decimal by {}", operand2.user_print(line)?), line)),
-            },
-            Object::Uncertain {
-                value: x,
-                uncertainty: u1,
-            } => match operand2 {
-                Object::Int(y) => Ok(Object::Uncertain {
-                    value: x * (Decimal::from(y as i64)),
-                    uncertainty: u1 * (Decimal::from(y as i64)),
-                }),
-                Object::Decimal(y) => Ok(Object::Uncertain {
-                    value: x * y,
-                    uncertainty: u1 * y,
-                }),
-                Object::Uncertain {
-                    value: y,
-                    uncertainty: u2,
-                } => Ok(Object::Uncertain {
-                    value: x * y,
-                    uncertainty: x * y * ((u1 / x) + (u2 / y)),
-                }),
-                _ => Err((format!("Can't multiply Uncertain by {}", operand2.user_print(line)?), line)),
+                Object::Int(y) => x.checked_mul(Decimal::from(y as i64)).map(Object::Decimal).ok_or_else(|| Self::too_large(line)),
+                Object::Decimal(y) => x.checked_mul(y).map(Object::Decimal).ok_or_else(|| Self::too_large(line)),
+                _ => Err(HawkError::runtime(format!("Can't multiply Decimal by {}", operand2.user_print(line)?), line)),
             },
+            Object::Uncertain { .. } => Err(HawkError::runtime(format!("Can't multiply Uncertain by {}", operand2.user_print(line)?), line)),
             Object::Column(operand1_data) => {
                 let mut products: Vec<Object> = Vec::new();
                 if let Object::Column(operand2_data) = operand2 {
@@ -200,68 +134,50 @@ impl Interpreter {
                         products.push(Interpreter::multiply(
                             operand1.clone(),
                             operand2_data[index].clone(),
+                            propagation,
                             line,
                         )?)
                     }
                 } else {
                     for operand1 in operand1_data {
-                        products.push(Interpreter::multiply(operand1, operand2.clone(), line)?)
+                        products.push(Interpreter::multiply(operand1, operand2.clone(), propagation, line)?)
                     }
                 }
                 Ok(Object::Column(products))
             }
-            _ => Err((format!("Can't multiply {}", operand1.user_print(line)?), line)),
+            _ => Err(HawkError::runtime(format!("Can't multiply {}", operand1.user_print(line)?), line)),
         }
     }
 
     /// Divides two numbers
-    pub fn divide(operand1: Object, operand2: Object, line: usize) -> Result<Object, (String, usize)> {
+    pub fn divide(operand1: Object, operand2: Object, propagation: Propagation, line: usize) -> Result<Object, HawkError> {
+        if let Some(result) = Self::quantity_operation(&operand1, &operand2, TokenType::Slash, propagation, line)? {
+            return Ok(result)
+        }
+
+        if matches!(Self::uncertain_parts(&operand2), Some((y, _)) if y.is_zero()) {
+            return Err(HawkError::runtime(String::from("Can't divide by 0"), line))
+        }
+        Self::check_bounds(&operand2, propagation, dec!(0), "Can't divide by", line)?;
+
+        if let Some(result) = Self::propagate_uncertainty(&operand1, &operand2, propagation, line, |x, y| {
+            Some((x.checked_div(y)?, dec!(1).checked_div(y)?, (-x).checked_div(y.checked_mul(y)?)?))
+        })? {
+            return Ok(result)
+        }
+
         match operand1 {
             Object::Int(x) => match operand2 {
-                Object::Int(y) => Ok(Object::Int(x / y)),
-                Object::Decimal(y) => Ok(Object::Decimal(Decimal::from(x as i64) / y)),
-                Object::Uncertain {
-                    value: y,
-                    uncertainty: u,
-                } => Ok(Object::Uncertain {
-                    value: (Decimal::from(x as i64)) / y,
-                    uncertainty: (Decimal::from(x as i64)) * u / (y * y),
-                }),
-                _ => Err((format!("Can't divide Int by {}", operand2.user_print(line)?), line)),
+                Object::Int(y) => x.checked_div(y).map(Object::Int).ok_or_else(|| Self::too_large(line)),
+                Object::Decimal(y) => Decimal::from(x as i64).checked_div(y).map(Object::Decimal).ok_or_else(|| Self::too_large(line)),
+                _ => Err(HawkError::runtime(format!("Can't divide Int by {}", operand2.user_print(line)?), line)),
             },
             Object::Decimal(x) => match operand2 {
-                Object::Int(y) => Ok(Object::Decimal(x / (Decimal::from(y as i64)))),
-                Object::Decimal(y) => Ok(Object::Decimal(x / y)),
-                Object::Uncertain {
-                    value: y,
-                    uncertainty: u,
-                } => Ok(Object::Uncertain {
-                    value: x / y,
-                    uncertainty: x * u / (y * y),
-                }),
-                _ => Err((format!("Can't divide Decimal by {}", operand2.user_print(line)?), line)),
-            },
-            Object::Uncertain {
-                value: x,
-                uncertainty: u1,
-            } => match operand2 {
-                Object::Int(y) => Ok(Object::Uncertain {
-                    value: x / (Decimal::from(y as i64)),
-                    uncertainty: u1 / (Decimal::from(y as i64)),
-                }),
-                Object::Decimal(y) => Ok(Object::Uncertain {
-                    value: x / y,
-                    uncertainty: u1 / y,
-                }),
-                Object::Uncertain {
-                    value: y,
-                    uncertainty: u2,
-                } => Ok(Object::Uncertain {
-                    value: x / y,
-                    uncertainty: (x / y) * ((u1 / x) + (u2 / y)),
-                }),
-                _ => Err((format!("Can't divide Uncertain by {}", operand2.user_print(line)?), line)),
+                Object::Int(y) => x.checked_div(Decimal::from(y as i64)).map(Object::Decimal).ok_or_else(|| Self::too_large(line)),
+                Object::Decimal(y) => x.checked_div(y).map(Object::Decimal).ok_or_else(|| Self::too_large(line)),
+                _ => Err(HawkError::runtime(format!("Can't divide Decimal by {}", operand2.user_print(line)?), line)),
             },
+            Object::Uncertain { .. } => Err(HawkError::runtime(format!("Can't divide Uncertain by {}", operand2.user_print(line)?), line)),
             Object::Column(operand1_data) => {
                 let mut quotients: Vec<Object> = Vec::new();
                 if let Object::Column(operand2_data) = operand2 {
@@ -269,46 +185,63 @@ impl Interpreter {
                         quotients.push(Interpreter::divide(
                             operand1.clone(),
                             operand2_data[index].clone(),
+                            propagation,
                             line,
                         )?)
                     }
                 } else {
                     for operand1 in operand1_data {
-                        quotients.push(Interpreter::divide(operand1, operand2.clone(), line)?)
+                        quotients.push(Interpreter::divide(operand1, operand2.clone(), propagation, line)?)
                     }
                 }
                 Ok(Object::Column(quotients))
             }
-            _ => Err((format!("Can't divide {}", operand1.user_print(line)?), line)),
+            _ => Err(HawkError::runtime(format!("Can't divide {}", operand1.user_print(line)?), line)),
         }
     }
 
     /// Raises a number to the power of another
-    pub fn exponent(operand1: Object, operand2: Object, line: usize) -> Result<Object, (String, usize)> {
+    pub fn exponent(operand1: Object, operand2: Object, propagation: Propagation, line: usize) -> Result<Object, HawkError> {
+        if let Some(result) = Self::quantity_operation(&operand1, &operand2, TokenType::Caret, propagation, line)? {
+            return Ok(result)
+        }
+
+        if let (Some((x, _)), Some((_, sources2))) = (Self::uncertain_parts(&operand1), Self::uncertain_parts(&operand2)) {
+            if !sources2.0.is_empty() && x <= dec!(0) {
+                return Err(HawkError::runtime(format!("Can't raise {} to an uncertain power", operand1.user_print(line)?), line))
+            }
+        }
+
+        if !matches!(Self::uncertain_parts(&operand2), Some((y, _)) if y.fract().is_zero()) {
+            Self::check_bounds(&operand1, propagation, dec!(0), "Can't raise to a non integer power:", line)?;
+        }
+
+        let power = |x: Decimal, y: Decimal| if y.fract().is_zero() {x.checked_powi(y.to_i64()?)} else {x.checked_powd(y)};
+
+        if let Some(result) = Self::propagate_uncertainty(&operand1, &operand2, propagation, line, |x, y| {
+            let value = power(x, y)?;
+            let d_base = y.checked_mul(power(x, y - dec!(1))?)?;
+            // Only needed (and only defined) when the exponent is uncertain, which requires a positive base
+            let d_exponent = if x > dec!(0) {value.checked_mul(x.ln())?} else {dec!(0)};
+            Some((value, d_base, d_exponent))
+        })? {
+            return Ok(result)
+        }
+
         match operand1 {
             Object::Int(x) => match operand2 {
-                Object::Int(y) => Ok(Object::Int(x.pow(y as u32))),
-                Object::Decimal(y) => Ok(Object::Decimal((Decimal::from(x as i64)).powd(y))),
-                _ => Err((format!("Can't raise Int to {}", operand2.user_print(line)?), line)),
+                Object::Int(y) if y >= 0 => u32::try_from(y).ok().and_then(|y| x.checked_pow(y)).map(Object::Int).ok_or_else(|| Self::too_large(line)),
+                // A negative power gives a fraction
+                Object::Int(y) => power(Decimal::from(x as i64), Decimal::from(y as i64)).map(Object::Decimal).ok_or_else(|| Self::too_large(line)),
+                Object::Decimal(y) => power(Decimal::from(x as i64), y).map(Object::Decimal).ok_or_else(|| Self::too_large(line)),
+                _ => Err(HawkError::runtime(format!("Can't raise Int to {}", operand2.user_print(line)?), line)),
             },
             Object::Decimal(x) => match operand2 {
-                Object::Int(y) => Ok(Object::Decimal(x.powi(y as i64))),
-                Object::Decimal(y) => Ok(Object::Decimal(x.powd(y))),
-                _ => Err((format!("Can't raise Decimal to {}", operand2.user_print(line)?), line)),
-            },
-            Object::Uncertain {
-                value: x,
-                uncertainty: u1,
-            } => match operand2 {
-                Object::Int(y) => Ok(Object::Uncertain {
-                    value: x.powd(Decimal::from(y as i64)),
-                    uncertainty: x.powd(Decimal::from(y as i64)) * (Decimal::from(y as i64)) * (u1 / x),
-                }),
-                Object::Decimal(y) => Ok(Object::Uncertain {
-                    value: x.powd(y),uncertainty: x.powd(y) * y * (u1 / x),
-                }),
-                _ => Err((format!("Can't raise Uncertain to {}", operand2.user_print(line)?), line)),
+                Object::Int(y) => power(x, Decimal::from(y as i64)).map(Object::Decimal).ok_or_else(|| Self::too_large(line)),
+                Object::Decimal(y) => power(x, y).map(Object::Decimal).ok_or_else(|| Self::too_large(line)),
+                _ => Err(HawkError::runtime(format!("Can't raise Decimal to {}", operand2.user_print(line)?), line)),
             },
+            Object::Uncertain { .. } => Err(HawkError::runtime(format!("Can't raise Uncertain to {}", operand2.user_print(line)?), line)),
             Object::Column(operand1_data) => {
                 let mut results: Vec<Object> = Vec::new();
                 if let Object::Column(operand2_data) = operand2 {
@@ -316,17 +249,81 @@ impl Interpreter {
                         results.push(Interpreter::exponent(
                             operand1.clone(),
                             operand2_data[index].clone(),
+                            propagation,
                             line,
                         )?)
                     }
                 } else {
                     for operand1 in operand1_data {
-                        results.push(Interpreter::exponent(operand1, operand2.clone(), line)?)
+                        results.push(Interpreter::exponent(operand1, operand2.clone(), propagation, line)?)
                     }
                 }
                 Ok(Object::Column(results))
             }
-            _ => Err((format!("Can't exponentiate {}", operand1.user_print(line)?), line)),
+            _ => Err(HawkError::runtime(format!("Can't exponentiate {}", operand1.user_print(line)?), line)),
+        }
+    }
+
+    /// If at least one operand is `Uncertain` and both are numbers, evaluates `operation` (which gives the value and
+    /// the partial derivatives with respect to each operand) and propagates each error source to first order, so that
+    /// contributions from a shared source add up (or cancel) before the uncertainty is calculated.
+    /// With min/max bounding, the operation is instead evaluated at the bounds of both operands, and the result becomes
+    /// a new independent error source
+    fn propagate_uncertainty(
+        operand1: &Object, operand2: &Object, propagation: Propagation, line: usize,
+        operation: impl Fn(Decimal, Decimal) -> Option<(Decimal, Decimal, Decimal)>,
+    ) -> Result<Option<Object>, HawkError> {
+        if let (Object::Uncertain { .. }, _) | (_, Object::Uncertain { .. }) = (operand1, operand2) {
+            let (x, sources1) = if let Some(parts) = Self::uncertain_parts(operand1) {parts} else {return Ok(None)};
+            let (y, sources2) = if let Some(parts) = Self::uncertain_parts(operand2) {parts} else {return Ok(None)};
+
+            let (value, derivative1, derivative2) = operation(x, y).ok_or_else(|| Self::too_large(line))?;
+
+            if let Propagation::MinMax = propagation {
+                let (u1, u2) = (sources1.worst_case(), sources2.worst_case());
+                let mut results: Vec<Decimal> = Vec::new();
+                for x in [x - u1, x + u1] {
+                    for y in [y - u2, y + u2] {
+                        results.push(operation(x, y).ok_or_else(|| Self::too_large(line))?.0)
+                    }
+                }
+
+                let max = results.iter().copied().fold(value, Decimal::max);
+                let min = results.iter().copied().fold(value, Decimal::min);
+                return Ok(Some(Object::uncertain(value, (max - min) / dec!(2))))
+            }
+
+            let sources = sources1.combine(derivative1, &sources2, derivative2);
+
+            Ok(Some(Object::Uncertain { value, uncertainty: propagation.uncertainty(&sources), sources }))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Checks that a bound of an uncertain operand doesn't reach `limit` (e.g. division by zero), which would make
+    /// min/max bounding impossible
+    fn check_bounds(operand: &Object, propagation: Propagation, limit: Decimal, message: &str, line: usize) -> Result<(), HawkError> {
+        if let (Propagation::MinMax, Object::Uncertain { value, uncertainty, .. }) = (propagation, operand) {
+            if value - uncertainty <= limit && value + uncertainty >= limit {
+                return Err(HawkError::runtime(format!("{message} {}", operand.user_print(line)?), line))
+            }
+        }
+        Ok(())
+    }
+
+    /// The error for arithmetic whose result doesn't fit in an `Int` or `Decimal`
+    fn too_large(line: usize) -> HawkError {
+        HawkError::runtime(String::from("The result is too large to be represented"), line)
+    }
+
+    /// Splits a number into its value and error sources (exact numbers have none)
+    pub fn uncertain_parts(obj: &Object) -> Option<(Decimal, ErrorSources)> {
+        match obj {
+            Object::Int(x) => Some((Decimal::from(*x as i64), ErrorSources::default())),
+            Object::Decimal(x) => Some((*x, ErrorSources::default())),
+            Object::Uncertain { value, sources, .. } => Some((*value, sources.clone())),
+            _ => None
         }
     }
 
@@ -335,14 +332,18 @@ impl Interpreter {
     
 
     /// Checks if object is greater than or equal to another object
-    pub fn greaterthanequal(operand1: Object, operand2: Object, line: usize) -> Result<Object, (String, usize)> {
+    pub fn greaterthanequal(operand1: Object, operand2: Object, line: usize) -> Result<Object, HawkError> {
+        if let Some(result) = Self::quantity_operation(&operand1, &operand2, TokenType::GreaterThanEqual, Propagation::default(), line)? {
+            return Ok(result)
+        }
+
         if let Object::Int(x) = operand1 {
             if let Object::Int(y) = operand2 {
                 Ok(Object::Boolean(x >= y))
             } else if let Object::Decimal(y) = operand2 {
                 Ok(Object::Boolean((Decimal::from(x as i64)) >= y))
             } else {
-                Err((format!("Can't compare Int to {}", operand2.user_print(line)?), line))
+                Err(HawkError::runtime(format!("Can't compare Int to {}", operand2.user_print(line)?), line))
             }
         } else if let Object::Decimal(x) = operand1 {
             if let Object::Int(y) = operand2 {
@@ -350,22 +351,26 @@ impl Interpreter {
             } else if let Object::Decimal(y) = operand2 {
                 Ok(Object::Boolean(x >= y))
             } else {
-                Err((format!("Can't compare Decimal to {}", operand2.user_print(line)?), line))
+                Err(HawkError::runtime(format!("Can't compare Decimal to {}", operand2.user_print(line)?), line))
             }
         } else {
-            Err((format!("Can't compare {}", operand1.user_print(line)?), line))
+            Err(HawkError::runtime(format!("Can't compare {}", operand1.user_print(line)?), line))
         }
     }
 
     /// Checks if object is greater than another object
-    pub fn greaterthan(operand1: Object, operand2: Object, line: usize) -> Result<Object, (String, usize)> {
+    pub fn greaterthan(operand1: Object, operand2: Object, line: usize) -> Result<Object, HawkError> {
+        if let Some(result) = Self::quantity_operation(&operand1, &operand2, TokenType::GreaterThan, Propagation::default(), line)? {
+            return Ok(result)
+        }
+
         if let Object::Int(x) = operand1 {
             if let Object::Int(y) = operand2 {
                 Ok(Object::Boolean(x > y))
             } else if let Object::Decimal(y) = operand2 {
                 Ok(Object::Boolean((Decimal::from(x as i64)) > y))
             } else {
-                Err((format!("Can't compare Int to {}", operand2.user_print(line)?), line))
+                Err(HawkError::runtime(format!("Can't compare Int to {}", operand2.user_print(line)?), line))
             }
         } else if let Object::Decimal(x) = operand1 {
             if let Object::Int(y) = operand2 {
@@ -373,22 +378,26 @@ impl Interpreter {
             } else if let Object::Decimal(y) = operand2 {
                 Ok(Object::Boolean(x > y))
             } else {
-                Err((format!("Can't compare Decimal to {}", operand2.user_print(line)?), line))
+                Err(HawkError::runtime(format!("Can't compare Decimal to {}", operand2.user_print(line)?), line))
             }
         } else {
-            Err((format!("Can't compare {}", operand1.user_print(line)?), line))
+            Err(HawkError::runtime(format!("Can't compare {}", operand1.user_print(line)?), line))
         }
     }
 
     /// Checks if object is less than or equal to another object
-    pub fn lessthanequal(operand1: Object, operand2: Object, line: usize) -> Result<Object, (String, usize)> {
+    pub fn lessthanequal(operand1: Object, operand2: Object, line: usize) -> Result<Object, HawkError> {
+        if let Some(result) = Self::quantity_operation(&operand1, &operand2, TokenType::LessThanEqual, Propagation::default(), line)? {
+            return Ok(result)
+        }
+
         if let Object::Int(x) = operand1 {
             if let Object::Int(y) = operand2 {
                 Ok(Object::Boolean(x <= y))
             } else if let Object::Decimal(y) = operand2 {
                 Ok(Object::Boolean((Decimal::from(x as i64)) <= y))
             } else {
-                Err((format!("Can't compare Int to {}", operand2.user_print(line)?), line))
+                Err(HawkError::runtime(format!("Can't compare Int to {}", operand2.user_print(line)?), line))
             }
         } else if let Object::Decimal(x) = operand1 {
             if let Object::Int(y) = operand2 {
@@ -396,22 +405,26 @@ impl Interpreter {
             } else if let Object::Decimal(y) = operand2 {
                 Ok(Object::Boolean(x <= y))
             } else {
-                Err((format!("Can't compare Decimal to {}", operand2.user_print(line)?), line))
+                Err(HawkError::runtime(format!("Can't compare Decimal to {}", operand2.user_print(line)?), line))
             }
         } else {
-            Err((format!("Can't compare {}", operand1.user_print(line)?), line))
+            Err(HawkError::runtime(format!("Can't compare {}", operand1.user_print(line)?), line))
         }
     }
 
     /// Checks if object is less than another object
-    pub fn lessthan(operand1: Object, operand2: Object, line: usize) -> Result<Object, (String, usize)> {
+    pub fn lessthan(operand1: Object, operand2: Object, line: usize) -> Result<Object, HawkError> {
+        if let Some(result) = Self::quantity_operation(&operand1, &operand2, TokenType::LessThan, Propagation::default(), line)? {
+            return Ok(result)
+        }
+
         if let Object::Int(x) = operand1 {
             if let Object::Int(y) = operand2 {
                 Ok(Object::Boolean(x < y))
             } else if let Object::Decimal(y) = operand2 {
                 Ok(Object::Boolean((Decimal::from(x as i64)) < y))
             } else {
-                Err((format!("Can't compare Int to {}", operand2.user_print(line)?), line))
+                Err(HawkError::runtime(format!("Can't compare Int to {}", operand2.user_print(line)?), line))
             }
         } else if let Object::Decimal(x) = operand1 {
             if let Object::Int(y) = operand2 {
@@ -419,22 +432,31 @@ impl Interpreter {
             } else if let Object::Decimal(y) = operand2 {
                 Ok(Object::Boolean(x < y))
             } else {
-                Err((format!("Can't compare Decimal to {}", operand2.user_print(line)?), line))
+                Err(HawkError::runtime(format!("Can't compare Decimal to {}", operand2.user_print(line)?), line))
             }
         } else {
-            Err((format!("Can't compare {}", operand1.user_print(line)?), line))
+            Err(HawkError::runtime(format!("Can't compare {}", operand1.user_print(line)?), line))
         }
     }
 
     /// Checks if object is not equal to another object
-    pub fn notequal(operand1: Object, operand2: Object, line: usize) -> Result<Object, (String, usize)> {
+    pub fn notequal(operand1: Object, operand2: Object, line: usize) -> Result<Object, HawkError> {
+        if let Some(result) = Self::quantity_operation(&operand1, &operand2, TokenType::NotEqual, Propagation::default(), line)? {
+            return Ok(result)
+        }
+
+        // Symbolic expressions are compared by their structure, like in `equalequal`
+        if let (Object::Symbolic(x), Object::Symbolic(y)) = (&operand1, &operand2) {
+            return Ok(Object::Boolean(x != y))
+        }
+
         if let Object::Int(x) = operand1 {
             if let Object::Int(y) = operand2 {
                 Ok(Object::Boolean(x != y))
             } else if let Object::Decimal(y) = operand2 {
                 Ok(Object::Boolean((Decimal::from(x as i64)) != y))
             } else {
-                Err((format!("Can't compare Int to {}", operand2.user_print(line)?), line))
+                Err(HawkError::runtime(format!("Can't compare Int to {}", operand2.user_print(line)?), line))
             }
         } else if let Object::Decimal(x) = operand1 {
             if let Object::Int(y) = operand2 {
@@ -442,22 +464,31 @@ impl Interpreter {
             } else if let Object::Decimal(y) = operand2 {
                 Ok(Object::Boolean(x != y))
             } else {
-                Err((format!("Can't compare Decimal to {}", operand2.user_print(line)?), line))
+                Err(HawkError::runtime(format!("Can't compare Decimal to {}", operand2.user_print(line)?), line))
             }
         } else {
-            Err((format!("Can't compare {}", operand1.user_print(line)?), line))
+            Err(HawkError::runtime(format!("Can't compare {}", operand1.user_print(line)?), line))
         }
     }
 
     /// Checks if object is equal to another object
-    pub fn equalequal(operand1: Object, operand2: Object, line: usize) -> Result<Object, (String, usize)> {
+    pub fn equalequal(operand1: Object, operand2: Object, line: usize) -> Result<Object, HawkError> {
+        if let Some(result) = Self::quantity_operation(&operand1, &operand2, TokenType::EqualEqual, Propagation::default(), line)? {
+            return Ok(result)
+        }
+
+        // Symbolic expressions are equal if they have the same structure
+        if let (Object::Symbolic(x), Object::Symbolic(y)) = (&operand1, &operand2) {
+            return Ok(Object::Boolean(x == y))
+        }
+
         if let Object::Int(x) = operand1 {
             if let Object::Int(y) = operand2 {
                 Ok(Object::Boolean(x == y))
             } else if let Object::Decimal(y) = operand2 {
                 Ok(Object::Boolean((Decimal::from(x as i64)) == y))
             } else {
-                Err((format!("Can't compare Int to {}", operand2.user_print(line)?), line))
+                Err(HawkError::runtime(format!("Can't compare Int to {}", operand2.user_print(line)?), line))
             }
         } else if let Object::Decimal(x) = operand1 {
             if let Object::Int(y) = operand2 {
@@ -465,20 +496,20 @@ impl Interpreter {
             } else if let Object::Decimal(y) = operand2 {
                 Ok(Object::Boolean(x == y))
             } else {
-                Err((format!("Can't compare Decimal to {}", operand2.user_print(line)?), line))
+                Err(HawkError::runtime(format!("Can't compare Decimal to {}", operand2.user_print(line)?), line))
             }
         } else {
-            Err((format!("Can't compare {}", operand1.user_print(line)?), line))
+            Err(HawkError::runtime(format!("Can't compare {}", operand1.user_print(line)?), line))
         }
     }
 
     /// Performs logical AND on two booleans
-    pub fn and(operand1: Object, operand2: Object, line: usize) -> Result<Object, (String, usize)> {
+    pub fn and(operand1: Object, operand2: Object, line: usize) -> Result<Object, HawkError> {
         if let Object::Boolean(op1) = operand1 {
             if let Object::Boolean(op2) = operand2 {
                 Ok(Object::Boolean(op1 && op2))
             } else {
-                Err((
+                Err(HawkError::runtime(
                     format!(
                         "Logical operations can only be performed on booleans, not {}",
                         operand2.user_print(line)?
@@ -487,7 +518,7 @@ impl Interpreter {
                 ))
             }
         } else {
-            Err((
+            Err(HawkError::runtime(
                 format!("Logical operations can only be performed on booleans, not {}", operand1.user_print(line)?),
                 line,
             ))
@@ -495,12 +526,12 @@ impl Interpreter {
     }
 
     /// Performs logical OR on two booleans
-    pub fn or(operand1: Object, operand2: Object, line: usize) -> Result<Object, (String, usize)> {
+    pub fn or(operand1: Object, operand2: Object, line: usize) -> Result<Object, HawkError> {
         if let Object::Boolean(op1) = operand1 {
             if let Object::Boolean(op2) = operand2 {
                 Ok(Object::Boolean(op1 || op2))
             } else {
-                Err((
+                Err(HawkError::runtime(
                     format!(
                         "Logical operations can only be performed on booleans, not {}",
                         operand2.user_print(line)?
@@ -509,7 +540,7 @@ impl Interpreter {
                 ))
             }
         } else {
-            Err((
+            Err(HawkError::runtime(
                 format!("Logical operations can only be performed on booleans, not {}", operand1.user_print(line)?),
                 line,
             ))
@@ -517,11 +548,11 @@ impl Interpreter {
     }
 
     /// Performs logical NOT on a boolean
-    pub fn not(eval_op: Object, line: usize) -> Result<Object, (String, usize)> {
+    pub fn not(eval_op: Object, line: usize) -> Result<Object, HawkError> {
         if let Object::Boolean(x) = eval_op {
             Ok(Object::Boolean(!x))
         } else {
-            Err((
+            Err(HawkError::runtime(
                 format!("Logical operations can only be performed on booleans, not {}", eval_op.user_print(line)?),
                 line,
             ))
@@ -529,10 +560,17 @@ impl Interpreter {
     }
 
     /// Negates a number
-    pub fn negate(eval_op: Object, line: usize) -> Result<Object, (String, usize)> {
+    pub fn negate(eval_op: Object, line: usize) -> Result<Object, HawkError> {
         match eval_op {
             Object::Int(x) => Ok(Object::Int(-x)),
             Object::Decimal(x) => Ok(Object::Decimal(-x)),
+            Object::Uncertain { value, uncertainty, sources } => Ok(Object::Uncertain {
+                value: -value,
+                uncertainty,
+                sources: sources.scale(dec!(-1)),
+            }),
+            Object::Quantity { value, unit } => Ok(Object::Quantity { value: Box::new(Interpreter::negate(*value, line)?), unit }),
+            Object::Symbolic(expr) => symbolic::negate(&expr, line),
             Object::Column(operand1_data) => {
                 let mut results: Vec<Object> = Vec::new();
                 for operand1 in operand1_data {
@@ -540,34 +578,26 @@ impl Interpreter {
                 }
                 Ok(Object::Column(results))
             }
-            _ => Err((format!("Expected number, found {}", eval_op.user_print(line)?), line)),
+            _ => Err(HawkError::runtime(format!("Expected number, found {}", eval_op.user_print(line)?), line)),
         }
     }
 
-    /// Adds an uncertainty to a number
-    pub fn make_uncertain(operand1: Object, operand2: Object, line: usize) -> Result<Object, (String, usize)> {
+    /// Adds an uncertainty to a number, creating a new independent error source
+    pub fn make_uncertain(operand1: Object, operand2: Object, line: usize) -> Result<Object, HawkError> {
+        if let Some(result) = Self::quantity_operation(&operand1, &operand2, TokenType::PlusMinus, Propagation::default(), line)? {
+            return Ok(result)
+        }
+
         match operand1 {
             Object::Int(x) => match operand2 {
-                Object::Int(y) => Ok(Object::Uncertain {
-                    value: Decimal::from(x as i64),
-                    uncertainty: Decimal::from(y as i64),
-                }),
-                Object::Decimal(y) => Ok(Object::Uncertain {
-                    value: Decimal::from(x as i64),
-                    uncertainty: y,
-                }),
-                _ => Err((format!("Can't add {} as uncertainty", operand2.user_print(line)?), line)),
+                Object::Int(y) => Ok(Object::uncertain(Decimal::from(x as i64), Decimal::from(y as i64))),
+                Object::Decimal(y) => Ok(Object::uncertain(Decimal::from(x as i64), y)),
+                _ => Err(HawkError::runtime(format!("Can't add {} as uncertainty", operand2.user_print(line)?), line)),
             },
             Object::Decimal(x) => match operand2 {
-                Object::Int(y) => Ok(Object::Uncertain {
-                    value: x,
-                    uncertainty: Decimal::from(y as i64),
-                }),
-                Object::Decimal(y) => Ok(Object::Uncertain {
-                    value: x,
-                    uncertainty: y,
-                }),
-                _ => Err((format!("Can't add {} as uncertainty", operand2.user_print(line)?), line)),
+                Object::Int(y) => Ok(Object::uncertain(x, Decimal::from(y as i64))),
+                Object::Decimal(y) => Ok(Object::uncertain(x, y)),
+                _ => Err(HawkError::runtime(format!("Can't add {} as uncertainty", operand2.user_print(line)?), line)),
             },
             Object::Column(operand1_data) => {
                 let mut results: Vec<Object> = Vec::new();
@@ -586,23 +616,21 @@ impl Interpreter {
                 }
                 Ok(Object::Column(results))
             }
-            _ => Err((format!("Can't add uncertainty to {}", operand1.user_print(line)?), line)),
+            _ => Err(HawkError::runtime(format!("Can't add uncertainty to {}", operand1.user_print(line)?), line)),
         }
     }
 }
 
 #[test]
 fn addition() {
-    assert_eq!(Interpreter::add(Object::Int(4), Object::Int(5), 0), Ok(Object::Int(9)));
-    assert_eq!(Interpreter::add(Object::Int(4), Object::Decimal(dec!(5.1)), 0), Ok(Object::Decimal(dec!(9.1))));
-    assert_eq!(Interpreter::add(Object::Decimal(dec!(4.2)), Object::Int(5), 0), Ok(Object::Decimal(dec!(9.2))));
-    assert_eq!(Interpreter::add(Object::Decimal(dec!(4.3)), Object::Decimal(dec!(5.2)), 0),Ok(Object::Decimal(dec!(9.5))));
-    if let Ok(Object::Uncertain { value, uncertainty }) = Interpreter::add(
-        Object::Uncertain {
-            value: dec!(1.0),
-            uncertainty: dec!(0.1),
-        },
+    assert_eq!(Interpreter::add(Object::Int(4), Object::Int(5), Propagation::WorstCase, 0), Ok(Object::Int(9)));
+    assert_eq!(Interpreter::add(Object::Int(4), Object::Decimal(dec!(5.1)), Propagation::WorstCase, 0), Ok(Object::Decimal(dec!(9.1))));
+    assert_eq!(Interpreter::add(Object::Decimal(dec!(4.2)), Object::Int(5), Propagation::WorstCase, 0), Ok(Object::Decimal(dec!(9.2))));
+    assert_eq!(Interpreter::add(Object::Decimal(dec!(4.3)), Object::Decimal(dec!(5.2)), Propagation::WorstCase, 0),Ok(Object::Decimal(dec!(9.5))));
+    if let Ok(Object::Uncertain { value, uncertainty, .. }) = Interpreter::add(
+        Object::uncertain(dec!(1.0), dec!(0.1)),
         Object::Int(3),
+        Propagation::WorstCase,
         1,
     ) {
         assert_eq!(value, dec!(4.0));
@@ -612,6 +640,7 @@ fn addition() {
         Interpreter::add(
             Object::String(String::from("Hello ")),
             Object::String(String::from("World")),
+            Propagation::WorstCase,
             0
         ),
         Ok(Object::String(String::from("Hello World")))
@@ -620,25 +649,84 @@ fn addition() {
 
 #[test]
 fn multiply() {
-    assert_eq!(Interpreter::multiply(Object::Int(4), Object::Int(5), 0), Ok(Object::Int(20)));
-    assert_eq!(Interpreter::multiply(Object::Int(4), Object::Decimal(dec!(5.1)), 0), Ok(Object::Decimal(dec!(20.4))));
-    assert_eq!(Interpreter::multiply(Object::Decimal(dec!(1.5)), Object::Int(2), 0), Ok(Object::Decimal(dec!(3.0))));
+    assert_eq!(Interpreter::multiply(Object::Int(4), Object::Int(5), Propagation::WorstCase, 0), Ok(Object::Int(20)));
+    assert_eq!(Interpreter::multiply(Object::Int(4), Object::Decimal(dec!(5.1)), Propagation::WorstCase, 0), Ok(Object::Decimal(dec!(20.4))));
+    assert_eq!(Interpreter::multiply(Object::Decimal(dec!(1.5)), Object::Int(2), Propagation::WorstCase, 0), Ok(Object::Decimal(dec!(3.0))));
     assert_eq!(
-        Interpreter::multiply(Object::Decimal(dec!(1.5)), Object::Decimal(dec!(1.5)), 0),
+        Interpreter::multiply(Object::Decimal(dec!(1.5)), Object::Decimal(dec!(1.5)), Propagation::WorstCase, 0),
         Ok(Object::Decimal(dec!(2.25)))
     );
-    if let Ok(Object::Uncertain { value, uncertainty }) = Interpreter::multiply(
-        Object::Uncertain {
-            value: dec!(6.8),
-            uncertainty: dec!(0.2),
-        },
-        Object::Uncertain {
-            value: dec!(3.75),
-            uncertainty: dec!(0.05),
-        },
+    if let Ok(Object::Uncertain { value, uncertainty, .. }) = Interpreter::multiply(
+        Object::uncertain(dec!(6.8), dec!(0.2)),
+        Object::uncertain(dec!(3.75), dec!(0.05)),
+        Propagation::WorstCase,
         1,
     ) {
         assert_eq!(value, dec!(25.5));
         assert_eq!(uncertainty, dec!(1.09))
     }
 }
+
+#[test]
+fn overflow() {
+    let too_large = Err(HawkError::runtime(String::from("The result is too large to be represented"), 3));
+    let big = Object::Decimal(dec!(150000000000000000000));
+
+    assert_eq!(Interpreter::multiply(big.clone(), Object::Decimal(dec!(100000000000000000000)), Propagation::WorstCase, 3), too_large);
+    assert_eq!(Interpreter::multiply(Object::uncertain(dec!(1e20), dec!(1)), big.clone(), Propagation::WorstCase, 3), too_large);
+    assert_eq!(Interpreter::add(Object::Int(i128::MAX), Object::Int(1), Propagation::WorstCase, 3), too_large);
+    assert_eq!(Interpreter::exponent(Object::Int(2), Object::Int(200), Propagation::WorstCase, 3), too_large);
+    assert_eq!(Interpreter::exponent(big, Object::Int(2), Propagation::WorstCase, 3), too_large);
+    assert_eq!(Interpreter::exponent(Object::Int(2), Object::Int(-1), Propagation::WorstCase, 3), Ok(Object::Decimal(dec!(0.5))));
+}
+
+#[test]
+fn correlated_uncertainty() {
+    let x = Object::uncertain(dec!(2.0), dec!(0.1));
+    let y = Object::uncertain(dec!(5.0), dec!(0.2));
+
+    let uncertainty = |result: Result<Object, HawkError>| {
+        if let Ok(Object::Uncertain { uncertainty, .. }) = result {
+            uncertainty
+        } else {
+            panic!("Expected uncertain result")
+        }
+    };
+
+    // x - x is exact, x + x and x * x are fully correlated
+    assert_eq!(uncertainty(Interpreter::subtract(x.clone(), x.clone(), Propagation::WorstCase, 0)), dec!(0));
+    assert_eq!(uncertainty(Interpreter::add(x.clone(), x.clone(), Propagation::WorstCase, 0)), dec!(0.2));
+    assert_eq!(uncertainty(Interpreter::multiply(x.clone(), x.clone(), Propagation::WorstCase, 0)), dec!(0.4));
+    assert_eq!(uncertainty(Interpreter::divide(x.clone(), x.clone(), Propagation::WorstCase, 0)), dec!(0));
+
+    // (x + y) - y only has the uncertainty of x
+    let sum = Interpreter::add(x.clone(), y.clone(), Propagation::WorstCase, 0).unwrap();
+    assert_eq!(uncertainty(Interpreter::subtract(sum, y.clone(), Propagation::WorstCase, 0)), dec!(0.1));
+
+    // x ^ 2 is the same as x * x
+    assert_eq!(uncertainty(Interpreter::exponent(x.clone(), Object::Int(2), Propagation::WorstCase, 0)), dec!(0.4));
+    assert_eq!(uncertainty(Interpreter::negate(x, 0)), dec!(0.1));
+}
+
+#[test]
+fn propagation_modes() {
+    let x = Object::uncertain(dec!(3.0), dec!(0.3));
+    let y = Object::uncertain(dec!(4.0), dec!(0.4));
+
+    let uncertainty = |result: Result<Object, HawkError>| {
+        if let Ok(Object::Uncertain { uncertainty, .. }) = result {
+            uncertainty
+        } else {
+            panic!("Expected uncertain result")
+        }
+    };
+
+    assert_eq!(uncertainty(Interpreter::add(x.clone(), y.clone(), Propagation::WorstCase, 0)), dec!(0.7));
+    assert_eq!(uncertainty(Interpreter::add(x.clone(), y.clone(), Propagation::Quadrature, 0)), dec!(0.5));
+    // Correlated contributions still cancel when adding in quadrature
+    assert_eq!(uncertainty(Interpreter::subtract(x.clone(), x.clone(), Propagation::Quadrature, 0)), dec!(0));
+
+    // (3 ± 0.3) * (4 ± 0.4) ranges from 2.7 * 3.6 = 9.72 to 3.3 * 4.4 = 14.52
+    assert_eq!(uncertainty(Interpreter::multiply(x.clone(), y.clone(), Propagation::MinMax, 0)), dec!(2.4));
+    assert!(Interpreter::divide(x, Object::uncertain(dec!(0.1), dec!(0.2)), Propagation::MinMax, 0).is_err());
+}
@@ -0,0 +1,246 @@
+use std::collections::HashMap;
+
+use crate::Interpreter;
+use hawk_cas::expression::Expression as CASExpression;
+use hawk_cas::factor::Factor;
+use hawk_cas::ToCASExpr;
+use hawk_common::error::HawkError;
+use hawk_common::object::Object;
+use hawk_common::tree::{Expression, Statement};
+
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+/// A derivative worked out by `symbolic_derivative`, kept with the function it is of so that it is worked out again
+/// if the function is redefined
+pub(crate) struct CachedDerivative {
+    /// Parameters and body of the function, or `None` for standard library functions
+    function: Option<(Vec<String>, Box<Statement>)>,
+    derivative: Option<(Vec<String>, Expression)>,
+}
+
+impl Interpreter<'_> {
+    /// Evaluates `propagate(f, args...)`: calls `f` with the values of the arguments and propagates their
+    /// uncertainties in quadrature with the partial derivatives of `f`. Gives a record with the result, its
+    /// uncertainty and the contribution `∂f/∂x * σ` of each argument, to find the dominant source of error
+    pub fn eval_propagate_call(&mut self, args: Vec<Box<Expression>>) -> Result<Object, HawkError> {
+        let mut args = args.into_iter();
+        let function = self.eval_function_name("propagate", args.next())?;
+
+        let mut evaled_args: Vec<Object> = Vec::new();
+        for arg in args {
+            evaled_args.push(self.eval_expression(arg)?)
+        }
+
+        // Call the function with the values of the arguments
+        let mut values = evaled_args.clone();
+        for value in values.iter_mut() {
+            if let Object::Uncertain { value: x, .. } = value {
+                *value = Object::Decimal(*x)
+            }
+        }
+        let result = self.call_function(function.clone(), values.clone())?;
+        let (value, mut sources) = Self::uncertain_parts(&result)
+            .ok_or(HawkError::runtime(format!("Expected Decimal or Int, got {result}"), self.line))?;
+
+        let mut derivatives: Vec<Object> = Vec::new();
+        let mut contributions: Vec<Decimal> = Vec::new();
+        for (index, arg) in evaled_args.iter().enumerate() {
+            let (derivative, contribution) = match arg {
+                Object::Uncertain { uncertainty, sources: arg_sources, .. } => {
+                    let derivative = self.partial_derivative(&function, &values, index)?;
+                    sources = sources.combine(dec!(1), arg_sources, derivative);
+                    (Object::Decimal(derivative), (derivative * uncertainty).abs())
+                },
+                // Exact arguments don't contribute, so their derivative is only given if it doesn't need estimating
+                Object::Int(_) | Object::Decimal(_) => {
+                    let derivative = self.symbolic_partial_derivative(&function, &values, index)?;
+                    (derivative.map_or(Object::Null, Object::Decimal), dec!(0))
+                },
+                x => return Err(HawkError::runtime(format!("Expected number as argument to propagate, found {}", x.user_print(self.line)?), self.line)),
+            };
+            derivatives.push(derivative);
+            contributions.push(contribution);
+        }
+
+        let uncertainty = sources.quadrature();
+        Ok(Object::from(HashMap::from([
+            (String::from("value"), Object::Uncertain { value, uncertainty, sources }),
+            (String::from("uncertainty"), Object::Decimal(uncertainty)),
+            (String::from("derivatives"), Object::from(derivatives)),
+            (String::from("contributions"), Object::from(contributions)),
+        ])))
+    }
+
+    /// The partial derivative of the function called `identifier` with respect to argument `index`, at `args`. It is
+    /// found by differentiating the function symbolically where possible, and otherwise estimated from the function's
+    /// values a small step either side of the argument. The step is relative to the argument, and much smaller than
+    /// its uncertainty would be, so that it stays inside the function's domain (e.g. `ln` at `0.1 ± 0.2`)
+    pub(crate) fn partial_derivative(&mut self, identifier: &str, args: &[Object], index: usize) -> Result<Decimal, HawkError> {
+        if let Some(derivative) = self.symbolic_partial_derivative(identifier, args, index)? {
+            return Ok(derivative)
+        }
+
+        let mut args = args.to_vec();
+        let value = self.number(args[index].clone())?;
+        let step = value.abs().max(dec!(1)) * dec!(0.000001);
+
+        // Change the argument to `value + step` to find max
+        args[index] = Object::Decimal(value + step);
+        let max = self.call_function(String::from(identifier), args.clone())?;
+
+        // Change the argument to `value - step` to find min
+        args[index] = Object::Decimal(value - step);
+        let min = self.call_function(String::from(identifier), args)?;
+
+        Ok((self.number(max)? - self.number(min)?) / (dec!(2) * step))
+    }
+
+    /// The partial derivative of the function called `identifier` with respect to argument `index`, at `args`, if it
+    /// can be differentiated symbolically
+    fn symbolic_partial_derivative(&mut self, identifier: &str, args: &[Object], index: usize) -> Result<Option<Decimal>, HawkError> {
+        let Some(derivative) = self.symbolic_derivative(identifier, args.len(), index) else {
+            return Ok(None)
+        };
+
+        // Integers are given as decimals so that the derivative doesn't use integer division
+        let args = args.iter()
+            .map(|arg| match arg {
+                Object::Int(x) => Object::Decimal(Decimal::from(*x as i64)),
+                arg => arg.clone(),
+            })
+            .collect();
+        let result = self.call(&derivative, args)?;
+        self.number(result).map(Some)
+    }
+
+    /// Differentiates a function that just returns an expression (e.g. `(x, y) => x * y^2`) with respect to one of its
+    /// parameters, or a standard library function like `sin`, giving a function for the derivative. Gives `None` if it
+    /// can't be differentiated, e.g. if it calls a function the CAS doesn't know the derivative of. Derivatives are
+    /// cached, since this is called for every row of a column
+    fn symbolic_derivative(&mut self, identifier: &str, arity: usize, index: usize) -> Option<Object> {
+        let (function, captured) = match self.get_variable(String::from(identifier)) {
+            Object::Function { params, block, captured } => (Some((params, block)), captured),
+            _ if self.natives.contains_key(identifier) => return None,
+            _ => (None, HashMap::new()),
+        };
+
+        let key = (String::from(identifier), arity, index);
+        let derivative = match self.derivatives.get(&key) {
+            Some(cached) if cached.function == function => cached.derivative.clone(),
+            _ => {
+                let derivative = Self::differentiate(identifier, function.as_ref(), arity, index);
+                self.derivatives.insert(key, CachedDerivative { function, derivative: derivative.clone() });
+                derivative
+            },
+        };
+
+        let (params, derivative) = derivative?;
+        Some(Object::Function { params, block: Box::new(Statement::Return(Box::new(derivative))), captured })
+    }
+
+    /// Differentiates `function` (given as its parameters and body), or the standard library function called
+    /// `identifier` if it is `None`, giving the parameters and the expression for the derivative
+    fn differentiate(
+        identifier: &str, function: Option<&(Vec<String>, Box<Statement>)>, arity: usize, index: usize,
+    ) -> Option<(Vec<String>, Expression)> {
+        let (params, expr) = match function {
+            Some((params, block)) => (params.clone(), block.returned_expression()?.to_cas_expr()?),
+            None => {
+                let params: Vec<String> = (0..arity).map(|i| format!("x{i}")).collect();
+                let args = params.iter().map(|param| CASExpression::symbol(param.clone())).collect();
+                (params, CASExpression::from_factor(Factor::function(identifier, args)))
+            },
+        };
+
+        let derivative = expr.derivative(params.get(index)?).ok()?;
+        Some((params, Expression::from_cas_expr(derivative)))
+    }
+
+    /// The value of a number, ignoring any uncertainty
+    fn number(&self, object: Object) -> Result<Decimal, HawkError> {
+        match object {
+            Object::Decimal(x) | Object::Uncertain { value: x, .. } => Ok(x),
+            Object::Int(x) => Ok(Decimal::from(x as i64)),
+            x => Err(HawkError::runtime(format!("Expected Decimal or Int, got {x}"), self.line)),
+        }
+    }
+}
+
+#[test]
+fn propagate() {
+    use hawk_common::environment::Memory;
+
+    let source = String::from("function area(w, h) {
+    return w * h
+}
+let w = 2 ± 0.1
+let h = 3 ± 0.4
+let result = propagate(area, w, h)
+let value = result.value
+let contributions = result.contributions
+let derivatives = result.derivatives
+let decay = (t) => e()^(-t / 2)
+let decay_result = propagate(\"decay\", 0 ± 0.2)
+let sine_result = propagate(\"sin\", 0 ± 0.1)
+let scaled_result = propagate(area, w, 2)
+let decayed = decay_result.uncertainty
+let sine = sine_result.uncertainty
+let scaled = scaled_result.contributions
+let scaled_derivatives = scaled_result.derivatives
+function safe_ln(x, scale) {
+    if x > 0 {
+        return scale * ln(x)
+    }
+    return 0
+}
+let safe_result = propagate(safe_ln, 0.1 ± 0.2, 1)
+let safe_derivatives = safe_result.derivatives");
+    let globals = crate::run::run(source, HashMap::new(), false, &mut Memory::new()).unwrap();
+
+    // σ² = (h σ_w)² + (w σ_h)² = 0.3² + 0.8²
+    assert_eq!(globals.get("contributions"), Some(&Object::Array(vec![Object::Decimal(dec!(0.3)), Object::Decimal(dec!(0.8))])));
+    assert_eq!(globals.get("derivatives"), Some(&Object::Array(vec![Object::Decimal(dec!(3)), Object::Decimal(dec!(2))])));
+    if let Some(Object::Uncertain { value, uncertainty, .. }) = globals.get("value") {
+        assert_eq!(*value, dec!(6));
+        assert_eq!(uncertainty.round_dp(6), dec!(0.854400));
+    } else {
+        panic!("expected an uncertain value")
+    }
+
+    assert_eq!(globals.get("decayed").map(|x| x.user_print(0).unwrap()), Some(String::from("0.1")));
+    assert_eq!(globals.get("sine"), Some(&Object::Decimal(dec!(0.1))));
+    assert_eq!(globals.get("scaled"), Some(&Object::Array(vec![Object::Decimal(dec!(0.2)), Object::Decimal(dec!(0))])));
+    assert_eq!(globals.get("scaled_derivatives"), Some(&Object::Array(vec![Object::Decimal(dec!(2)), Object::Decimal(dec!(2))])));
+
+    // The step for a numeric derivative stays inside the domain of ln, and exact arguments aren't differentiated
+    match globals.get("safe_derivatives") {
+        Some(Object::Array(derivatives)) => match &derivatives[..] {
+            [Object::Decimal(derivative), Object::Null] => assert_eq!(derivative.round_dp(6), dec!(10)),
+            derivatives => panic!("expected a derivative and null, found {derivatives:?}"),
+        },
+        x => panic!("expected an array, found {x:?}"),
+    }
+}
+
+#[test]
+fn cached_derivatives() {
+    use hawk_common::environment::Memory;
+
+    let mut env = Memory::new();
+    let mut interpreter = crate::run::Builder::new().interpreter(HashMap::new(), &mut env);
+    interpreter.run_source("let f = (x) => x^2
+let first_result = propagate(f, 3 ± 0.1)
+let second_result = propagate(f, 2 ± 0.1)
+let f = (x) => 5 * x
+let redefined_result = propagate(f, 2 ± 0.1)
+let first = first_result.derivatives
+let second = second_result.derivatives
+let redefined = redefined_result.derivatives").unwrap();
+
+    // The derivative is worked out once, and again when the function is redefined
+    assert_eq!(interpreter.derivatives.len(), 1);
+    assert_eq!(interpreter.globals.get("first"), Some(&Object::Array(vec![Object::Decimal(dec!(6))])));
+    assert_eq!(interpreter.globals.get("second"), Some(&Object::Array(vec![Object::Decimal(dec!(4))])));
+    assert_eq!(interpreter.globals.get("redefined"), Some(&Object::Array(vec![Object::Decimal(dec!(5))])));
+}
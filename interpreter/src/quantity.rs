@@ -0,0 +1,200 @@
+use crate::Interpreter;
+use crate::standard_lib::symbolic;
+
+use hawk_common::error::HawkError;
+use hawk_common::object::Object;
+use hawk_common::token::TokenType;
+use hawk_common::uncertainty::Propagation;
+use hawk_common::units::{self, Unit};
+
+use rust_decimal::prelude::*;
+
+impl Interpreter<'_> {
+    /// Carries out an operation where at least one operand has units, checking that the units are compatible.
+    /// Returns `None` if neither operand is a `Quantity`, so that the operation can be carried out as usual
+    pub fn quantity_operation(
+        operand1: &Object, operand2: &Object, operator: TokenType, propagation: Propagation, line: usize,
+    ) -> Result<Option<Object>, HawkError> {
+        let operation = |operand1: Object, operand2: Object| Self::binary_operation(operand1, operand2, operator, propagation, line);
+
+        let result = match (operand1, operand2) {
+            // Apply the operation to each row of a column
+            (Object::Quantity { .. }, Object::Column(data)) => {
+                let mut results: Vec<Object> = Vec::new();
+                for operand2 in data {
+                    results.push(operation(operand1.clone(), operand2.clone())?)
+                }
+                Object::Column(results)
+            },
+            (Object::Column(data), Object::Quantity { .. }) => {
+                let mut results: Vec<Object> = Vec::new();
+                for operand1 in data {
+                    results.push(operation(operand1.clone(), operand2.clone())?)
+                }
+                Object::Column(results)
+            },
+
+            (Object::Quantity { unit: unit1, .. }, Object::Quantity { unit: unit2, .. })
+            if !matches!(operator, TokenType::Asterisk | TokenType::Slash | TokenType::Caret) && unit1.dimension() != unit2.dimension() => {
+                return Err(HawkError::runtime(format!(
+                    "Can't combine {} with {}: the units don't match", operand1.user_print(line)?, operand2.user_print(line)?
+                ), line))
+            },
+            (Object::Quantity { value: value1, unit: unit1 }, Object::Quantity { value: value2, unit: unit2 }) => match operator {
+                TokenType::Asterisk => Self::quantity(operation(*value1.clone(), *value2.clone())?, unit1.multiply(unit2), line)?,
+                TokenType::Slash => Self::quantity(operation(*value1.clone(), *value2.clone())?, unit1.divide(unit2), line)?,
+                TokenType::Caret => return Err(HawkError::runtime(format!("Can't raise {} to a power with units", operand1.user_print(line)?), line)),
+                TokenType::Plus | TokenType::Minus | TokenType::PlusMinus => {
+                    let value2 = Self::convert(*value2.clone(), unit2, unit1, line)?;
+                    Self::quantity(operation(*value1.clone(), value2)?, unit1.clone(), line)?
+                },
+                // Comparisons
+                _ => operation(*value1.clone(), Self::convert(*value2.clone(), unit2, unit1, line)?)?,
+            },
+
+            (Object::Quantity { value, unit }, other) if Self::uncertain_parts(other).is_some() => match operator {
+                TokenType::Asterisk | TokenType::Slash => Self::quantity(operation(*value.clone(), other.clone())?, unit.clone(), line)?,
+                TokenType::Caret => {
+                    let power = match other {
+                        Object::Int(power) => Decimal::from_i128(*power),
+                        Object::Decimal(power) => Some(*power),
+                        _ => None,
+                    }.ok_or(HawkError::runtime(format!("Can't raise {} to an uncertain power", operand1.user_print(line)?), line))?;
+
+                    Self::quantity(operation(*value.clone(), other.clone())?, Self::unit_power(unit, power, line)?, line)?
+                },
+                _ => return Err(HawkError::runtime(format!(
+                    "Can't combine {} with {}, which has no units", operand1.user_print(line)?, operand2.user_print(line)?
+                ), line)),
+            },
+
+            (other, Object::Quantity { value, unit }) if Self::uncertain_parts(other).is_some() => match operator {
+                TokenType::Asterisk => Self::quantity(operation(other.clone(), *value.clone())?, unit.clone(), line)?,
+                TokenType::Slash => Self::quantity(operation(other.clone(), *value.clone())?, unit.pow(-1), line)?,
+                // `9.81 ± 0.02 m/s^2` gives the value the units of the uncertainty
+                TokenType::PlusMinus => Self::quantity(operation(other.clone(), *value.clone())?, unit.clone(), line)?,
+                _ => return Err(HawkError::runtime(format!(
+                    "Can't combine {}, which has no units, with {}", operand1.user_print(line)?, operand2.user_print(line)?
+                ), line)),
+            },
+
+            _ => return Ok(None)
+        };
+
+        Ok(Some(result))
+    }
+
+    /// Matches operator and carries out a binary operation
+    pub fn binary_operation(
+        operand1: Object, operand2: Object, operator: TokenType, propagation: Propagation, line: usize,
+    ) -> Result<Object, HawkError> {
+        if let Some(result) = symbolic::operation(&operand1, &operand2, operator, line)? {
+            return Ok(result)
+        }
+        match operator {
+            TokenType::Plus => Self::add(operand1, operand2, propagation, line),
+            TokenType::Minus => Self::subtract(operand1, operand2, propagation, line),
+            TokenType::Asterisk => Self::multiply(operand1, operand2, propagation, line),
+            TokenType::Slash => Self::divide(operand1, operand2, propagation, line),
+            TokenType::Caret => Self::exponent(operand1, operand2, propagation, line),
+            TokenType::PlusMinus => Self::make_uncertain(operand1, operand2, line),
+            TokenType::EqualEqual => Self::equalequal(operand1, operand2, line),
+            TokenType::NotEqual => Self::notequal(operand1, operand2, line),
+            TokenType::Or => Self::or(operand1, operand2, line),
+            TokenType::And => Self::and(operand1, operand2, line),
+            TokenType::LessThan => Self::lessthan(operand1, operand2, line),
+            TokenType::LessThanEqual => Self::lessthanequal(operand1, operand2, line),
+            TokenType::GreaterThan => Self::greaterthan(operand1, operand2, line),
+            TokenType::GreaterThanEqual => Self::greaterthanequal(operand1, operand2, line),
+            _ => Err(HawkError::runtime(
+                "Couldn't evaluate binary expression: operator does not match any binary operator".to_string(),
+                line,
+            )),
+        }
+    }
+
+    /// Creates a quantity, dropping the units if they have cancelled out (e.g. `m/km` becomes a plain number)
+    fn quantity(value: Object, unit: Unit, line: usize) -> Result<Object, HawkError> {
+        if unit.0.is_empty() {
+            Ok(value)
+        } else if unit.dimension().is_dimensionless() {
+            Self::scale(&value, unit.factor(), line)
+        } else {
+            Ok(Object::Quantity { value: Box::new(value), unit })
+        }
+    }
+
+    /// Raises a unit to a power, which must give whole powers of every symbol (e.g. `(m^2)^0.5` is allowed)
+    fn unit_power(unit: &Unit, power: Decimal, line: usize) -> Result<Unit, HawkError> {
+        let mut parts: Vec<(String, i32)> = Vec::new();
+        for (symbol, symbol_power) in unit.0.iter() {
+            let new_power = Decimal::from(*symbol_power) * power;
+            match new_power.to_i32() {
+                Some(whole_power) if new_power.fract().is_zero() => parts.push((symbol.clone(), whole_power)),
+                _ => return Err(HawkError::runtime(format!("Can't raise {unit} to the power of {power}"), line)),
+            }
+        }
+        Ok(Unit(parts))
+    }
+
+    /// Converts a value from one unit to another with the same dimension
+    pub fn convert(value: Object, from: &Unit, to: &Unit, line: usize) -> Result<Object, HawkError> {
+        if from == to {
+            return Ok(value)
+        }
+
+        if from.dimension() != to.dimension() {
+            return Err(HawkError::runtime(format!(
+                "Can't convert {from} to {to}: the units don't match ({} and {})", from.dimension(), to.dimension()
+            ), line))
+        }
+
+        // Dividing the sizes as fractions keeps conversions like m/s to km/h exact
+        let ((from_numerator, from_denominator), (to_numerator, to_denominator)) = (from.fraction(), to.fraction());
+        let factor = from_numerator.checked_mul(to_denominator)
+            .zip(from_denominator.checked_mul(to_numerator))
+            .and_then(|(numerator, denominator)| numerator.checked_div(denominator));
+        match factor {
+            Some(factor) => Self::scale(&value, factor, line),
+            None => Err(HawkError::runtime(format!("Can't convert {from} to {to}"), line)),
+        }
+    }
+
+    fn scale(value: &Object, factor: Decimal, line: usize) -> Result<Object, HawkError> {
+        units::scale(value, factor).ok_or(HawkError::runtime(format!("Expected a number, found {}", value.user_print(line)?), line))
+    }
+}
+
+#[test]
+fn quantities() {
+    use rust_decimal_macros::dec;
+
+    let metres = |x| Object::Quantity { value: Box::new(Object::Int(x)), unit: Unit::parse("m").unwrap() };
+    let seconds = Object::Quantity { value: Box::new(Object::Int(2)), unit: Unit::parse("s").unwrap() };
+    let kilometres = Object::Quantity { value: Box::new(Object::Decimal(dec!(1.5))), unit: Unit::parse("km").unwrap() };
+
+    assert_eq!(Interpreter::add(metres(3), metres(4), Propagation::WorstCase, 0), Ok(metres(7)));
+    assert_eq!(
+        Interpreter::add(kilometres.clone(), metres(500), Propagation::WorstCase, 0),
+        Ok(Object::Quantity { value: Box::new(Object::Decimal(dec!(2))), unit: Unit::parse("km").unwrap() })
+    );
+    assert!(Interpreter::add(metres(3), seconds.clone(), Propagation::WorstCase, 0).is_err());
+    assert!(Interpreter::add(metres(3), Object::Int(1), Propagation::WorstCase, 0).is_err());
+
+    assert_eq!(
+        Interpreter::divide(metres(6), seconds.clone(), Propagation::WorstCase, 0),
+        Ok(Object::Quantity { value: Box::new(Object::Int(3)), unit: Unit::parse("m/s").unwrap() })
+    );
+    assert_eq!(
+        Interpreter::exponent(metres(3), Object::Int(2), Propagation::WorstCase, 0),
+        Ok(Object::Quantity { value: Box::new(Object::Int(9)), unit: Unit::parse("m^2").unwrap() })
+    );
+    // Units that cancel out give a plain number
+    assert_eq!(Interpreter::divide(kilometres, metres(500), Propagation::WorstCase, 0), Ok(Object::Decimal(dec!(3))));
+    let kilometre = Object::Quantity { value: Box::new(Object::Int(1)), unit: Unit::parse("km").unwrap() };
+    assert!(matches!(Interpreter::lessthan(metres(600), kilometre, 0), Ok(Object::Boolean(true))));
+
+    // 1000/3600 can't be written exactly, so converting has to divide the sizes of the units together
+    let speed = Interpreter::convert(Object::Int(50), &Unit::parse("m/s").unwrap(), &Unit::parse("km/h").unwrap(), 0);
+    assert_eq!(speed, Ok(Object::Decimal(dec!(180))));
+}
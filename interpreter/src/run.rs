@@ -1,71 +1,186 @@
 use std::collections::HashMap;
 
+use hawk_common::environment::Environment;
+use hawk_common::error::HawkError;
 use hawk_common::object::Object;
+use hawk_common::uncertainty::Propagation;
 
-/// Runs Hawk code from a file given by `filename`, returning the global scope after execution
+use crate::monte_carlo::MonteCarlo;
+use crate::native::NativeFunction;
+use crate::Interpreter;
+
+/// Runs Hawk code from a file given by `filename`, returning the global scope after execution, or every error
 pub fn run_script(
     filename: String,
     global_state: HashMap<String, Object>,
-    filein_fn: fn(String) -> Result<String, String>,
-    fileout_fn: fn(String, String) -> Result<(), ()>,
-    warn_fn: fn(String, usize) -> (),
-    err_fn: fn(String, usize) -> (),
-    output_fn: fn(String) -> (),
-) -> HashMap<String, Object> {
-    let source = filein_fn(filename.clone());
-
-    match source {
-        Result::Ok(source) => match run(
-            source,
-            global_state,
-            false,
-            filein_fn,
-            fileout_fn,
-            warn_fn,
-            err_fn,
-            output_fn,
-        ) {
-            Ok(globals) => globals,
-            Err((message, line)) => {
-                err_fn(message, line);
-                HashMap::new()
-            }
-        },
-        Result::Err(_) => {
-            err_fn(format!("Couldn't read file {filename}"), 0);
-            HashMap::new()
-        }
-    }
+    env: &mut dyn Environment,
+) -> Result<HashMap<String, Object>, Vec<HawkError>> {
+    Builder::new().run_script(filename, global_state, env)
 }
 
+/// Runs Hawk code, returning the global scope after execution. If the code doesn't parse, every syntax error is
+/// returned
 pub fn run(
     source: String,
     global_state: HashMap<String, Object>,
     in_repl: bool,
-    filein_fn: fn(String) -> Result<String, String>,
-    fileout_fn: fn(String, String) -> Result<(), ()>,
-    warn_fn: fn(String, usize) -> (),
-    err_fn: fn(String, usize) -> (),
-    output_fn: fn(String) -> (),
-) -> Result<HashMap<String, Object>, (String, usize)> {
-    let tokens = hawk_lib::lexer::Lexer::lex(&source);
-
-    //println!("{}", Tokens(tokens.clone()));
-
-    let statements = hawk_lib::parser::Parser::parse(&tokens, warn_fn)?;
-
-    //println!("{:?}\n", statements);
-
-    let result = crate::Interpreter::interpret(
-        statements,
-        global_state,
-        in_repl,
-        filein_fn,
-        fileout_fn,
-        warn_fn,
-        err_fn,
-        output_fn,
-    )?;
-
-    Ok(result)
+    env: &mut dyn Environment,
+) -> Result<HashMap<String, Object>, Vec<HawkError>> {
+    Builder::new().repl(in_repl).run(source, global_state, env)
+}
+
+/// Runs Hawk code with Rust functions it can call, for programs embedding Hawk:
+///
+/// ```ignore
+/// let globals = Builder::new()
+///     .function(NativeFunction::typed("read_voltage", |channel: i64| driver.read(channel)))
+///     .run_script(String::from("experiment.hawk"), HashMap::new(), &mut Terminal)?;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Builder {
+    natives: HashMap<String, NativeFunction>,
+    in_repl: bool,
+    propagation: Propagation,
+    monte_carlo: MonteCarlo,
+}
+
+impl Builder {
+    pub fn new() -> Self {
+        Builder::default()
+    }
+
+    pub(crate) fn with_natives(natives: HashMap<String, NativeFunction>) -> Self {
+        Builder { natives, ..Builder::default() }
+    }
+
+    /// Lets the code call a Rust function, see `Interpreter::register`
+    pub fn function(mut self, function: NativeFunction) -> Self {
+        self.natives.insert(function.name.clone(), function);
+        self
+    }
+
+    /// Runs code as lines of the REPL, which shows the values of expressions
+    pub fn repl(mut self, in_repl: bool) -> Self {
+        self.in_repl = in_repl;
+        self
+    }
+
+    /// Sets how uncertainties are combined, until the code changes it with `propagation()`
+    pub fn propagation(mut self, propagation: Propagation) -> Self {
+        self.propagation = propagation;
+        self
+    }
+
+    /// Sets the number of samples, seed and distribution used by `montecarlo`, until the code changes them with
+    /// `montecarlo_settings()`
+    pub fn monte_carlo(mut self, monte_carlo: MonteCarlo) -> Self {
+        self.monte_carlo = monte_carlo;
+        self
+    }
+
+    /// Creates an interpreter with the functions and settings of the builder, without running any code. Code can
+    /// be run in it several times with `Interpreter::run_source`, keeping variables and settings in between
+    pub fn interpreter<'a>(&self, global_state: HashMap<String, Object>, env: &'a mut dyn Environment) -> Interpreter<'a> {
+        let mut interpreter = Interpreter::new(global_state, None, self.in_repl, env);
+        for function in self.natives.values() {
+            interpreter.register(function.clone());
+        }
+        interpreter.set_propagation(self.propagation);
+        interpreter.set_monte_carlo(self.monte_carlo.clone());
+        interpreter
+    }
+
+    /// Runs Hawk code from a file given by `filename`, returning the global scope after execution, or every error
+    pub fn run_script(
+        &self, filename: String, global_state: HashMap<String, Object>, env: &mut dyn Environment,
+    ) -> Result<HashMap<String, Object>, Vec<HawkError>> {
+        let mut interpreter = self.interpreter(global_state, env);
+        interpreter.run_file(filename)?;
+        Ok(interpreter.globals)
+    }
+
+    /// Runs Hawk code, returning the global scope after execution
+    pub fn run(
+        &self, source: String, global_state: HashMap<String, Object>, env: &mut dyn Environment,
+    ) -> Result<HashMap<String, Object>, Vec<HawkError>> {
+        let mut interpreter = self.interpreter(global_state, env);
+        interpreter.run_source(&source)?;
+        Ok(interpreter.globals)
+    }
+}
+
+impl Interpreter<'_> {
+    /// Lexes, parses and runs Hawk code, keeping the variables it defines in `globals`. If the code doesn't parse,
+    /// every syntax error is returned
+    pub fn run_source(&mut self, source: &str) -> Result<(), Vec<HawkError>> {
+        let env = &mut *self.env;
+        // Errors keep the line of code they are on, so that it can be shown with them
+        let tokens = hawk_lib::lexer::Lexer::lex(source, &mut |message, line| env.warn(message, line))
+            .map_err(|errors| errors.into_iter().map(|error| error.with_source(source)).collect::<Vec<_>>())?;
+
+        //println!("{}", Tokens(tokens.clone()));
+
+        let statements = hawk_lib::parser::Parser::parse(&tokens, &mut |message, line| env.warn(message, line))
+            .map_err(|errors| errors.into_iter().map(|error| error.with_source(source)).collect::<Vec<_>>())?;
+
+        //println!("{:?}\n", statements);
+
+        self.run(statements).map_err(|error| {
+            // An error can stop the code inside a block or function, so leave any local scopes it was in
+            self.scopes.clear();
+            vec![error.with_source(source)]
+        })
+    }
+
+    /// Runs Hawk code from a file given by `filename`, which is kept so that tracebacks can show where functions
+    /// were called from
+    pub fn run_file(&mut self, filename: String) -> Result<(), Vec<HawkError>> {
+        match self.env.read_file(&filename) {
+            Ok(source) => {
+                self.file = Some(filename.clone());
+                self.run_source(&source)
+                    .map_err(|errors| errors.into_iter().map(|error| error.in_file(filename.clone())).collect())
+            },
+            Err(_) => Err(vec![HawkError::io(format!("Couldn't read file {filename}"), 0)]),
+        }
+    }
+}
+
+#[test]
+fn memory_environment() {
+    use hawk_common::environment::Memory;
+
+    let mut env = Memory::new()
+        .with_file("main.hawk", "import \"lib.hawk\"\nprint double(2)\nexpect 1 == 2\nwritefile(\"out.txt\", \"done\")")
+        .with_file("lib.hawk", "function double(x) return x * 2");
+
+    let globals = run_script(String::from("main.hawk"), HashMap::new(), &mut env).unwrap();
+
+    assert!(globals.contains_key("double"));
+    assert_eq!(env.output, vec![String::from("4")]);
+    assert_eq!(env.warnings.len(), 1);
+    assert_eq!(env.warnings[0].1, 3);
+    assert_eq!(env.files.get("out.txt"), Some(&String::from("done")));
+
+    let errors = run_script(String::from("missing.hawk"), HashMap::new(), &mut env).unwrap_err();
+    assert_eq!(errors[0].kind, hawk_common::error::ErrorKind::Io);
+}
+
+#[test]
+fn propagation_setting() {
+    use hawk_common::environment::Memory;
+    use rust_decimal_macros::dec;
+
+    let mut env = Memory::new().with_file("lib.hawk", "propagation(\"worst_case\")");
+    let mut interpreter = Builder::new().propagation(Propagation::Quadrature).interpreter(HashMap::new(), &mut env);
+
+    interpreter.run_source("let a = (1 ± 0.3) + (1 ± 0.4)").unwrap();
+    assert!(matches!(interpreter.globals.get("a"), Some(Object::Uncertain { uncertainty, .. }) if *uncertainty == dec!(0.5)));
+
+    // The mode is kept between runs, and imported files can change it, but it isn't a variable
+    interpreter.run_source("import \"lib.hawk\"").unwrap();
+    assert_eq!(interpreter.propagation, Propagation::WorstCase);
+    interpreter.run_source("propagation(\"minmax\")").unwrap();
+    assert_eq!(interpreter.propagation, Propagation::MinMax);
+    assert_eq!(interpreter.globals.len(), 1);
 }
@@ -0,0 +1,116 @@
+use hawk_common::object::Object;
+use hawk_common::uncertainty::ErrorSources;
+use hawk_common::units::Unit;
+
+use rust_decimal::Decimal;
+use std::str::FromStr;
+
+/// A physical constant with its CODATA 2018 value in SI units. Values that are too small to be stored as a `Decimal`
+/// (e.g. Planck's constant in J s) are given with a prefix on the unit instead, like `yJ*s` or `yg`
+pub struct Constant {
+    pub name: &'static str,
+    pub value: &'static str,
+    /// Standard uncertainty, or `None` for exact values
+    pub uncertainty: Option<&'static str>,
+    pub unit: &'static str,
+    pub description: &'static str,
+}
+
+pub const CONSTANTS: [Constant; 20] = [
+    Constant {name: "c", value: "299792458", uncertainty: None, unit: "m/s", description: "speed of light in vacuum"},
+    Constant {name: "h", value: "6.62607015e-10", uncertainty: None, unit: "yJ*s", description: "Planck constant"},
+    Constant {name: "hbar", value: "1.054571817e-10", uncertainty: None, unit: "yJ*s", description: "reduced Planck constant"},
+    Constant {name: "k_B", value: "13.80649", uncertainty: None, unit: "yJ/K", description: "Boltzmann constant"},
+    Constant {name: "e_charge", value: "1.602176634e-19", uncertainty: None, unit: "C", description: "elementary charge"},
+    Constant {name: "G", value: "6.67430e-11", uncertainty: Some("1.5e-15"), unit: "m^3/kg/s^2", description: "Newtonian constant of gravitation"},
+    Constant {name: "g", value: "9.80665", uncertainty: None, unit: "m/s^2", description: "standard acceleration of gravity"},
+    Constant {name: "N_A", value: "6.02214076e23", uncertainty: None, unit: "1/mol", description: "Avogadro constant"},
+    Constant {name: "R", value: "8.314462618", uncertainty: None, unit: "J/mol/K", description: "molar gas constant"},
+    Constant {name: "F", value: "96485.33212", uncertainty: None, unit: "C/mol", description: "Faraday constant"},
+    Constant {name: "sigma", value: "5.670374419e-8", uncertainty: None, unit: "W/m^2/K^4", description: "Stefan-Boltzmann constant"},
+    Constant {name: "epsilon_0", value: "8.8541878128e-12", uncertainty: Some("1.3e-21"), unit: "F/m", description: "vacuum electric permittivity"},
+    Constant {name: "mu_0", value: "1.25663706212e-6", uncertainty: Some("1.9e-16"), unit: "N/A^2", description: "vacuum magnetic permeability"},
+    Constant {name: "alpha", value: "7.2973525693e-3", uncertainty: Some("1.1e-12"), unit: "", description: "fine-structure constant"},
+    Constant {name: "R_inf", value: "10973731.568160", uncertainty: Some("0.000021"), unit: "1/m", description: "Rydberg constant"},
+    Constant {name: "a_0", value: "5.29177210903e-11", uncertainty: Some("8.0e-21"), unit: "m", description: "Bohr radius"},
+    Constant {name: "m_e", value: "9.1093837015e-4", uncertainty: Some("2.8e-13"), unit: "yg", description: "electron mass"},
+    Constant {name: "m_p", value: "1.67262192369", uncertainty: Some("5.1e-10"), unit: "yg", description: "proton mass"},
+    Constant {name: "m_n", value: "1.67492749804", uncertainty: Some("9.5e-10"), unit: "yg", description: "neutron mass"},
+    Constant {name: "m_u", value: "1.66053906660", uncertainty: Some("5.0e-10"), unit: "yg", description: "atomic mass constant"},
+];
+
+/// Gets the value of a physical constant as a quantity with its units, with an `Uncertain` value if it isn't exact.
+/// Every use of a constant shares the same error source, so e.g. `constant("G") - constant("G")` is exactly 0
+pub fn get_constant(name: &str) -> Option<Object> {
+    let (index, constant) = CONSTANTS.iter().enumerate().find(|(_, constant)| constant.name == name)?;
+    let value = parse(constant.value)?;
+
+    let value = match constant.uncertainty {
+        Some(uncertainty) => {
+            let uncertainty = parse(uncertainty)?;
+            Object::Uncertain {value, uncertainty, sources: ErrorSources::constant(index, uncertainty)}
+        },
+        None => Object::Decimal(value)
+    };
+
+    if constant.unit.is_empty() {
+        Some(value)
+    } else {
+        Some(Object::Quantity {value: Box::new(value), unit: Unit::parse(constant.unit)?})
+    }
+}
+
+/// Parses a value written either as a plain number or in scientific notation
+fn parse(value: &str) -> Option<Decimal> {
+    if value.contains('e') {
+        Decimal::from_scientific(value).ok()
+    } else {
+        Decimal::from_str(value).ok()
+    }
+}
+
+/// Lists all constants as a datatable, so that they can be looked up from the REPL
+pub fn list_constants() -> Object {
+    let column = |get: fn(&Constant) -> Object| Object::Column(CONSTANTS.iter().map(get).collect());
+
+    Object::DataTable {
+        names: vec!["name", "value", "uncertainty", "unit", "description"].into_iter().map(String::from).collect(),
+        data: vec![
+            column(|constant| Object::String(constant.name.to_owned())),
+            column(|constant| Object::String(constant.value.to_owned())),
+            column(|constant| Object::String(constant.uncertainty.unwrap_or("exact").to_owned())),
+            column(|constant| Object::String(constant.unit.to_owned())),
+            column(|constant| Object::String(constant.description.to_owned())),
+        ],
+    }
+}
+
+#[test]
+fn constants() {
+    use rust_decimal_macros::dec;
+
+    let quantity = |value: Object, unit: &str| Some(Object::Quantity {value: Box::new(value), unit: Unit::parse(unit).unwrap()});
+
+    // Every value needs to fit in a `Decimal`, and every unit needs to be known
+    for constant in CONSTANTS.iter() {
+        assert!(get_constant(constant.name).is_some(), "{} can't be represented", constant.name);
+    }
+
+    assert_eq!(get_constant("c"), quantity(Object::Decimal(dec!(299792458)), "m/s"));
+    assert_eq!(get_constant("e_charge"), quantity(Object::Decimal(dec!(0.0000000000000000001602176634)), "C"));
+    assert_eq!(get_constant("h"), quantity(Object::Decimal(dec!(0.000000000662607015)), "yJ*s"));
+    assert_eq!(get_constant("k_B"), quantity(Object::Decimal(dec!(13.80649)), "yJ/K"));
+    match get_constant("G") {
+        Some(Object::Quantity {value, unit}) => match *value {
+            Object::Uncertain {value, uncertainty, ..} => {
+                assert_eq!(value, dec!(0.0000000000667430));
+                assert_eq!(uncertainty, dec!(0.0000000000000015));
+                assert_eq!(unit.to_string(), "m^3/kg/s^2");
+            },
+            x => panic!("Expected G to be uncertain, found {x:?}"),
+        },
+        x => panic!("Expected G to have units, found {x:?}"),
+    }
+    assert!(matches!(get_constant("alpha"), Some(Object::Uncertain {..})));
+    assert_eq!(get_constant("speed_of_light"), None);
+}
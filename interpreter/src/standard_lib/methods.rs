@@ -0,0 +1,288 @@
+use std::cmp::Ordering;
+
+use crate::Interpreter;
+use hawk_common::error::HawkError;
+use hawk_common::object::Object;
+
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+impl Interpreter<'_> {
+    /// Calls a method built into arrays, columns, strings or datatables. Returns the result, and the new value of the
+    /// object if the method changed it (e.g. `push`)
+    pub fn call_builtin_method(
+        &mut self, object: Object, method: &str, args: Vec<Object>,
+    ) -> Result<(Object, Option<Object>), HawkError> {
+        match (object, method) {
+            (Object::Array(items) | Object::Column(items), "len") => Ok((Object::Int(items.len() as i128), None)),
+            (Object::Array(mut items), "push") => {
+                items.extend(args);
+                Ok((Object::Null, Some(Object::Array(items))))
+            },
+            (Object::Column(mut items), "push") => {
+                items.extend(args);
+                Ok((Object::Null, Some(Object::Column(items))))
+            },
+            (Object::Array(mut items), "pop") => {
+                let last = items.pop().unwrap_or(Object::Null);
+                Ok((last, Some(Object::Array(items))))
+            },
+            (Object::Array(items), "map") => Ok((Object::Array(self.map(items, Self::arg(&args, 0, method, self.line)?)?), None)),
+            (Object::Column(items), "map") => Ok((Object::Column(self.map(items, Self::arg(&args, 0, method, self.line)?)?), None)),
+            (Object::Array(items), "filter") => Ok((Object::Array(self.filter(items, Self::arg(&args, 0, method, self.line)?)?), None)),
+            (Object::Column(items), "filter") => Ok((Object::Column(self.filter(items, Self::arg(&args, 0, method, self.line)?)?), None)),
+            (Object::Array(items) | Object::Column(items), "reduce") => {
+                let function = Self::arg(&args, 0, method, self.line)?;
+                let mut items = items.into_iter();
+                let mut accumulator = match args.get(1) {
+                    Some(initial) => initial.clone(),
+                    None => items.next().ok_or(HawkError::runtime(format!("Can't reduce no values without an initial value"), self.line))?,
+                };
+                for item in items {
+                    accumulator = self.call(&function, vec![accumulator, item])?
+                }
+                Ok((accumulator, None))
+            },
+            (Object::Array(items), "sort_by") => Ok((Object::Array(self.sort_by_key(items, Self::arg(&args, 0, method, self.line)?)?), None)),
+            (Object::Column(items), "sort_by") => Ok((Object::Column(self.sort_by_key(items, Self::arg(&args, 0, method, self.line)?)?), None)),
+            (Object::Array(items) | Object::Column(items), "sum") => Ok((self.sum(items)?, None)),
+            (Object::Array(items) | Object::Column(items), "mean") => Ok((self.mean(items)?, None)),
+            (Object::Array(items) | Object::Column(items), "std") => Ok((self.std(items)?, None)),
+            (Object::Array(items) | Object::Column(items), "min") => Ok((self.extreme(items, Ordering::Less)?, None)),
+            (Object::Array(items) | Object::Column(items), "max") => Ok((self.extreme(items, Ordering::Greater)?, None)),
+
+            (Object::String(string), "len") => Ok((Object::Int(string.chars().count() as i128), None)),
+            (Object::String(string), "split") => {
+                let parts: Vec<Object> = match args.get(0) {
+                    Some(Object::String(separator)) => string.split(separator.as_str()).map(|part| Object::String(part.to_owned())).collect(),
+                    None => string.split_whitespace().map(|part| Object::String(part.to_owned())).collect(),
+                    Some(x) => return Err(HawkError::runtime(format!("Expected string to split by, found {}", x.user_print(self.line)?), self.line)),
+                };
+                Ok((Object::Array(parts), None))
+            },
+            (Object::String(string), "trim") => Ok((Object::String(string.trim().to_owned()), None)),
+            (Object::String(string), "upper") => Ok((Object::String(string.to_uppercase()), None)),
+            (Object::String(string), "lower") => Ok((Object::String(string.to_lowercase()), None)),
+            (Object::String(string), "contains") => match args.get(0) {
+                Some(Object::String(part)) => Ok((Object::Boolean(string.contains(part.as_str())), None)),
+                _ => Err(HawkError::runtime(format!("Expected string as argument to contains"), self.line)),
+            },
+
+            (table @ Object::DataTable { .. }, "len") => Ok((Object::Int(self.items(table)?.len() as i128), None)),
+            (Object::DataTable { names, .. }, "columns") => Ok((Object::Array(names.into_iter().map(Object::String).collect()), None)),
+            (Object::DataTable { names, data }, "column") => {
+                let index = self.column_index(&names, &args)?;
+                Ok((data[index].clone(), None))
+            },
+            (table @ Object::DataTable { .. }, "filter") => {
+                let function = Self::arg(&args, 0, method, self.line)?;
+                let rows = self.items(table.clone())?;
+                let mut keep: Vec<usize> = Vec::new();
+                for (index, row) in rows.into_iter().enumerate() {
+                    match self.call(&function, vec![row])? {
+                        Object::Boolean(true) => keep.push(index),
+                        Object::Boolean(false) => (),
+                        x => return Err(HawkError::runtime(format!("Expected filter function to return a boolean, found {}", x.user_print(self.line)?), self.line)),
+                    }
+                }
+                Ok((Self::select_rows(table, &keep), None))
+            },
+            (Object::DataTable { names, data }, "sort_by") => {
+                let index = self.column_index(&names, &args)?;
+                let keys = if let Object::Column(keys) = &data[index] {keys.clone()} else {Vec::new()};
+
+                let mut order: Vec<usize> = (0..keys.len()).collect();
+                let mut error = None;
+                order.sort_by(|a, b| Self::compare(&keys[*a], &keys[*b], self.line).unwrap_or_else(|e| {
+                    error.get_or_insert(e);
+                    Ordering::Equal
+                }));
+                if let Some(error) = error {
+                    return Err(error)
+                }
+
+                Ok((Self::select_rows(Object::DataTable { names, data }, &order), None))
+            },
+
+            (x, method) => Err(HawkError::runtime(format!("{} has no method {method}", x.user_print(self.line)?), self.line)),
+        }
+    }
+
+    fn arg(args: &[Object], index: usize, method: &str, line: usize) -> Result<Object, HawkError> {
+        args.get(index).cloned().ok_or(HawkError::runtime(format!("Expected more arguments to {method}"), line))
+    }
+
+    fn map(&mut self, items: Vec<Object>, function: Object) -> Result<Vec<Object>, HawkError> {
+        let mut results: Vec<Object> = Vec::new();
+        for item in items {
+            results.push(self.call(&function, vec![item])?)
+        }
+        Ok(results)
+    }
+
+    fn filter(&mut self, items: Vec<Object>, function: Object) -> Result<Vec<Object>, HawkError> {
+        let mut results: Vec<Object> = Vec::new();
+        for item in items {
+            match self.call(&function, vec![item.clone()])? {
+                Object::Boolean(true) => results.push(item),
+                Object::Boolean(false) => (),
+                x => return Err(HawkError::runtime(format!("Expected filter function to return a boolean, found {}", x.user_print(self.line)?), self.line)),
+            }
+        }
+        Ok(results)
+    }
+
+    /// Sorts items by the result of calling a function on each of them
+    fn sort_by_key(&mut self, items: Vec<Object>, function: Object) -> Result<Vec<Object>, HawkError> {
+        let mut keyed: Vec<(Object, Object)> = Vec::new();
+        for item in items {
+            keyed.push((self.call(&function, vec![item.clone()])?, item))
+        }
+
+        let mut error = None;
+        keyed.sort_by(|(a, _), (b, _)| Self::compare(a, b, self.line).unwrap_or_else(|e| {
+            error.get_or_insert(e);
+            Ordering::Equal
+        }));
+        match error {
+            Some(error) => Err(error),
+            None => Ok(keyed.into_iter().map(|(_, item)| item).collect()),
+        }
+    }
+
+    /// Adds up the items, propagating uncertainties and checking units
+    fn sum(&self, items: Vec<Object>) -> Result<Object, HawkError> {
+        let mut items = items.into_iter();
+        let mut total = items.next().unwrap_or(Object::Int(0));
+        for item in items {
+            total = Self::add(total, item, self.propagation, self.line)?
+        }
+        Ok(total)
+    }
+
+    fn mean(&self, items: Vec<Object>) -> Result<Object, HawkError> {
+        if items.is_empty() {
+            return Err(HawkError::runtime(format!("Can't find the mean of no values"), self.line))
+        }
+        let n = Object::Decimal(Decimal::from(items.len()));
+        Self::divide(self.sum(items)?, n, self.propagation, self.line)
+    }
+
+    /// Sample standard deviation of the values, ignoring their uncertainties
+    fn std(&self, items: Vec<Object>) -> Result<Object, HawkError> {
+        if items.len() < 2 {
+            return Err(HawkError::runtime(format!("Can't find the standard deviation of fewer than 2 values"), self.line))
+        }
+
+        let values: Vec<Object> = items.iter().map(Self::without_uncertainty).collect();
+        let n = Decimal::from(values.len());
+        let mean = self.mean(values.clone())?;
+
+        let mut squares: Vec<Object> = Vec::new();
+        for value in values {
+            let deviation = Self::subtract(value, mean.clone(), self.propagation, self.line)?;
+            squares.push(Self::exponent(deviation, Object::Int(2), self.propagation, self.line)?)
+        }
+        let variance = Self::divide(self.sum(squares)?, Object::Decimal(n - dec!(1)), self.propagation, self.line)?;
+        Self::exponent(variance, Object::Decimal(dec!(0.5)), self.propagation, self.line)
+    }
+
+    fn without_uncertainty(obj: &Object) -> Object {
+        match obj {
+            Object::Uncertain { value, .. } => Object::Decimal(*value),
+            Object::Quantity { value, unit } => Object::Quantity { value: Box::new(Self::without_uncertainty(value)), unit: unit.clone() },
+            x => x.clone(),
+        }
+    }
+
+    /// Finds the smallest (`Ordering::Less`) or largest (`Ordering::Greater`) item
+    fn extreme(&self, items: Vec<Object>, ordering: Ordering) -> Result<Object, HawkError> {
+        let mut items = items.into_iter();
+        let mut extreme = items.next().ok_or(HawkError::runtime(format!("Can't find the extreme of no values"), self.line))?;
+        for item in items {
+            if Self::compare(&item, &extreme, self.line)? == ordering {
+                extreme = item
+            }
+        }
+        Ok(extreme)
+    }
+
+    /// Orders two numbers (by their values, in SI units if they have units) or two strings
+    fn compare(a: &Object, b: &Object, line: usize) -> Result<Ordering, HawkError> {
+        if let (Object::String(a), Object::String(b)) = (a, b) {
+            return Ok(a.cmp(b))
+        }
+        match (Self::sort_key(a), Self::sort_key(b)) {
+            (Some(a), Some(b)) => Ok(a.cmp(&b)),
+            _ => Err(HawkError::runtime(format!("Can't compare {} and {}", a.user_print(line)?, b.user_print(line)?), line)),
+        }
+    }
+
+    fn sort_key(obj: &Object) -> Option<Decimal> {
+        match obj {
+            Object::Quantity { value, unit } => Some(Self::sort_key(value)? * unit.factor()),
+            _ => Self::uncertain_parts(obj).map(|(value, _)| value),
+        }
+    }
+
+    fn column_index(&self, names: &[String], args: &[Object]) -> Result<usize, HawkError> {
+        match args.get(0) {
+            Some(Object::String(name)) => names.iter().position(|column| column == name)
+                .ok_or(HawkError::runtime(format!("The datatable has no column {name}"), self.line)),
+            _ => Err(HawkError::runtime(format!("Expected the name of a column"), self.line)),
+        }
+    }
+
+    /// Makes a datatable from the given rows of another, in the given order
+    fn select_rows(table: Object, rows: &[usize]) -> Object {
+        if let Object::DataTable { names, data } = table {
+            let data = data.into_iter().map(|column| match column {
+                Object::Column(items) => Object::Column(rows.iter().filter_map(|row| items.get(*row).cloned()).collect()),
+                x => x,
+            }).collect();
+            Object::DataTable { names, data }
+        } else {
+            table
+        }
+    }
+}
+
+#[test]
+fn builtin_methods() {
+    use hawk_common::environment::Memory;
+    use std::collections::HashMap;
+
+    let source = String::from("function double(x) return 2 * x
+function fast(row) return row[1] > 3
+let arr = [3, 1, 2]
+arr.push(4)
+let doubled = arr.map(double)
+let words = \"a, b\"
+let parts = words.split(\", \")
+let table = read(\"data.csv\")
+let speeds = table.column(\"v\")
+let mean = speeds.mean()
+let sorted = table.sort_by(\"v\")
+let first = sorted.column(\"t\")
+let filtered = table.filter(fast)
+let count = filtered.len()
+let literal_parts = \"a,b\".split(\",\")
+let literal_doubled = [1, 2].map(double)
+let fastest = table.column(\"v\").max()
+let rows = read(\"data.csv\").len()");
+    let globals = crate::run::run(
+        source, HashMap::new(), false, &mut Memory::new().with_file("data.csv", "t,v\n1,5\n2,2\n3,8"),
+    ).unwrap();
+
+    assert_eq!(globals.get("doubled"), Some(&Object::Array(vec![Object::Int(6), Object::Int(2), Object::Int(4), Object::Int(8)])));
+    assert_eq!(globals.get("parts"), Some(&Object::Array(vec![Object::String(String::from("a")), Object::String(String::from("b"))])));
+    assert_eq!(globals.get("mean"), Some(&Object::Int(5)));
+    assert_eq!(globals.get("first"), Some(&Object::Column(vec![Object::Int(2), Object::Int(1), Object::Int(3)])));
+    assert_eq!(globals.get("count"), Some(&Object::Int(2)));
+
+    // Methods can be called on literals and on the results of calls
+    assert_eq!(globals.get("literal_parts"), Some(&Object::Array(vec![Object::String(String::from("a")), Object::String(String::from("b"))])));
+    assert_eq!(globals.get("literal_doubled"), Some(&Object::Array(vec![Object::Int(2), Object::Int(4)])));
+    assert_eq!(globals.get("fastest"), Some(&Object::Int(8)));
+    assert_eq!(globals.get("rows"), Some(&Object::Int(3)));
+}
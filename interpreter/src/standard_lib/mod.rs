@@ -1,13 +1,21 @@
 use crate::Interpreter;
+use hawk_common::error::HawkError;
 use hawk_common::object::Object;
+use hawk_common::tree::Expression;
 
 pub mod standard_lib_hawk;
 mod standard_lib_rs;
+mod constants;
+mod methods;
+pub mod symbolic;
 
 use standard_lib_hawk::get_std_hawk_fn;
 
-impl Interpreter {
-    pub fn run_fn_std(&mut self, identifier: String, args: Vec<Object>) -> Result<Option<Object>, (String, usize)> {
+/// A standard library function that is given its arguments as expressions, along with the name it was called by
+type SpecialFunction = fn(&mut Interpreter, &str, Vec<Box<Expression>>) -> Result<Object, HawkError>;
+
+impl Interpreter<'_> {
+    pub fn run_fn_std(&mut self, identifier: String, args: Vec<Object>) -> Result<Option<Object>, HawkError> {
         if let Some(x) = self.get_std_rs_fn(identifier.clone(), args)? {
             Ok(Some(x))
         } else if let Some(x) = get_std_hawk_fn(identifier) {
@@ -16,4 +24,58 @@ impl Interpreter {
             Ok(None)
         }
     }
+
+    /// Standard library functions that can't be called with their evaluated arguments like other functions: `sym`
+    /// and the functions taking other functions by name need the expressions they are given, and higher order
+    /// functions take whole arrays and columns instead of being called once per row
+    pub fn get_std_special_fn(identifier: &str) -> Option<SpecialFunction> {
+        match identifier {
+            "montecarlo" | "montecarlo_samples" => Some(|interpreter, identifier, args| {
+                interpreter.eval_monte_carlo_call(String::from(identifier), args)
+            }),
+            "propagate" => Some(|interpreter, _, args| interpreter.eval_propagate_call(args)),
+            "sym" => Some(|interpreter, _, args| match args.as_slice() {
+                [arg] => symbolic::quote(arg, interpreter.line),
+                _ => Err(HawkError::runtime(format!("Expected one expression to make symbolic, found {}", args.len()), interpreter.line)),
+            }),
+            // These are the same as the methods, e.g. `map(arr, f)` is `arr.map(f)`
+            "map" | "filter" | "reduce" | "sort_by" => Some(|interpreter, identifier, args| {
+                let mut evaled_args: Vec<Object> = Vec::new();
+                for arg in args {
+                    evaled_args.push(interpreter.eval_expression(arg)?)
+                }
+                if evaled_args.is_empty() {
+                    return Err(HawkError::runtime(format!("Expected an array or column to {identifier}"), interpreter.line))
+                }
+                let object = evaled_args.remove(0);
+                let (result, _) = interpreter.call_builtin_method(object, identifier, evaled_args)?;
+                Ok(result)
+            }),
+            _ => None,
+        }
+    }
+}
+
+#[test]
+fn special_functions() {
+    use std::collections::HashMap;
+
+    use hawk_common::environment::Memory;
+
+    let source = String::from("let expr = sym(x^2)
+let doubled = map([1, 2], x => 2 * x)
+function propagate(f, x) {
+    return x
+}
+let shadowed = propagate(sin, 2 ± 0.1)");
+    let globals = crate::run::run(source, HashMap::new(), false, &mut Memory::new()).unwrap();
+
+    assert!(matches!(globals.get("expr"), Some(Object::Symbolic(_))));
+    assert_eq!(globals.get("doubled"), Some(&Object::Array(vec![Object::Int(2), Object::Int(4)])));
+
+    // Functions with the same name as a special function are called like any other function
+    assert_eq!(globals.get("shadowed").map(|x| x.user_print(0).unwrap()), Some(String::from("2 ± 0.1")));
+
+    let errors = crate::run::run(String::from("map()"), HashMap::new(), false, &mut Memory::new()).unwrap_err();
+    assert_eq!(errors[0].message, "Expected an array or column to map");
 }
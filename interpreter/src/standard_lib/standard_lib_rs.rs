@@ -1,21 +1,28 @@
 use crate::csv::{csv_to_datatable, datatable_to_csv};
+use super::constants::{get_constant, list_constants};
+use super::symbolic;
 
 use crate::Interpreter;
+use hawk_common::error::HawkError;
 use hawk_common::object::Object;
+use hawk_common::uncertainty::Propagation;
+use hawk_common::units::Unit;
+
+use crate::monte_carlo::{Distribution, MonteCarlo};
 
 use rust_decimal::{Decimal, MathematicalOps};
 use rust_decimal_macros::dec;
 
-impl Interpreter {
-    pub fn get_std_rs_fn(&mut self, identifier: String, args: Vec<Object>) -> Result<Option<Object>, (String, usize)> {
+impl Interpreter<'_> {
+    pub fn get_std_rs_fn(&mut self, identifier: String, args: Vec<Object>) -> Result<Option<Object>, HawkError> {
         match identifier.as_str() {
             "readfile" => {
                 if let Object::String(file) = args[0].clone() {
-                    if let Ok(str) = (self.filein_fn)(file.clone()) {
+                    if let Ok(str) = self.env.read_file(&file) {
                         Ok(Some(Object::String(str)))
                     } else {
-                        Err((
-                            format!("Expected string as filename, found {}", file),
+                        Err(HawkError::io(
+                            format!("Couldn't read file {}", file),
                             self.line,
                         ))
                     }
@@ -28,12 +35,12 @@ impl Interpreter {
                 let file = args[0].clone();
                 if let Object::String(str) = val {
                     if let Object::String(filename) = file {
-                        if let Ok(()) = (self.fileout_fn)(filename.clone(), str) {
+                        if let Ok(()) = self.env.write_file(&filename, &str) {
                         } else {
-                            return Err((format!("Couldn't write file: {}", filename), self.line))
+                            return Err(HawkError::io(format!("Couldn't write file: {}", filename), self.line))
                         }
                     } else {
-                        return Err((format!("Expected string as filename, found {}", file), self.line))
+                        return Err(HawkError::runtime(format!("Expected string as filename, found {}", file), self.line))
                     }
                 }
                 Ok(Some(Object::Null))
@@ -41,9 +48,9 @@ impl Interpreter {
             "read" => {
                 let filename = args[0].clone();
                 if let Object::String(filename) = filename {
-                    Ok(Some(csv_to_datatable(filename, self.line, self.filein_fn)?))
+                    Ok(Some(csv_to_datatable(filename, self.line, self.env)?))
                 } else {
-                    Err((
+                    Err(HawkError::runtime(
                         format!("Expected string as filename, found {}", filename),
                         self.line,
                     ))
@@ -54,14 +61,43 @@ impl Interpreter {
                 let file = args[0].clone();
                 if let Object::DataTable { names: _, data: _ } = val {
                     if let Object::String(filename) = file {
-                        datatable_to_csv(filename, val, self.line, self.fileout_fn)?;
+                        datatable_to_csv(filename, val, self.line, self.env)?;
                     } else {
-                        return Err((format!("Expected string as filename, found {}", file), self.line))
+                        return Err(HawkError::runtime(format!("Expected string as filename, found {}", file), self.line))
                     }
                 }
                 Ok(Some(Object::Null))
             }
+            "error" | "throw" => Err(self.throw(args)),
             "pi" => Ok(Some(Object::Decimal(Decimal::PI))),
+            "constant" => {
+                if let Some(Object::String(name)) = args.get(0) {
+                    if let Some(constant) = get_constant(name) {
+                        Ok(Some(constant))
+                    } else {
+                        Err(HawkError::runtime(format!("Unknown constant {}, use constants() to list them", name), self.line))
+                    }
+                } else {
+                    Err(HawkError::runtime(format!("Expected name of constant as a string"), self.line))
+                }
+            },
+            "constants" => Ok(Some(list_constants())),
+            "simplify" => match args.get(0) {
+                Some(arg) => Ok(Some(symbolic::simplify(arg, self.line)?)),
+                None => Err(HawkError::runtime(String::from("Expected an expression to simplify"), self.line)),
+            },
+            "substitute" => match (args.get(0), args.get(1)) {
+                (Some(expr), Some(values)) => Ok(Some(symbolic::substitute(expr, values, self.line)?)),
+                _ => Err(HawkError::runtime(String::from("Expected a symbolic expression and a record of values"), self.line)),
+            },
+            "evaluate" => match args.get(0) {
+                Some(expr) => Ok(Some(self.evaluate(expr, args.get(1))?)),
+                None => Err(HawkError::runtime(String::from("Expected a symbolic expression to evaluate"), self.line)),
+            },
+            "derive" => match args.get(0) {
+                Some(arg) => Ok(Some(symbolic::derive(arg, args.get(1), self.line)?)),
+                None => Err(HawkError::runtime(String::from("Expected an expression to differentiate"), self.line)),
+            },
             "ln10" => Ok(Some(Object::Decimal(dec!(2.30258509299)))),
             "ln" => {
                 let x;
@@ -76,6 +112,11 @@ impl Interpreter {
                 Ok(Some(Object::Decimal(ln(x))))
             }
             "e" => Ok(Some(Object::Decimal(Decimal::E))),
+            "exp" => match args.get(0) {
+                Some(Object::Int(x)) => Ok(Some(Object::Decimal(exp(Decimal::from(*x as i64), self.line)?))),
+                Some(Object::Decimal(x)) => Ok(Some(Object::Decimal(exp(*x, self.line)?))),
+                _ => Err(HawkError::runtime(String::from("Expected number as argument to exp"), self.line)),
+            },
             "sin" => {
                 let x;
                 if let Object::Decimal(val) = args[0] {
@@ -83,7 +124,7 @@ impl Interpreter {
                 } else if let Object::Int(val) = args[0] {
                     x = Decimal::from(val as i64)
                 } else {
-                    return Err((
+                    return Err(HawkError::runtime(
                         format!("Expected number as argument to sin, found {}", args[0]),
                         self.line,
                     ))
@@ -95,17 +136,43 @@ impl Interpreter {
                 if let Object::Array(data) = args[0].to_owned() {
                     Ok(Some(Object::Int(data.len() as i128)))
                 } else {
-                    Err((
+                    Err(HawkError::runtime(
                         format!("Expected array as argument to len, found {}", args[0]),
                         self.line,
                     ))
                 }
             },
+            "enumerate" => {
+                if let Some(Object::Array(data)) = args.get(0) {
+                    Ok(Some(Object::Array(Self::enumerate(data.clone()))))
+                } else {
+                    Err(HawkError::runtime(format!("Expected array as argument to enumerate"), self.line))
+                }
+            },
+            "to" => {
+                if let (Some(Object::Quantity{value, unit}), Some(Object::String(target))) = (args.get(0), args.get(1)) {
+                    if let Some(target) = Unit::parse(target) {
+                        let value = Self::convert(*value.clone(), unit, &target, self.line)?;
+                        Ok(Some(Object::Quantity{value: Box::new(value), unit: target}))
+                    } else {
+                        Err(HawkError::runtime(format!("Unknown unit {}", target), self.line))
+                    }
+                } else {
+                    Err(HawkError::runtime(format!("Expected a quantity with units and a string with the units to convert to"), self.line))
+                }
+            },
+            "unit" => {
+                if let Some(Object::Quantity{unit, ..}) = args.get(0) {
+                    Ok(Some(Object::String(unit.to_string())))
+                } else {
+                    Ok(Some(Object::Null))
+                }
+            },
             "str" | "string" => {
                 if args.len() == 1 {
                     Ok(Some(Object::String(args[0].user_print(self.line)?)))
                 } else {
-                    Err((
+                    Err(HawkError::runtime(
                         format!("Expected exactly 1 input to str, got {:?}", args),
                         self.line,
                     ))
@@ -157,7 +224,7 @@ impl Interpreter {
             },
             "isuncertain" | "is_uncertain" | "isUncertain" | "hasuncertainty" | "has_uncertainty" | "hasUncertainty" => {
                 if args.len() == 1 {
-                    if let Object::Uncertain{value: _, uncertainty: _} = args[0] {
+                    if let Object::Uncertain{..} = args[0] {
                         Ok(Some(Object::Boolean(true)))
                     } else {
                         Ok(Some(Object::Boolean(false)))
@@ -166,6 +233,51 @@ impl Interpreter {
                     Ok(Some(Object::Boolean(false)))
                 }
             },
+            "propagation" => {
+                match args.get(0) {
+                    None => Ok(Some(Object::String(String::from(self.propagation.name())))),
+                    Some(Object::String(name)) => {
+                        if let Some(propagation) = Propagation::from_name(name) {
+                            self.set_propagation(propagation);
+                            Ok(Some(Object::Null))
+                        } else {
+                            Err(HawkError::runtime(
+                                format!("Unknown propagation mode {}, expected worst_case, quadrature or minmax", name),
+                                self.line,
+                            ))
+                        }
+                    },
+                    Some(arg) => Err(HawkError::runtime(format!("Expected string as propagation mode, found {}", arg.user_print(self.line)?), self.line)),
+                }
+            },
+            "montecarlo_settings" => {
+                let mut settings = self.monte_carlo.clone();
+
+                if let Some(arg) = args.get(0) {
+                    match arg {
+                        Object::Int(samples) if *samples > 0 => settings.samples = *samples as usize,
+                        _ => return Err(HawkError::runtime(format!("Expected positive Int as number of samples, found {}", arg.user_print(self.line)?), self.line)),
+                    }
+                }
+                if let Some(arg) = args.get(1) {
+                    match arg {
+                        Object::Int(seed) if *seed >= 0 => settings.seed = *seed as u64,
+                        _ => return Err(HawkError::runtime(format!("Expected positive Int as seed, found {}", arg.user_print(self.line)?), self.line)),
+                    }
+                }
+                if let Some(arg) = args.get(2) {
+                    match arg {
+                        Object::String(name) => match Distribution::from_name(name) {
+                            Some(distribution) => settings.distribution = distribution,
+                            None => return Err(HawkError::runtime(format!("Unknown distribution {}, expected normal or uniform", name), self.line)),
+                        },
+                        _ => return Err(HawkError::runtime(format!("Expected string as distribution, found {}", arg.user_print(self.line)?), self.line)),
+                    }
+                }
+
+                self.set_monte_carlo(MonteCarlo::new(settings.samples, settings.seed, settings.distribution));
+                Ok(Some(Object::Null))
+            },
             "isarray" | "is_arr" | "isArr" | "isarr" | "is_array" | "isArray" => {
                 if args.len() == 1 {
                     if let Object::Array(_) = args[0] {
@@ -208,6 +320,10 @@ fn ln(x: Decimal) -> Decimal {
     x.ln()
 }
 
+fn exp(x: Decimal, line: usize) -> Result<Decimal, HawkError> {
+    x.checked_exp().ok_or(HawkError::runtime(format!("exp({x}) is too large"), line))
+}
+
 fn sin(x: Decimal) -> Decimal {
     x.sin()
 }
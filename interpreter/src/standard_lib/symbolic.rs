@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+
+use crate::Interpreter;
+use hawk_cas::expression::Expression;
+use hawk_cas::factor::Factor;
+use hawk_cas::term::Term;
+use hawk_cas::ToCASExpr;
+use hawk_common::error::HawkError;
+use hawk_common::object::Object;
+use hawk_common::token::TokenType;
+use hawk_common::tree::{self, Statement};
+
+/// Parses a string of Hawk code holding a single expression, e.g. `"x^2 + 2 * x"`
+pub fn parse_expression(source: &str, line: usize) -> Result<tree::Expression, HawkError> {
+    let tokens = hawk_lib::lexer::Lexer::lex(source, &mut |_, _| ())
+        .map_err(|errors| HawkError::runtime(format!("Couldn't parse expression {source}: {}", errors[0].message), line))?;
+    let statements = hawk_lib::parser::Parser::parse(&tokens, &mut |_, _| ())
+        .map_err(|errors| HawkError::runtime(format!("Couldn't parse expression {source}: {}", errors[0].message), line))?;
+
+    match statements.into_iter().find(|statement| matches!(statement, Statement::Expression(_))) {
+        Some(Statement::Expression(expr)) => Ok(*expr),
+        _ => Err(HawkError::runtime(format!("Expected an expression, found {source}"), line)),
+    }
+}
+
+/// Converts an expression to a symbolic one, where variables are symbols
+pub fn to_symbolic(expr: &tree::Expression, line: usize) -> Result<Expression, HawkError> {
+    expr.to_cas_expr().ok_or(HawkError::runtime(
+        String::from("Expression can't be used symbolically, only numbers, variables, arithmetic and function calls can"), line,
+    ))
+}
+
+/// A symbolic expression `Object`, stored as a Hawk expression so that it can be evaluated
+pub fn from_symbolic(expr: Expression) -> Object {
+    Object::Symbolic(Box::new(tree::Expression::from_cas_expr(expr)))
+}
+
+/// Evaluates `sym(expr)`, which quotes an expression rather than evaluating it, e.g. `sym(x^2 + 2 * x)`. The
+/// expression can also be given as a string
+pub fn quote(expr: &tree::Expression, line: usize) -> Result<Object, HawkError> {
+    let expr = match expr {
+        tree::Expression::Literal(Object::String(source)) => parse_expression(source, line)?,
+        expr => expr.clone(),
+    };
+    Ok(from_symbolic(to_symbolic(&expr, line)?.simplified()))
+}
+
+/// A number or symbolic expression used in arithmetic with a symbolic expression, or in `substitute`
+fn operand(object: &Object, line: usize) -> Result<Expression, HawkError> {
+    match object {
+        Object::Symbolic(expr) => to_symbolic(expr, line),
+        Object::Int(_) | Object::Decimal(_) => to_symbolic(&tree::Expression::Literal(object.clone()), line),
+        x => Err(HawkError::runtime(format!("Can't use {} in a symbolic expression, only numbers", x.type_name()), line)),
+    }
+}
+
+/// Arithmetic with a symbolic expression builds a larger symbolic expression, e.g. `sym(x) * 2 + 1`. Gives `None` if
+/// neither operand is symbolic
+pub fn operation(operand1: &Object, operand2: &Object, operator: TokenType, line: usize) -> Result<Option<Object>, HawkError> {
+    let symbolic = matches!(operand1, Object::Symbolic(_)) || matches!(operand2, Object::Symbolic(_));
+    let arithmetic = matches!(operator, TokenType::Plus | TokenType::Minus | TokenType::Asterisk | TokenType::Slash | TokenType::Caret);
+    if !symbolic || !arithmetic {
+        return Ok(None)
+    }
+
+    let (a, b) = (operand(operand1, line)?, operand(operand2, line)?);
+    let result = match operator {
+        TokenType::Plus => a.sum(b),
+        TokenType::Minus => a.sum(Expression::from_term(Term::negate(b))),
+        TokenType::Asterisk => a.product(b),
+        TokenType::Slash => Expression::from_term(Term::div(Factor::from_expression(a), Factor::from_expression(b))),
+        _ => Expression::from_factor(Factor::exponent(a, b)),
+    };
+    Ok(Some(from_symbolic(result.simplified())))
+}
+
+/// `-expr` for a symbolic expression
+pub fn negate(expr: &tree::Expression, line: usize) -> Result<Object, HawkError> {
+    Ok(from_symbolic(Expression::from_term(Term::negate(to_symbolic(expr, line)?)).simplified()))
+}
+
+/// Replaces variables of a symbolic expression with the values of the fields of a record, e.g.
+/// `substitute(expr, {x: 3})`. Values can be numbers or other symbolic expressions
+pub fn substitute(expr: &Object, values: &Object, line: usize) -> Result<Object, HawkError> {
+    let (expr, fields) = match (expr, values) {
+        (Object::Symbolic(expr), Object::Instance { fields, .. }) => (to_symbolic(expr, line)?, fields),
+        _ => return Err(HawkError::runtime(
+            String::from("Expected a symbolic expression and a record of values, e.g. substitute(expr, {x: 3})"), line,
+        )),
+    };
+
+    let mut result = expr;
+    for (name, value) in fields {
+        result = result.substitute(name, &operand(value, line)?);
+    }
+    Ok(from_symbolic(result.simplified()))
+}
+
+/// The names of the variables in a symbolic expression
+pub fn symbols(expr: &tree::Expression, line: usize) -> Result<Vec<String>, HawkError> {
+    let mut symbols = Vec::new();
+    to_symbolic(expr, line)?.symbols(&mut symbols);
+    Ok(symbols)
+}
+
+impl Interpreter<'_> {
+    /// Evaluates a symbolic expression with the current values of its variables, or with the values given in a
+    /// record, e.g. `evaluate(expr, {x: 3 ± 0.1})`
+    pub fn evaluate(&mut self, expr: &Object, values: Option<&Object>) -> Result<Object, HawkError> {
+        let expr = match expr {
+            Object::Symbolic(expr) => expr,
+            x => return Err(HawkError::runtime(format!("Expected a symbolic expression to evaluate, found {}", x.user_print(self.line)?), self.line)),
+        };
+        let scope = match values {
+            Some(Object::Instance { fields, .. }) => fields.clone(),
+            Some(x) => return Err(HawkError::runtime(format!("Expected a record of values, found {}", x.user_print(self.line)?), self.line)),
+            None => HashMap::new(),
+        };
+
+        for symbol in symbols(expr, self.line)? {
+            if !scope.contains_key(&symbol) && matches!(self.get_variable(symbol.clone()), Object::Null) {
+                return Err(HawkError::runtime(format!("Can't evaluate {}, as {symbol} has no value", expr.source()), self.line))
+            }
+        }
+
+        self.scopes.push(scope);
+        let result = self.eval_expression(expr.clone());
+        self.scopes.pop();
+        result
+    }
+}
+
+/// Applies `operation` to an expression given as a string, giving the result as a string, to a function, giving a
+/// function that returns the result, or to a symbolic expression. `action` describes the operation for errors, e.g.
+/// "simplified"
+fn transform(
+    arg: &Object, action: &str, line: usize, operation: impl Fn(Expression) -> Result<Expression, HawkError>,
+) -> Result<Object, HawkError> {
+    match arg {
+        Object::String(source) => {
+            let result = operation(to_symbolic(&parse_expression(source, line)?, line)?)?;
+            Ok(Object::String(result.to_string()))
+        },
+        Object::Symbolic(expr) => Ok(from_symbolic(operation(to_symbolic(expr, line)?)?)),
+        Object::Function { params, block, captured } => {
+            let body = block.returned_expression().ok_or(HawkError::runtime(
+                format!("Only functions that just return an expression can be {action}"), line,
+            ))?;
+            let result = operation(to_symbolic(body, line)?)?;
+            Ok(Object::Function {
+                params: params.clone(),
+                block: Box::new(Statement::Return(Box::new(tree::Expression::from_cas_expr(result)))),
+                captured: captured.clone(),
+            })
+        },
+        x => Err(HawkError::runtime(
+            format!("Expected an expression as a string or a function to be {action}, found {}", x.user_print(line)?), line,
+        )),
+    }
+}
+
+/// Simplifies an expression given as a string or a function, see `transform`
+pub fn simplify(arg: &Object, line: usize) -> Result<Object, HawkError> {
+    transform(arg, "simplified", line, |expr| Ok(expr.simplified()))
+}
+
+/// Differentiates an expression given as a string, a function or a symbolic expression with respect to `var`, see
+/// `transform`. The variable can be left out for functions with one parameter, and symbolic expressions with one
+/// variable
+pub fn derive(arg: &Object, var: Option<&Object>, line: usize) -> Result<Object, HawkError> {
+    let var = match (arg, var) {
+        (_, Some(Object::String(var))) => var.clone(),
+        (Object::Function { params, .. }, None) if params.len() == 1 => params[0].clone(),
+        (Object::Symbolic(expr), None) if symbols(expr, line)?.len() == 1 => symbols(expr, line)?.remove(0),
+        (_, Some(x)) => return Err(HawkError::runtime(
+            format!("Expected the variable to differentiate with respect to as a string, found {}", x.user_print(line)?), line,
+        )),
+        (_, None) => return Err(HawkError::runtime(String::from("Expected the variable to differentiate with respect to"), line)),
+    };
+    transform(arg, "differentiated", line, |expr| expr.derivative(&var).map_err(|message| HawkError::runtime(message, line)))
+}
+
+#[test]
+fn simplify_expressions() {
+    use std::collections::HashMap;
+
+    use hawk_common::environment::Memory;
+
+    let source = String::from("let expr = simplify(\"x * 1 + 2 * x + 0\")
+let f = simplify((x) => (x^2 * x) / x + 1 * 3)
+let y = f(2)
+let k = 2
+let g = simplify((x) => k * x + x)
+let z = g(1)");
+    let globals = crate::run::run(source, HashMap::new(), false, &mut Memory::new()).unwrap();
+
+    assert_eq!(globals.get("expr"), Some(&Object::String(String::from("3 * x"))));
+    assert_eq!(globals.get("y"), Some(&Object::Int(7)));
+    assert_eq!(globals.get("z"), Some(&Object::Int(3)));
+
+    let errors = crate::run::run(String::from("simplify(\"x > 1\")"), HashMap::new(), false, &mut Memory::new()).unwrap_err();
+    assert!(errors[0].message.starts_with("Expression can't be used symbolically"));
+}
+
+#[test]
+fn derivatives() {
+    use std::collections::HashMap;
+
+    use hawk_common::environment::Memory;
+    use rust_decimal_macros::dec;
+
+    let source = String::from("let expr = derive(\"x^3 + sin(x)\", \"x\")
+let partial = derive(\"x * y^2\", \"y\")
+let f = (x) => x^2 + 3 * x
+let df = derive(f)
+let slope = df(2)
+let g = (t) => exp(2 * t)
+let dg = derive(g, \"t\")
+let growth = dg(0)");
+    let globals = crate::run::run(source, HashMap::new(), false, &mut Memory::new()).unwrap();
+
+    assert_eq!(globals.get("expr"), Some(&Object::String(String::from("3 * x^2 + cos(x)"))));
+    assert_eq!(globals.get("partial"), Some(&Object::String(String::from("2 * x * y"))));
+    assert_eq!(globals.get("slope"), Some(&Object::Int(7)));
+    assert_eq!(globals.get("growth"), Some(&Object::Decimal(dec!(2))));
+    assert_eq!(globals.get("df").unwrap().user_print(0).unwrap(), "(x) => 2 * x + 3");
+
+    let errors = crate::run::run(String::from("derive(\"abs(x)\", \"x\")"), HashMap::new(), false, &mut Memory::new()).unwrap_err();
+    assert_eq!(errors[0].message, "Can't differentiate abs()");
+    let errors = crate::run::run(String::from("derive((x, y) => x * y)"), HashMap::new(), false, &mut Memory::new()).unwrap_err();
+    assert_eq!(errors[0].message, "Expected the variable to differentiate with respect to");
+}
+
+#[test]
+fn symbolic_values() {
+    use hawk_common::environment::Memory;
+    use rust_decimal_macros::dec;
+
+    let source = String::from("let f = sym(x^2 + 2 * x)
+let g = f * 2 - sym(x) + 1
+let h = -sym(x^2)
+let printed = str(g)
+let at_three = substitute(f, {x: 3})
+let shifted = substitute(f, {x: sym(y + 1)})
+let df = derive(f)
+let same = sym(x + 1) == sym(x + 1)
+let different = sym(x) != sym(y)
+let negated = simplify(sym(-(a - b)))
+let x = 2
+let evaluated = evaluate(f)
+let given = evaluate(sym(a * b), {a: 3, b: 0.5})
+let uncertain = evaluate(f, {x: 1 ± 0.1})
+let parsed = sym(\"x * x\")");
+    let globals = crate::run::run(source, HashMap::new(), false, &mut Memory::new()).unwrap();
+    let printed = |name: &str| globals.get(name).unwrap().user_print(0).unwrap();
+
+    assert_eq!(printed("f"), "2 * x + x^2");
+    assert_eq!(printed("printed"), "3 * x + 2 * x^2 + 1");
+    assert_eq!(printed("h"), "-(x^2)");
+    assert_eq!(printed("at_three"), "15");
+    assert_eq!(printed("shifted"), "2 * y + (y + 1)^2 + 2");
+    assert_eq!(printed("df"), "2 * x + 2");
+    assert_eq!(printed("parsed"), "x^2");
+    assert_eq!(printed("negated"), "-a + b");
+    assert_eq!(globals.get("same"), Some(&Object::Boolean(true)));
+    assert_eq!(globals.get("different"), Some(&Object::Boolean(true)));
+    assert_eq!(globals.get("evaluated"), Some(&Object::Int(8)));
+    assert_eq!(globals.get("given"), Some(&Object::Decimal(dec!(1.5))));
+    if let Some(Object::Uncertain { value, uncertainty, .. }) = globals.get("uncertain") {
+        assert_eq!((*value, *uncertainty), (dec!(3), dec!(0.4)));
+    } else {
+        panic!("expected an uncertain value")
+    }
+
+    let error = |source: &str| crate::run::run(String::from(source), HashMap::new(), false, &mut Memory::new()).unwrap_err()[0].message.clone();
+    assert_eq!(error("evaluate(sym(z + 1))"), "Can't evaluate z + 1, as z has no value");
+    assert_eq!(error("sym(x) + \"a\""), "Can't use string in a symbolic expression, only numbers");
+}
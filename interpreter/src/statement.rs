@@ -1,29 +1,26 @@
+use hawk_common::error::HawkError;
 use hawk_common::tree::Expression;
 use std::collections::HashMap;
 
-use crate::Interpreter;
+use rust_decimal::Decimal;
+
+use crate::{ControlFlow, Interpreter};
 
 // Common types used throughout the interpreter
 use hawk_common::object::Object;
-use hawk_common::tree::Statement;
+use hawk_common::tree::{Iterable, Statement};
+use hawk_common::token::{TokenType, UserPrint};
+use hawk_common::uncertainty::Propagation;
 
-impl Interpreter {
-    /// Executes a given statement
-    pub fn run_statement(&mut self, statement: Statement) -> Result<(), (String, usize)> {
+impl Interpreter<'_> {
+    /// Executes a given statement, returning whether it finished normally or with `break`, `continue` or `return`
+    pub fn run_statement(&mut self, statement: Statement) -> Result<ControlFlow, HawkError> {
         match statement {
-            Statement::Print(expr) => {
-                let text = self.eval_expression(expr)?.user_print(self.line)?;
-                (self.output_fn)(text);
-                Ok(())
-            }
-
-            Statement::Definition { name, value } => self.run_definition(name, value),
-            Statement::ArrayAssign { name, idx, value } => self.run_array_assign(name, idx, value),
-
             Statement::While { condition, block } => self.run_while(condition, block),
-
             Statement::Loop(block) => self.run_loop(block),
-            Statement::Break => Ok(self.loops -= 1),
+            Statement::For { variables, iterable, block } => self.run_for(variables, iterable, block),
+            Statement::Break => Ok(ControlFlow::Break),
+            Statement::Continue => Ok(ControlFlow::Continue),
 
             Statement::If { condition, block } => self.run_if(condition, block),
 
@@ -35,15 +32,50 @@ impl Interpreter {
 
             Statement::Block(block) => self.run_block(block),
 
+            Statement::Try { block, error, catch } => self.run_try(block, error, catch),
+
+            Statement::Return(expr) => Ok(ControlFlow::Return(self.eval_expression(expr)?)),
+
+            Statement::Process {
+                readfile,
+                writefile,
+                block,
+            } => {
+                self.run_process(readfile, writefile, block)
+            }
+
+            statement => {
+                self.run_simple_statement(statement)?;
+                Ok(ControlFlow::Normal)
+            }
+        }
+    }
+
+    /// Executes a statement that can't change control flow
+    fn run_simple_statement(&mut self, statement: Statement) -> Result<(), HawkError> {
+        match statement {
+            Statement::Print(expr) => {
+                let text = self.eval_expression(expr)?.user_print(self.line)?;
+                self.env.output(text);
+                Ok(())
+            }
+
+            Statement::Definition { name, value } => self.run_definition(name, value),
+            Statement::ArrayAssign { name, idx, value } => self.run_array_assign(name, idx, value),
+
             Statement::Function {
                 identifier,
                 params,
                 block,
             } => {
                 // Define function in top scope
-                self.insert_top_scope(identifier, Object::Function { params, block })
+                let captured = self.capture();
+                self.insert_top_scope(identifier, Object::Function { params, block, captured })
             }
 
+            Statement::Class { identifier, parent, fields, methods } => self.run_class(identifier, parent, fields, methods),
+            Statement::FieldAssign { object, field, value } => self.run_field_assign(object, field, value),
+
             Statement::Finder {
                 identifier,
                 equations
@@ -52,10 +84,6 @@ impl Interpreter {
                 self.insert_top_scope(identifier, Object::Finder(equations))
             }
 
-            Statement::Return(expr) => {
-                self.run_return(expr)
-            }
-
             Statement::Import(expr) => {
                 self.run_import(expr)
             }
@@ -63,27 +91,110 @@ impl Interpreter {
             Statement::Expression(expr) => {
                 if self.in_repl {
                     let text = self.eval_expression(expr)?.user_print(self.line)?;
-                    (self.output_fn)(text)
+                    self.env.output(text)
                 } else {
                     self.eval_expression(expr)?;
                 }
                 Ok(())
             }
 
-            Statement::Process {
-                readfile,
-                writefile,
-                block,
-            } => {
-                self.run_process(readfile, writefile, block)
-            }
+            Statement::Expect { condition, message } => self.run_expect(condition, message),
 
-            Statement::Line => Ok(self.line += 1),
+            // Standard library code has no lines, so errors in it keep the line it was called from
+            Statement::Line(0) => Ok(()),
+            Statement::Line(line) => Ok(self.line = line),
             _ => Ok(())
         }
     }
 
-    fn run_array_assign(&mut self, name: String, idx: Box<Expression>, value: Box<Expression>) -> Result<(), (String, usize)> {
+    /// Gets the value a function call gives from how the function's block finished
+    pub fn returned(&self, flow: ControlFlow) -> Result<Object, HawkError> {
+        match flow {
+            ControlFlow::Return(value) => Ok(value),
+            ControlFlow::Normal => Ok(Object::Null),
+            ControlFlow::Break | ControlFlow::Continue => Err(HawkError::runtime(
+                String::from("Can't use break or continue outside of a loop"), self.line
+            )),
+        }
+    }
+
+    /// Runs an `expect` statement. The operands of a comparison are only evaluated once, and kept to describe the
+    /// failure, so that side effects aren't repeated and the message shows the values that were compared. `==` and
+    /// `!=` are checked with `expect_equal`
+    fn run_expect(&mut self, condition: Box<Expression>, message: Option<Box<Expression>>) -> Result<(), HawkError> {
+        let (result, compared) = match *condition {
+            Expression::Binary { operand1, operand2, operator } if matches!(
+                operator,
+                TokenType::EqualEqual | TokenType::NotEqual | TokenType::LessThan | TokenType::LessThanEqual
+                | TokenType::GreaterThan | TokenType::GreaterThanEqual
+            ) => {
+                let operand1 = self.eval_expression(operand1)?;
+                let operand2 = self.eval_expression(operand2)?;
+                let result = match operator {
+                    TokenType::EqualEqual => Object::Boolean(self.expect_equal(&operand1, &operand2)?),
+                    TokenType::NotEqual => Object::Boolean(!self.expect_equal(&operand1, &operand2)?),
+                    _ => Self::binary_operation(operand1.clone(), operand2.clone(), operator, self.propagation, self.line)?,
+                };
+                (result, Some((operand1, operator, operand2)))
+            },
+            condition => (self.eval_expression(Box::new(condition))?, None),
+        };
+
+        match result {
+            Object::Boolean(true) => Ok(()),
+            Object::Boolean(false) => {
+                let message = match message {
+                    Some(message) => self.eval_expression(message)?.user_print(self.line)?,
+                    None => self.describe_expectation(compared)?,
+                };
+                self.fail_expect();
+                self.env.warn(format!("Expect failed ({message})"), self.line);
+                Ok(())
+            },
+            x => Err(HawkError::runtime(format!("Expected a boolean after expect, found {}", x.user_print(self.line)?), self.line)),
+        }
+    }
+
+    /// Compares the operands of `==` in an `expect` statement. Unlike `==` elsewhere, uncertain values are equal to
+    /// anything within their uncertainty, so a measurement can be checked against a known value (`9.8 ± 0.1 == 9.81`
+    /// is true), and strings, booleans and `null` can be compared
+    fn expect_equal(&self, operand1: &Object, operand2: &Object) -> Result<bool, HawkError> {
+        let number = |object: &Object| matches!(object, Object::Int(_) | Object::Decimal(_) | Object::Uncertain { .. } | Object::Quantity { .. });
+
+        match (operand1, operand2) {
+            (Object::String(x), Object::String(y)) => Ok(x == y),
+            (Object::Boolean(x), Object::Boolean(y)) => Ok(x == y),
+            (Object::Null, Object::Null) => Ok(true),
+            (x, y) if number(x) && number(y) => {
+                // The uncertainty of the difference is the sum of the uncertainties, or 0 for a value compared to itself
+                let difference = match Self::subtract(x.clone(), y.clone(), Propagation::WorstCase, self.line)? {
+                    Object::Quantity { value, .. } => *value,
+                    difference => difference,
+                };
+                match difference {
+                    Object::Int(x) => Ok(x == 0),
+                    Object::Decimal(x) => Ok(x.is_zero()),
+                    Object::Uncertain { value, uncertainty, .. } => Ok(value.abs() <= uncertainty),
+                    x => Err(HawkError::runtime(format!("Can't compare {}", x.user_print(self.line)?), self.line)),
+                }
+            },
+            (x, y) => Err(HawkError::runtime(
+                format!("Can't compare {} to {}", x.user_print(self.line)?, y.user_print(self.line)?), self.line,
+            )),
+        }
+    }
+
+    /// Describes a failed expectation. Comparisons show the values that were compared, e.g. `expected 9.7 == 9.81`
+    fn describe_expectation(&self, compared: Option<(Object, TokenType, Object)>) -> Result<String, HawkError> {
+        match compared {
+            Some((operand1, operator, operand2)) => Ok(format!(
+                "expected {} {} {}", operand1.user_print(self.line)?, operator.user_print(), operand2.user_print(self.line)?,
+            )),
+            None => Ok(String::from("condition was false")),
+        }
+    }
+
+    fn run_array_assign(&mut self, name: String, idx: Box<Expression>, value: Box<Expression>) -> Result<(), HawkError> {
         if let Object::Array(mut data) = self.get_variable(name.clone()) {
             let idx = self.eval_expression(idx)?;
             let val = self.eval_expression(value)?;
@@ -97,34 +208,30 @@ impl Interpreter {
 
                 data[x as usize] = val;
 
-                let mut got_variable = false;
-
-                if !(self.scopes.len() == 0) {
-                    for i in (0..self.scopes.len()).rev() {
-                        if !got_variable && self.scopes[i].contains_key(&name.clone()) {
-                            got_variable = true;
-                            self.scopes[i].insert(name.clone(), Object::Array(data.clone()));
-                        }
-                    }
-                }
-
-                if !got_variable {
-                    self.globals.insert(name.clone(), Object::Array(data.clone()));
-                }
+                self.set_variable(name, Object::Array(data));
             }
         }
         Ok(())
     }
 
+    /// Runs a `process` block in its own scope, which is removed afterwards even if there is an error
     fn run_process(
         &mut self, readfile: Box<Expression>, writefile: Box<Expression>, block: Box<Statement>,
-) -> Result<(), (String, usize)> {
+    ) -> Result<ControlFlow, HawkError> {
         self.scopes.push(HashMap::new());
+        let result = self.run_process_block(readfile, writefile, block);
+        self.scopes.pop();
+        result
+    }
+
+    fn run_process_block(
+        &mut self, readfile: Box<Expression>, writefile: Box<Expression>, block: Box<Statement>,
+    ) -> Result<ControlFlow, HawkError> {
         if let Object::String(readfile) = self.eval_expression(readfile)? {
             self.open_datatable(readfile)?;
         }
 
-        self.run_statement(*block)?;
+        let flow = self.run_statement(*block)?;
 
         let mut columns: Vec<String> = Vec::new();
         let mut values: Vec<Object> = Vec::new();
@@ -146,11 +253,11 @@ impl Interpreter {
                 },
             ],
         )?;
-        Ok(())
+        Ok(flow)
     }
 
-    fn open_datatable(&mut self, readfile: String) -> Result<(), (String, usize)> {
-        let datatable = crate::csv::csv_to_datatable(readfile, self.line, self.filein_fn)?;
+    fn open_datatable(&mut self, readfile: String) -> Result<(), HawkError> {
+        let datatable = crate::csv::csv_to_datatable(readfile, self.line, self.env)?;
 
         if let Object::DataTable { names, data } = datatable.clone() {
             self.insert_top_scope(String::from("datatable"), datatable)?;
@@ -159,17 +266,36 @@ impl Interpreter {
                 self.insert_top_scope(name.clone(), data[index].clone())?;
             }
         } else {
-            return Err((format!("Expected datatable, found {}", datatable), self.line));
+            return Err(HawkError::runtime(format!("Expected datatable, found {}", datatable), self.line));
         }
         Ok(())
     }
 
-    fn run_import(&mut self, expr: Box<Expression>) -> Result<(), (String, usize)> {
+    fn run_import(&mut self, expr: Box<Expression>) -> Result<(), HawkError> {
         let evaled_filename = self.eval_expression(expr)?;
         if let Object::String(filename) = evaled_filename {
-            self.globals = crate::run::run_script(filename, self.globals.clone(), self.filein_fn, self.fileout_fn, self.warn_fn, self.err_fn, self.output_fn);
+            // The imported file runs with its own statements, but shares variables and settings
+            let mut imported = crate::run::Builder::with_natives(self.natives.clone())
+                .propagation(self.propagation)
+                .monte_carlo(self.monte_carlo.clone())
+                .interpreter(self.globals.clone(), self.env);
+            match imported.run_file(filename.clone()) {
+                Ok(()) => {
+                    self.globals = imported.globals;
+                    self.propagation = imported.propagation;
+                    self.monte_carlo = imported.monte_carlo;
+                    self.failed_expects += imported.failed_expects;
+                },
+                Err(errors) => {
+                    // Show the errors in the imported file, then stop here
+                    for error in errors {
+                        imported.env.error(error)
+                    }
+                    return Err(HawkError::runtime(format!("Couldn't import {filename}"), self.line))
+                }
+            }
         } else {
-            return Err((
+            return Err(HawkError::runtime(
                 format!("Expected filename to be a string, found {}", evaled_filename),
                 self.line,
             ));
@@ -177,48 +303,44 @@ impl Interpreter {
         Ok(())
     }
 
-    fn run_return(&mut self, expr: Box<Expression>) -> Result<(), (String, usize)> {
-        let val = self.eval_expression(expr)?;
-
-        // Assign to the variable `"return"` in the top scope, which the function call will see and return
-        self.insert_top_scope(String::from("return"), val)
-    }
-
-    fn run_block(&mut self, block: Vec<Statement>) -> Result<(), (String, usize)> {
+    fn run_block(&mut self, block: Vec<Statement>) -> Result<ControlFlow, HawkError> {
         for statement in block {
-            self.run_statement(statement)? // Run each statement in block
+            match self.run_statement(statement)? {
+                ControlFlow::Normal => (),
+                // After `break`, `continue` or `return`, skip the rest of the block
+                flow => return Ok(flow),
+            }
         }
-        Ok(())
+        Ok(ControlFlow::Normal)
     }
 
     fn run_if_else(
         &mut self, condition: Box<Expression>, if_block: Box<Statement>, else_block: Box<Statement>,
-    ) -> Result<(), (String, usize)> {
+    ) -> Result<ControlFlow, HawkError> {
         let evaled_condition = self.eval_expression(condition)?;
 
         // Make sure condition is a boolean
         if let Object::Boolean(condition) = evaled_condition {
-            self.run_statement(if condition { *if_block } else { *else_block })?
+            self.run_statement(if condition { *if_block } else { *else_block })
         } else {
-            return Err((
+            Err(HawkError::runtime(
                 format!(
                     "Expected boolean as condition for if else statement, found {}",
                     evaled_condition
                 ),
                 self.line,
-            ));
+            ))
         }
-        Ok(())
     }
 
-    fn run_if(&mut self, condition: Box<Expression>, block: Box<Statement>) -> Result<(), (String, usize)> {
+    fn run_if(&mut self, condition: Box<Expression>, block: Box<Statement>) -> Result<ControlFlow, HawkError> {
         let evaled_condition = self.eval_expression(condition)?;
         if let Object::Boolean(condition) = evaled_condition {
             if condition {
-                self.run_statement(*block)?
+                return self.run_statement(*block)
             }
         } else {
-            return Err((
+            return Err(HawkError::runtime(
                 format!(
                     "Expected boolean as condition for if statement, found {}",
                     evaled_condition
@@ -226,41 +348,39 @@ impl Interpreter {
                 self.line,
             ));
         }
-        Ok(())
+        Ok(ControlFlow::Normal)
+    }
+
+    /// Runs the body of a loop once. Gives the control flow the loop should finish with if it should stop
+    fn run_iteration(&mut self, block: &Statement) -> Result<Option<ControlFlow>, HawkError> {
+        match self.run_statement(block.clone())? {
+            ControlFlow::Normal | ControlFlow::Continue => Ok(None),
+            ControlFlow::Break => Ok(Some(ControlFlow::Normal)),
+            // `return` ends the loop and is passed on to the function call
+            ControlFlow::Return(value) => Ok(Some(ControlFlow::Return(value))),
+        }
     }
 
-    fn run_loop(&mut self, block: Box<Statement>) -> Result<(), (String, usize)> {
-        self.loops += 1;
-        let current_loop = self.loops;
+    fn run_loop(&mut self, block: Box<Statement>) -> Result<ControlFlow, HawkError> {
         loop {
-            if self.loops == current_loop {
-                self.run_statement(*block.clone())?
-            } else {
-                break;
+            if let Some(flow) = self.run_iteration(&block)? {
+                return Ok(flow)
             }
         }
-        Ok(())
     }
 
-    fn run_while(&mut self, condition: Box<Expression>, block: Box<Statement>) -> Result<(), (String, usize)> {
-        let is_true = true;
-        self.loops += 1;
-        let current_loop = self.loops;
-        while is_true {
+    fn run_while(&mut self, condition: Box<Expression>, block: Box<Statement>) -> Result<ControlFlow, HawkError> {
+        loop {
             let evaled_condition = self.eval_expression(condition.clone())?;
             if let Object::Boolean(is_true) = evaled_condition {
-                if is_true && (self.loops == current_loop) {
-                    self.run_statement(*block.clone())?
-                } else {
-                    if !(self.loops == current_loop) {
-                        break;
-                    } else {
-                        self.loops -= 1;
-                        break;
-                    }
+                if !is_true {
+                    return Ok(ControlFlow::Normal)
+                }
+                if let Some(flow) = self.run_iteration(&block)? {
+                    return Ok(flow)
                 }
             } else {
-                return Err((
+                return Err(HawkError::runtime(
                     format!(
                         "Expected boolean as condition for while loop, found {}",
                         evaled_condition
@@ -269,10 +389,130 @@ impl Interpreter {
                 ));
             }
         }
-        Ok(())
     }
 
-    fn run_definition(&mut self, name: String, value: Box<Expression>) -> Result<(), (String, usize)> {
+    fn run_for(&mut self, variables: Vec<String>, iterable: Iterable, block: Box<Statement>) -> Result<ControlFlow, HawkError> {
+        let items = self.eval_iterable(iterable)?;
+
+        self.scopes.push(HashMap::new());
+        let result = self.run_for_items(&variables, items, &block);
+
+        // Blocks don't have their own scopes, so only the loop variables go away when the loop finishes
+        let scope = self.scopes.pop().unwrap_or_default();
+        for (name, value) in scope {
+            if !variables.contains(&name) {
+                self.insert_top_scope(name, value)?
+            }
+        }
+        result
+    }
+
+    /// Runs the block of a `for` loop with its variables set to each item in turn
+    fn run_for_items(
+        &mut self, variables: &[String], items: Box<dyn Iterator<Item = Object>>, block: &Statement,
+    ) -> Result<ControlFlow, HawkError> {
+        for item in items {
+            if variables.len() == 1 {
+                self.insert_top_scope(variables[0].clone(), item)?
+            } else if let Object::Array(values) = item {
+                if values.len() != variables.len() {
+                    return Err(HawkError::runtime(format!("Can't unpack {} values into {} loop variables", values.len(), variables.len()), self.line))
+                }
+                for (variable, value) in variables.iter().zip(values) {
+                    self.insert_top_scope(variable.clone(), value)?
+                }
+            } else {
+                return Err(HawkError::runtime(format!("Can't unpack {} into {} loop variables", item.user_print(self.line)?, variables.len()), self.line))
+            }
+
+            if let Some(flow) = self.run_iteration(block)? {
+                return Ok(flow)
+            }
+        }
+        Ok(ControlFlow::Normal)
+    }
+
+    /// Gets the items a `for` loop goes through. `enumerate(x)` gives each item of `x` together with its index
+    fn eval_iterable(&mut self, iterable: Iterable) -> Result<Box<dyn Iterator<Item = Object>>, HawkError> {
+        match iterable {
+            Iterable::Range { start, end, step } => {
+                let start = self.eval_expression(start)?;
+                let end = self.eval_expression(end)?;
+                let step = match step {
+                    Some(step) => self.eval_expression(step)?,
+                    None => Object::Int(1),
+                };
+                self.range(start, end, step)
+            },
+            Iterable::Collection(expr) => match *expr {
+                Expression::FunctionCall { identifier, mut args } if identifier == "enumerate" && args.len() == 1 => {
+                    let collection = self.eval_expression(args.remove(0))?;
+                    Ok(Box::new(Self::enumerate(self.items(collection)?).into_iter()))
+                },
+                expr => {
+                    let collection = self.eval_expression(Box::new(expr))?;
+                    Ok(Box::new(self.items(collection)?.into_iter()))
+                }
+            },
+        }
+    }
+
+    /// Gets the items of an array or column, or the rows of a datatable as arrays
+    pub fn items(&self, collection: Object) -> Result<Vec<Object>, HawkError> {
+        match collection {
+            Object::Array(items) | Object::Column(items) => Ok(items),
+            Object::DataTable { data, .. } => {
+                let rows = data.iter().map(|column| if let Object::Column(x) = column {x.len()} else {0}).max().unwrap_or(0);
+                Ok((0..rows).map(|row| Object::Array(
+                    data.iter().map(|column| match column {
+                        Object::Column(x) => x.get(row).cloned().unwrap_or(Object::Null),
+                        _ => Object::Null,
+                    }).collect()
+                )).collect())
+            },
+            x => Err(HawkError::runtime(format!("Can't loop over {}", x.user_print(self.line)?), self.line)),
+        }
+    }
+
+    /// Pairs each item with its index, e.g. `[a, b]` becomes `[[0, a], [1, b]]`
+    pub fn enumerate(items: Vec<Object>) -> Vec<Object> {
+        items.into_iter().enumerate().map(|(index, item)| Object::Array(vec![Object::Int(index as i128), item])).collect()
+    }
+
+    /// The numbers from `start` up to (but not including) `end`, counting by `step`. They are made as the loop goes
+    /// through them, so a long range doesn't have to be stored
+    fn range(&self, start: Object, end: Object, step: Object) -> Result<Box<dyn Iterator<Item = Object>>, HawkError> {
+        if let (Object::Int(start), Object::Int(end), Object::Int(step)) = (&start, &end, &step) {
+            if *step == 0 {
+                return Err(HawkError::runtime(String::from("Step of a range can't be 0"), self.line))
+            }
+            let (end, step) = (*end, *step);
+            return Ok(Box::new(
+                std::iter::successors(Some(*start), move |i| i.checked_add(step))
+                    .take_while(move |i| (step > 0 && *i < end) || (step < 0 && *i > end))
+                    .map(Object::Int)
+            ))
+        }
+
+        let number = |x: &Object| match x {
+            Object::Int(x) => Some(Decimal::from(*x as i64)),
+            Object::Decimal(x) => Some(*x),
+            _ => None
+        };
+        match (number(&start), number(&end), number(&step)) {
+            (Some(_), Some(_), Some(step)) if step.is_zero() => Err(HawkError::runtime(String::from("Step of a range can't be 0"), self.line)),
+            (Some(start), Some(end), Some(step)) => Ok(Box::new(
+                std::iter::successors(Some(start), move |i| i.checked_add(step))
+                    .take_while(move |i| (step > Decimal::ZERO && *i < end) || (step < Decimal::ZERO && *i > end))
+                    .map(Object::Decimal)
+            )),
+            _ => Err(HawkError::runtime(format!(
+                "Expected numbers for a range, found {}..{} step {}", start.user_print(self.line)?, end.user_print(self.line)?, step.user_print(self.line)?
+            ), self.line)),
+        }
+    }
+
+    fn run_definition(&mut self, name: String, value: Box<Expression>) -> Result<(), HawkError> {
         let val = self.eval_expression(value)?;
         self.insert_top_scope(name, val)
     }
@@ -293,8 +533,19 @@ impl Interpreter {
         }
     }
 
+    /// Changes a variable in the topmost scope where it is defined, or defines it as a global if it isn't defined
+    pub fn set_variable(&mut self, identifier: String, value: Object) {
+        for scope in self.scopes.iter_mut().rev() {
+            if let Some(variable) = scope.get_mut(&identifier) {
+                *variable = value;
+                return
+            }
+        }
+        self.globals.insert(identifier, value);
+    }
+
     /// Inserts a varibable into the most local scope currently available (`HashMap` on the top of the `scopes` stack)
-    pub fn insert_top_scope(&mut self, identifier: String, value: Object) -> Result<(), (String, usize)> {
+    pub fn insert_top_scope(&mut self, identifier: String, value: Object) -> Result<(), HawkError> {
         if self.scopes.len() > 0 {
             let index = self.scopes.len() - 1;
             self.scopes[index].insert(identifier, value);
@@ -304,3 +555,152 @@ impl Interpreter {
         Ok(())
     }
 }
+
+#[test]
+fn expect() {
+    use hawk_common::environment::Memory;
+
+    let mut env = Memory::new().with_file("lib.hawk", "expect false");
+    let mut interpreter = crate::run::Builder::new().interpreter(HashMap::new(), &mut env);
+    interpreter.run_source(
+        "let g = 9.79 ± 0.05\nexpect g == 9.81\nexpect g == 9.9, \"g is too small\"\nassert 1 < 2\nexpect \"a\" != \"a\"",
+    ).unwrap();
+
+    assert_eq!(interpreter.failed_expects, 2);
+    assert_eq!(interpreter.globals.len(), 1);
+
+    // Failures in imported files count too
+    interpreter.run_source("import \"lib.hawk\"").unwrap();
+    assert_eq!(interpreter.failed_expects, 3);
+    drop(interpreter);
+    assert_eq!(env.warnings[1].0, "Expect failed (expected a != a)");
+
+    // The operands of a failed comparison are only evaluated once, and the message shows the values compared
+    let mut env = Memory::new();
+    crate::run::run(String::from("function next() {
+    print \"called\"
+    return 1
+}
+expect next() == 5"), HashMap::new(), false, &mut env).unwrap();
+    assert_eq!(env.output, vec![String::from("called")]);
+    assert_eq!(env.warnings[0].0, "Expect failed (expected 1 == 5)");
+
+    // Uncertain values agree with anything within their uncertainty, also with units, but only in expect
+    let mut env = Memory::new();
+    let globals = crate::run::run(String::from("let g = 9.8 ± 0.05 m/s^2
+expect g == 981 cm/s^2
+expect g != 9.85 ± 0.01 m/s^2
+expect g == g
+expect g != 10 m/s^2"), HashMap::new(), false, &mut env).unwrap();
+    assert_eq!(env.warnings.len(), 1);
+    assert_eq!(env.warnings[0].1, 3);
+    assert!(globals.contains_key("g"));
+
+    let errors = crate::run::run(String::from("let g = 9.8 ± 0.05\nlet same = g == 9.81"), HashMap::new(), false, &mut env).unwrap_err();
+    assert_eq!(errors[0].message, "Can't compare 9.8 ± 0.05");
+}
+
+#[test]
+fn for_loops() {
+    use hawk_common::environment::Memory;
+
+    let source = String::from("let total = 0
+for i in 0..10 step 2 {
+    if i == 4 continue
+    total = total + i
+}
+let found = -1
+for (i, x) in enumerate([5, 7, 9, 7]) {
+    if x == 7 {
+        found = i
+        break
+    }
+}
+let rows = 0
+for row in read(\"data.csv\") rows = rows + row[1]
+let last = 0
+for i in 0..1000000000000 {
+    if i == 3 break
+    last = i
+}
+let down = 0
+for x in 1..0 step -0.25 down = down + x");
+    let globals = crate::run::run(
+        source, HashMap::new(), false, &mut Memory::new().with_file("data.csv", "a,b\n1,2\n3,4"),
+    ).unwrap();
+
+    assert_eq!(globals.get("total"), Some(&Object::Int(16)));
+    assert_eq!(globals.get("found"), Some(&Object::Int(1)));
+    assert_eq!(globals.get("rows"), Some(&Object::Int(6)));
+    // A long range isn't made before the loop starts
+    assert_eq!(globals.get("last"), Some(&Object::Int(2)));
+    assert_eq!(globals.get("down").map(|x| x.user_print(0).unwrap()), Some(String::from("2.5")));
+
+    // Loop variables are removed after the loop, and a variable they shadowed is kept
+    let globals = crate::run::run(String::from("let x = 5
+for x in [10, 20, 30] {
+    let seen = x
+}
+for (i, y) in enumerate([1, 2]) print y"), HashMap::new(), false, &mut Memory::new()).unwrap();
+    assert_eq!(globals.get("x"), Some(&Object::Int(5)));
+    assert_eq!(globals.get("seen"), Some(&Object::Int(30)));
+    assert_eq!(globals.get("i"), None);
+    assert_eq!(globals.get("y"), None);
+}
+
+#[test]
+fn control_flow() {
+    use hawk_common::environment::Memory;
+
+    let source = String::from("function position(grid, target) {
+    for i in 0..len(grid) {
+        let row = grid[i]
+        for j in 0..len(row) {
+            if row[j] == target return [i, j]
+        }
+    }
+    return null
+}
+let found = position([[1, 2], [3, 4]], 3)
+let missing = position([[1, 2], [3, 4]], 5)
+function fib(n) {
+    if n < 2 return n
+    return fib(n - 1) + fib(n - 2)
+}
+let f = fib(10)
+let after = 0
+function early() {
+    return 1
+    after = 1
+}
+let e = early()
+let return_value = 5
+function nothing() {
+    let x = 1
+}
+let n = nothing()
+let count = 0
+let i = 0
+while true {
+    i = i + 1
+    if i > 10 break
+    if i == 5 continue
+    count = count + 1
+}");
+    let globals = crate::run::run(
+        source, HashMap::new(), false, &mut Memory::new(),
+    ).unwrap();
+
+    assert_eq!(globals.get("found"), Some(&Object::Array(vec![Object::Int(1), Object::Int(0)])));
+    assert_eq!(globals.get("missing"), Some(&Object::Null));
+    assert_eq!(globals.get("f"), Some(&Object::Int(55)));
+    assert_eq!(globals.get("e"), Some(&Object::Int(1)));
+    assert_eq!(globals.get("after"), Some(&Object::Int(0)));
+    assert_eq!(globals.get("n"), Some(&Object::Null));
+    assert_eq!(globals.get("count"), Some(&Object::Int(9)));
+
+    let outside = crate::run::run(
+        String::from("break"), HashMap::new(), false, &mut Memory::new(),
+    );
+    assert!(outside.is_err());
+}
@@ -0,0 +1,27 @@
+use hawk_common::environment::Environment;
+use hawk_common::error::HawkError;
+
+/// Runs Hawk in the terminal, with files from the filesystem
+pub struct Terminal;
+
+impl Environment for Terminal {
+    fn read_file(&mut self, name: &str) -> Result<String, String> {
+        std::fs::read_to_string(name).map_err(|error| format!("Unable to read file: {error}"))
+    }
+
+    fn write_file(&mut self, name: &str, contents: &str) -> Result<(), String> {
+        std::fs::write(name, contents).map_err(|error| format!("Unable to write file: {error}"))
+    }
+
+    fn output(&mut self, text: String) {
+        crate::output::output(text)
+    }
+
+    fn warn(&mut self, message: String, line: usize) {
+        crate::error::warn(message, line);
+    }
+
+    fn error(&mut self, error: HawkError) {
+        crate::error::report(&error)
+    }
+}
@@ -2,31 +2,85 @@ use std::io::{stdout};
 
 use crossterm::execute;
 use crossterm::style::{Color::{Red, Yellow}, Colors, Print, SetColors};
+use unicode_segmentation::UnicodeSegmentation;
 
+use hawk_common::error::HawkError;
 use hawk_common::object::Object;
 
-/// Exit the program with an error message. Returns Null just to appease Rust
-pub fn error(message: String, line: usize) -> Object {
-    let in_repl = line == 0;
+/// Exit the program with an error. Returns Null just to appease Rust
+pub fn error(error: HawkError) -> Object {
+    report(&error);
+    std::process::exit(1)
+}
 
+/// Show an error without exiting, e.g. in the REPL
+pub fn report(error: &HawkError) {
     execute!(
         stdout(),
         SetColors(Colors{ foreground: Some(Red), background: None}),
-        Print(
-            if !in_repl {
-                format!("Error on line {line}: {message}\n")
-            } else {
-                format!("Error: {message}\n")
-            }),
+        Print(render(error)),
     ).unwrap();
+}
+
+/// Renders an error like rustc does, with the line of code it is on and the part of the line that caused it
+/// underlined:
+///
+/// ```text
+/// syntax error: Expected 'in' after loop variables
+///  --> script.hawk:3:10
+///   |
+/// 3 | for (a, b) pairs {
+///   |          ^
+/// ```
+pub fn render(error: &HawkError) -> String {
+    let mut text = format!("{}: {}\n", error.kind.name(), error.message);
+    let span = error.span;
+
+    // Leave room in the margin for the line number
+    let margin = if span.line == 0 { String::new() } else { " ".repeat(span.line.to_string().len()) };
+
+    if span.line != 0 {
+        let location = match (&error.file, span.column) {
+            (Some(file), 0) => format!("{file}:{}", span.line),
+            (Some(file), column) => format!("{file}:{}:{column}", span.line),
+            (None, 0) => format!("line {}", span.line),
+            (None, column) => format!("line {}, column {column}", span.line),
+        };
+        text += &format!("{margin}--> {location}\n");
 
-    if !in_repl {
-        std::process::exit(1);
+        if let Some(source_line) = &error.source_line {
+            text += &format!("{margin} |\n");
+            text += &format!("{} | {}\n", span.line, source_line.trim_end());
+            text += &format!("{margin} | {}\n", underline(source_line, span.column, span.length));
+        }
     }
 
-    // Satisfy return to shut up the compiler
-    #[allow(unreachable_code)]
-    Object::Null
+    for note in error.notes.iter() {
+        text += &format!("{margin} = note: {note}\n");
+    }
+
+    // Frames of the traceback line up under its heading
+    for (index, line) in error.traceback().lines().enumerate() {
+        text += &format!("{margin} {} {line}\n", if index == 0 {"="} else {" "});
+    }
+
+    text
+}
+
+/// Underlines `length` characters of a line of code starting at `column`, or the whole line if the column isn't known
+fn underline(line: &str, column: u32, length: u32) -> String {
+    let characters = line.trim_end().graphemes(true).collect::<Vec<&str>>();
+
+    let (start, length) = if column == 0 {
+        let indent = characters.iter().take_while(|c| c.trim().is_empty()).count();
+        (indent, characters.len() - indent)
+    } else {
+        (column as usize - 1, length as usize)
+    };
+
+    // Tabs are kept so that the underline lines up with the code
+    let padding: String = characters.iter().take(start).map(|c| if *c == "\t" {"\t"} else {" "}).collect();
+    format!("{padding}{}", "^".repeat(length.max(1)))
 }
 
 /// Exit the program with an error message. Returns Null just to appease Rust
@@ -43,4 +97,41 @@ pub fn warn(message: String, line: usize) -> Object {
         } ),
     ).unwrap();
     Object::Null
-}
\ No newline at end of file
+}
+
+#[test]
+fn render_error() {
+    use hawk_common::error::Span;
+
+    let error = HawkError::syntax(String::from("Expected 'in' after loop variables"), Span::new(3, 10, 1))
+        .with_source("let pairs = []\n\nfor (a, b) pairs {")
+        .in_file(String::from("script.hawk"));
+
+    assert_eq!(render(&error), "syntax error: Expected 'in' after loop variables
+ --> script.hawk:3:10
+  |
+3 | for (a, b) pairs {
+  |          ^
+");
+
+    let error = HawkError::runtime(String::from("Can't add"), 1)
+        .with_source("\tprint a + b ")
+        .with_note(String::from("a is a string"));
+
+    assert_eq!(render(&error), "error: Can't add
+ --> line 1
+  |
+1 | \tprint a + b
+  | \t^^^^^^^^^^^
+  = note: a is a string
+");
+
+    let error = HawkError::runtime(String::from("Can't divide by 0"), 7)
+        .with_trace(&[hawk_common::error::Frame { function: String::from("mean"), line: 7, file: None }]);
+
+    assert_eq!(render(&error), "error: Can't divide by 0
+ --> line 7
+  = traceback (most recent call last):
+        mean, called from line 7
+");
+}
@@ -1,3 +1,4 @@
+pub mod environment;
 pub mod error;
 pub mod shell;
 pub mod output;
\ No newline at end of file
@@ -103,7 +103,7 @@ impl Input {
 
 pub fn print_welcome_message() {
     print_line_equals();
-    execute!(stdout(), style::PrintStyledContent("\nWelcome to Hawk REPL. Exit the REPL by running 'exit' or pressing ctrl + C.\n\n".dark_grey())).unwrap();
+    execute!(stdout(), style::PrintStyledContent("\nWelcome to Hawk REPL. Save or load a session with 'save <file>' or 'load <file>'. Exit the REPL by running 'exit' or pressing ctrl + C.\n\n".dark_grey())).unwrap();
 }
 
 pub fn print_exit_message() {
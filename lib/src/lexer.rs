@@ -1,7 +1,9 @@
 use unicode_segmentation::UnicodeSegmentation;
 
+use hawk_common::error::{HawkError, Span};
 use hawk_common::object::Object;
 use hawk_common::token::{Token, TokenType};
+use hawk_common::units::{self, Unit};
 
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
@@ -11,18 +13,29 @@ pub struct Lexer<'a> {
     num_chars: usize,
     pub tokens: Vec<Token>,
     line: usize,
+    /// Index of the first character on the current line, to work out columns
+    line_start: usize,
+    /// Index of the first character of the token being lexed
+    token_start: usize,
     lexeme_start: usize,
-    index: usize
+    index: usize,
+    warn: &'a mut dyn FnMut(String, usize),
+    /// Literals that can't be represented, reported once the whole source has been lexed
+    errors: Vec<HawkError>,
 }
 
 impl<'a> Lexer<'a> {
     /// Constructs a lexer and lexes sources code
-    pub fn lex(source: &'a str) -> Vec<Token> {
-        let mut code_lexer = Lexer {characters: source.graphemes(true).collect::<Vec<&str>>(), num_chars: 0, tokens: Vec::new(), line: 1, lexeme_start: 0, index: 0};
+    pub fn lex(source: &'a str, warn: &'a mut dyn FnMut(String, usize)) -> Result<Vec<Token>, Vec<HawkError>> {
+        let mut code_lexer = Lexer {characters: source.graphemes(true).collect::<Vec<&str>>(), num_chars: 0, tokens: Vec::new(), line: 1, line_start: 0, token_start: 0, lexeme_start: 0, index: 0, warn, errors: Vec::new()};
 
         code_lexer.lex_code();
 
-        code_lexer.tokens
+        if code_lexer.errors.is_empty() {
+            Ok(code_lexer.tokens)
+        } else {
+            Err(code_lexer.errors)
+        }
     }
 
     /// Loops through characters of source code and converts to meaningful tokens
@@ -33,6 +46,7 @@ impl<'a> Lexer<'a> {
 
         while self.index < self.num_chars {
             let c = self.characters[self.index];    // Get character to match on
+            self.token_start = self.index;
             self.consume_char();
             self.match_character(c);
         }
@@ -44,7 +58,7 @@ impl<'a> Lexer<'a> {
         match c {
             " " | "\r" | "\t" => {},    // Ignore whitespace
             "\n" => {
-                self.line = self.line + 1;  // Keep track of line number
+                self.new_line(self.index)
             },
             "(" => self.add_token(TokenType::ParenthesisLeft, None),
             ")" => self.add_token(TokenType::ParenthesisRight, None),
@@ -57,7 +71,13 @@ impl<'a> Lexer<'a> {
             "^" => self.add_token(TokenType::Caret, None),
             ":" => self.add_token(TokenType::Colon, None),
             "," => self.add_token(TokenType::Comma, None),
-            "." => self.add_token(TokenType::Dot, None),
+            "." => {
+                if self.match_next(".") {
+                    self.add_token(TokenType::DotDot, None)
+                } else {
+                    self.add_token(TokenType::Dot, None)
+                }
+            },
             "-" => self.add_token(TokenType::Minus, None),
             "±" => self.add_token(TokenType::PlusMinus, None),
             "?" => self.add_token(TokenType::QuestionMark, None),
@@ -123,16 +143,104 @@ impl<'a> Lexer<'a> {
         }
     }
 
-    /// Lexes int and float literals
+    /// Lexes int and float literals, including scientific notation (e.g. `6.626e-34`)
     fn number(&mut self, c: &str) {
         if let Ok(_) = c.parse::<usize>() {
             let (is_float, int) = self.parse_int();
 
-            if is_float {   // Get decimal part of number
-                self.parse_float(int);
+            let mantissa = if is_float {   // Get decimal part of number
+                self.parse_float(int)
+            } else {
+                self.index = self.index - 1; // Undo final advance in case there is another token without a space in between
+                Decimal::from(int as i64)
+            };
+
+            if let Some(exponent) = self.parse_exponent() {
+                let number = match apply_exponent(mantissa, exponent) {
+                    Ok((number, Some(warning))) => {
+                        (self.warn)(warning, self.line);
+                        number
+                    },
+                    Ok((number, None)) => number,
+                    Err(message) => {
+                        let column = self.token_start.saturating_sub(self.line_start) + 1;
+                        self.errors.push(HawkError::syntax(message, Span::new(self.line, column, self.index - self.token_start)));
+                        Decimal::ZERO
+                    },
+                };
+                self.add_token(TokenType::Decimal, Some(Object::Decimal(number)));
+            } else if is_float {
+                self.add_token(TokenType::Decimal, Some(Object::Decimal(mantissa)));
             } else {
                 self.add_token(TokenType::Int, Some(Object::Int(int as i128)));
-                self.index = self.index - 1 // Undo final advance in case there is another token without a space in between
+            }
+
+            self.unit()
+        }
+    }
+
+    /// Lexes a unit written after a number literal, e.g. the `s` in `3s` or the `m/s^2` in `9.81 m/s^2`. A unit
+    /// separated from the number by spaces has to be combined with `*` or `/`, since a single symbol there is usually
+    /// a variable (`if x > 3 s = 1`). Anything that isn't a known unit is left to be lexed as usual, so `2m/t` is `2 m`
+    /// divided by `t`
+    fn unit(&mut self) {
+        let is_letter = |c: &str| c.chars().next().is_some_and(char::is_alphabetic);
+
+        let mut index = self.index;
+        while let " " | "\t" = self.char_at(index) {
+            index += 1
+        }
+        let separated = index > self.index;
+
+        let start = index;
+        let mut end = None;
+        let mut symbols = 0;
+
+        loop {
+            let symbol_start = index;
+            while is_letter(self.char_at(index)) {
+                index += 1
+            }
+
+            // A symbol followed by a digit or underscore is an identifier, not a unit
+            let symbol = self.characters[symbol_start..index].join("");
+            let followed_by_identifier = self.char_at(index).parse::<usize>().is_ok() || self.char_at(index) == "_";
+            if symbol.is_empty() || followed_by_identifier || units::lookup(&symbol).is_none() {
+                break
+            }
+            end = Some(index);
+            symbols += 1;
+
+            if self.char_at(index) == "^" {
+                let mut power_end = index + 1;
+                if self.char_at(power_end) == "-" {
+                    power_end += 1
+                }
+                let digits_start = power_end;
+                while self.char_at(power_end).parse::<usize>().is_ok() {
+                    power_end += 1
+                }
+                if power_end > digits_start {
+                    index = power_end;
+                    end = Some(index)
+                }
+            }
+
+            if let "*" | "/" = self.char_at(index) {
+                index += 1
+            } else {
+                break
+            }
+        }
+
+        if separated && symbols < 2 {
+            return
+        }
+        if let Some(end) = end {
+            if let Some(unit) = Unit::parse(&self.characters[start..end].join("")) {
+                self.token_start = start;
+                self.index = end;
+                self.add_token(TokenType::Unit, Some(Object::Quantity{value: Box::new(Object::Int(1)), unit}))
             }
         }
     }
@@ -156,7 +264,7 @@ impl<'a> Lexer<'a> {
     }
 
     /// Parses decimal part of a float
-    fn parse_float(&mut self, int: usize) {
+    fn parse_float(&mut self, int: usize) -> Decimal {
         let mut decimal: Decimal = dec!(0);
         let mut decimal_digits: Vec<usize> = Vec::new();
 
@@ -169,9 +277,31 @@ impl<'a> Lexer<'a> {
             decimal = (decimal + Decimal::from(*i as i64)) / dec!(10);
         }
 
-        let number = Decimal::from(int as i64) + decimal;
+        Decimal::from(int as i64) + decimal
+    }
+
+    /// Parses the exponent of a number in scientific notation (`e` or `E`, an optional sign and digits).
+    /// Returns `None` without consuming anything if there is no exponent, so that e.g. `2e` still lexes as `2` and `e`
+    fn parse_exponent(&mut self) -> Option<i64> {
+        let (sign, digits_start) = match (self.char_at(self.index), self.char_at(self.index + 1)) {
+            ("e" | "E", "-") => (-1, self.index + 2),
+            ("e" | "E", "+") => (1, self.index + 2),
+            ("e" | "E", _) => (1, self.index + 1),
+            _ => return None
+        };
+
+        if let Err(_) = self.char_at(digits_start).parse::<usize>() {
+            return None
+        }
+
+        self.index = digits_start;
+        let mut exponent: i64 = 0;
+        while let Ok(num) = self.char_at(self.index).parse::<i64>() {
+            exponent = exponent.saturating_mul(10).saturating_add(num);
+            self.consume_char()
+        }
 
-        self.add_token(TokenType::Decimal, Some(Object::Decimal(number)));
+        Some(sign * exponent)
     }
 
     /// Checks for keywords, otherwise adds identifier
@@ -183,9 +313,11 @@ impl<'a> Lexer<'a> {
             "while" => self.add_token(TokenType::While, None),
             "for" => self.add_token(TokenType::For, None),
             "break" => self.add_token(TokenType::Break, None),
+            "continue" => self.add_token(TokenType::Continue, None),
+            "in" => self.add_token(TokenType::In, None),
             "class" => self.add_token(TokenType::Class, None),
-            "super" => self.add_token(TokenType::Super, None),
-            "this" => self.add_token(TokenType::This, None),
+            "super" => self.add_token(TokenType::Super, Some(Object::Identifier(current_token))),
+            "this" => self.add_token(TokenType::This, Some(Object::Identifier(current_token))),
             "let" => self.add_token(TokenType::Let, None),
             "null" => self.add_token(TokenType::Null, None),
             "function" => self.add_token(TokenType::Function, None),
@@ -201,6 +333,9 @@ impl<'a> Lexer<'a> {
             "finder" => self.add_token(TokenType::Finder, None),
             "find" => self.add_token(TokenType::Find, None),
             "equation" => self.add_token(TokenType::Equation, None),
+            "expect" | "assert" => self.add_token(TokenType::Expect, None),
+            "try" => self.add_token(TokenType::Try, None),
+            "catch" => self.add_token(TokenType::Catch, None),
             _ => self.add_token(TokenType::Identifier, Some(Object::Identifier(current_token)))
         }
     }
@@ -211,11 +346,12 @@ impl<'a> Lexer<'a> {
         while (!self.peek("\"")) && self.index < self.num_chars {
             self.consume_char();
             if self.peek("\n") {
-                self.line = self.line + 1;
+                self.new_line(self.index + 1)
             }
         }
-        self.add_token(TokenType::String, Some(Object::String(self.characters[self.lexeme_start..self.index].join(""))));
-        self.consume_char()
+        let string = self.characters[self.lexeme_start..self.index].join("");
+        self.consume_char();    // Closing quote
+        self.add_token(TokenType::String, Some(Object::String(string)))
     }
 
     /// Distinguishes division, single line comments, and multiline comments
@@ -226,6 +362,9 @@ impl<'a> Lexer<'a> {
             }
         } else if self.match_next("*") {    // Multi line comment
             while !(self.peek("*") && self.peek_another("/")) && self.index < self.num_chars {
+                if self.peek("\n") {
+                    self.new_line(self.index + 1)
+                }
                 self.consume_char()
             }
             self.consume_chars(2)
@@ -312,6 +451,15 @@ impl<'a> Lexer<'a> {
         !at_end && correct_char
     }
 
+    /// Gets the character at `index`, or an empty string if `index` is past the end of the source
+    fn char_at(&self, index: usize) -> &'a str {
+        if index < self.num_chars {
+            self.characters[index]
+        } else {
+            ""
+        }
+    }
+
     /// Advances the index by 1
     fn consume_char(&mut self) {
         self.index = self.index + 1;
@@ -322,8 +470,115 @@ impl<'a> Lexer<'a> {
         self.index = self.index + num;
     }
 
+    /// Keeps track of the line number. `line_start` is the index of the first character on the new line
+    fn new_line(&mut self, line_start: usize) {
+        self.line = self.line + 1;
+        self.line_start = line_start;
+    }
+
     /// Adds lexed token
     fn add_token(&mut self, token_type: TokenType, literal: Option<Object>) {
-        self.tokens.push(Token::new(token_type, self.line, literal))
+        // A string can span several lines, so it might start before the current line
+        let column = self.token_start.saturating_sub(self.line_start) + 1;
+        let length = self.index.saturating_sub(self.token_start).max(1);
+        self.tokens.push(Token::new(token_type, self.line, column, length, literal))
+    }
+}
+
+
+/// Scales `mantissa` by `10^exponent`, as written in scientific notation. `Decimal` only holds 28 decimal places
+/// and magnitudes up to about `7.9e28`, so the second element describes any precision that was lost. A number too
+/// small to keep any of its digits is an error
+pub fn apply_exponent(mantissa: Decimal, exponent: i64) -> Result<(Decimal, Option<String>), String> {
+    if mantissa.is_zero() {
+        return Ok((mantissa, None))
+    }
+
+    let mut number = mantissa;
+
+    if exponent >= 0 {
+        for _ in 0..exponent {
+            if let Some(x) = number.checked_mul(dec!(10)) {
+                number = x
+            } else {
+                let clamped = if mantissa.is_sign_negative() {Decimal::MIN} else {Decimal::MAX};
+                return Ok((clamped, Some(format!("{}e{exponent} is too large to be represented, using {clamped} instead", mantissa.normalize()))))
+            }
+        }
+        Ok((number, None))
+    } else {
+        for _ in 0..exponent.unsigned_abs() {
+            number = number / dec!(10);
+            if number.is_zero() {
+                break
+            }
+        }
+
+        if number.is_zero() {
+            Err(format!("{}e{exponent} is too small to be represented, numbers can only have 28 decimal places", mantissa.normalize()))
+        } else if (mantissa.normalize().scale() as i64) - exponent > 28 {
+            Ok((number, Some(format!("{}e{exponent} has more than 28 decimal places, so it was rounded to {}", mantissa.normalize(), number.normalize()))))
+        } else {
+            Ok((number, None))
+        }
     }
 }
+
+#[test]
+fn units() {
+    let units = |source| Lexer::lex(source, &mut |_, _| ()).unwrap().into_iter().filter(|token| token.token_type == TokenType::Unit).filter_map(|token| token.literal).collect::<Vec<_>>();
+    let unit = |text| Object::Quantity{value: Box::new(Object::Int(1)), unit: Unit::parse(text).unwrap()};
+
+    assert_eq!(units("9.81 m/s^2"), vec![unit("m/s^2")]);
+    assert_eq!(units("1 ± 0.1 kg*m^2/s^2"), vec![unit("kg*m^2/s^2")]);
+    assert_eq!(units("(1 ± 0.1) kg"), vec![]);
+    assert_eq!(units("if (1 > 0) s = 5"), vec![]);
+    assert_eq!(units("while (K < 3) K = K + 1"), vec![]);
+    assert_eq!(units("3s + 2km"), vec![unit("s"), unit("km")]);
+    assert_eq!(units("1.2 ± 0.1kg"), vec![unit("kg")]);
+    assert_eq!(units("2m/t"), vec![unit("m")]);
+    assert_eq!(units("2 m/t"), vec![]);
+    assert_eq!(units("2 m2"), vec![]);
+    assert_eq!(units("2\nm = 3"), vec![]);
+
+    // A single symbol after a space is a variable, not a unit
+    assert_eq!(units("if x > 3 s = 1"), vec![]);
+    assert_eq!(units("while i < 3 s = s + 1"), vec![]);
+    assert_eq!(units("print 3 h"), vec![]);
+}
+
+#[test]
+fn scientific_notation() {
+    use Object::*;
+
+    let literals = |source| Lexer::lex(source, &mut |_, _| ()).unwrap().into_iter().filter_map(|token| token.literal).collect::<Vec<_>>();
+    let identifier = |token: &Object, name: &str| if let Identifier(x) = token {x == name} else {false};
+
+    assert_eq!(literals("1.38E-23"), vec![Decimal(dec!(0.0000000000000000000000138))]);
+    assert_eq!(literals("6.022e23"), vec![Decimal(dec!(602200000000000000000000))]);
+    assert_eq!(literals("1e+3"), vec![Decimal(dec!(1000))]);
+    assert_eq!(literals("3.2e-3 ± 1e-4"), vec![Decimal(dec!(0.0032)), Decimal(dec!(0.0001))]);
+
+    // `e` is only an exponent if digits follow it
+    let tokens = literals("2e-x");
+    assert_eq!(tokens[0], Int(2));
+    assert!(identifier(&tokens[1], "e") && identifier(&tokens[2], "x"));
+
+    assert_eq!(apply_exponent(dec!(1.5), -28), Ok((dec!(0.0000000000000000000000000002), Some("1.5e-28 has more than 28 decimal places, so it was rounded to 0.0000000000000000000000000002".to_owned()))));
+    assert!(apply_exponent(dec!(1), 29).unwrap().1.is_some());
+
+    // A number that would lose all of its digits is an error
+    let errors = Lexer::lex("let h = 6.626e-34", &mut |_, _| ()).unwrap_err();
+    assert_eq!(errors[0].message, "6.626e-34 is too small to be represented, numbers can only have 28 decimal places");
+    assert_eq!(errors[0].span, Span::new(1, 9, 9));
+}
+
+#[test]
+fn token_spans() {
+    let spans = |source| Lexer::lex(source, &mut |_, _| ()).unwrap().into_iter().map(|token| token.span()).collect::<Vec<_>>();
+
+    assert_eq!(spans("let x = 9.81 m/s^2"), vec![
+        Span::new(1, 1, 3), Span::new(1, 5, 1), Span::new(1, 7, 1), Span::new(1, 9, 4), Span::new(1, 14, 5), Span::new(1, 19, 1),
+    ]);
+    assert_eq!(spans("/* a\nb */\n  print \"hi\"")[..2], [Span::new(3, 3, 5), Span::new(3, 9, 4)]);
+}
@@ -1,42 +1,147 @@
 use std::collections::HashMap;
 
+use hawk_common::error::HawkError;
 use hawk_common::object::Object;
 use hawk_common::token::{Token, TokenType};
-use hawk_common::tree::{Expression, Statement};
+use hawk_common::tree::{Expression, Iterable, Statement};
 
 use hawk_common::token::UserPrint;
 
-pub struct Parser {
+pub struct Parser<'a> {
     tokens: Vec<Token>,
     index: usize,
-    warn: fn(String, usize) -> ()
+    warn: &'a mut dyn FnMut(String, usize),
+    /// Syntax errors found so far. Parsing carries on after an error so that they can all be reported at once
+    errors: Vec<HawkError>,
 }
 
-impl Parser {
-    /// Constructs a `Parser` and parses a vec of tokens
-    pub fn parse(tokens: &Vec<Token>, warn: fn(String, usize) -> ()) -> Result<Vec<Statement>, (String, usize)> {
+impl<'a> Parser<'a> {
+    /// Constructs a `Parser` and parses a vec of tokens, returning every syntax error if there are any
+    pub fn parse(tokens: &Vec<Token>, warn: &'a mut dyn FnMut(String, usize)) -> Result<Vec<Statement>, Vec<HawkError>> {
         let mut parser = Parser {
             tokens: tokens.clone(),
             index: 0,
-            warn
+            warn,
+            errors: Vec::new(),
         };
         let mut statements: Vec<Statement> = Vec::new();
 
         while !parser.at_end() {
-            let start_line = parser.current().line;
+            let start = parser.index;
+            statements.push(Statement::Line(parser.current().line));
 
-            statements.push(parser.statement()?);
+            match parser.statement() {
+                Ok(statement) => statements.push(statement),
+                Err(error) => parser.recover(error, start),
+            }
+        }
+
+        if parser.errors.is_empty() {
+            Ok(statements)
+        } else {
+            Err(parser.errors)
+        }
+    }
+
+    /// Records a syntax error in the statement starting at token `start`, and skips to the next statement
+    fn recover(&mut self, error: HawkError, start: usize) {
+        self.errors.push(error);
+        self.synchronize(start);
+
+        // Always make progress, e.g. past a stray `}`
+        if self.index <= start {
+            self.index = start + 1
+        }
+    }
+
+    /// After a syntax error, skips ahead to the start of the next statement: the first token on a later line, once
+    /// any brackets opened since the start of the statement are closed, so that a mistake inside a block or an array
+    /// written over several lines only gives one error. A statement keyword at the start of a line, or a line indented
+    /// no further than the statement, also starts a new statement, in case the mistake was a bracket that was never
+    /// closed
+    fn synchronize(&mut self, start: usize) {
+        let error_line = self.previous().line;
+        let start_column = self.tokens[start].column;
+        let opened = &self.tokens[start..self.index.min(self.tokens.len())];
+        let mut brackets: i32 = opened.iter().map(|token| Self::nesting(token.token_type)).sum();
+        let mut braces: i32 = opened.iter().map(|token| Self::brace_nesting(token.token_type)).sum();
+
+        while !self.at_end() {
+            let token = self.current();
+            let starts_line = token.line > self.previous().line && token.line > error_line;
+            // Only braces make blocks, so a keyword in an unclosed block is still inside it
+            let closes_block = token.token_type == TokenType::BraceRight && braces > 0;
+            if starts_line
+                && ((braces <= 0 && (brackets <= 0 || Self::starts_statement(token.token_type)))
+                    || (token.column <= start_column && !closes_block))
+            {
+                break
+            }
 
-            let mut end_line = parser.current().line;
-            while end_line > start_line {
-                statements.push(Statement::Line);
-                end_line -= 1;
+            brackets = (brackets + Self::nesting(token.token_type)).max(0);
+            braces += Self::brace_nesting(token.token_type);
+            if braces < 0 {
+                // The `}` at the end of the block the statement is in
+                break
             }
+            self.consume()
+        }
+    }
+
+    /// Describes a token for an error message, e.g. the name of an identifier or `'='`
+    fn describe(token: &Token) -> Result<String, HawkError> {
+        match &token.literal {
+            Some(literal) => literal.user_print(token.line),
+            None => Ok(format!("'{}'", token.token_type.user_print())),
         }
-        Ok(statements)
     }
 
-    fn statement(&mut self) -> Result<Statement, (String, usize)> {
+    fn warn(&mut self, message: String, line: usize) {
+        (self.warn)(message, line)
+    }
+
+    /// How a token changes how deeply nested in parentheses or square brackets the code is
+    fn nesting(token_type: TokenType) -> i32 {
+        match token_type {
+            TokenType::ParenthesisLeft | TokenType::BracketLeft => 1,
+            TokenType::ParenthesisRight | TokenType::BracketRight => -1,
+            _ => 0,
+        }
+    }
+
+    /// How a token changes how deeply nested in blocks or records the code is
+    fn brace_nesting(token_type: TokenType) -> i32 {
+        match token_type {
+            TokenType::BraceLeft => 1,
+            TokenType::BraceRight => -1,
+            _ => 0,
+        }
+    }
+
+    /// Whether a token always starts a statement, so it can't be part of an expression
+    fn starts_statement(token_type: TokenType) -> bool {
+        matches!(
+            token_type,
+            TokenType::Print
+                | TokenType::Let
+                | TokenType::If
+                | TokenType::Loop
+                | TokenType::Break
+                | TokenType::Continue
+                | TokenType::For
+                | TokenType::While
+                | TokenType::Function
+                | TokenType::Class
+                | TokenType::Return
+                | TokenType::Import
+                | TokenType::Process
+                | TokenType::Finder
+                | TokenType::Expect
+                | TokenType::Try
+        )
+    }
+
+    fn statement(&mut self) -> Result<Statement, HawkError> {
         self.consume();
         match self.previous().token_type {
             TokenType::Print => Ok(Statement::Print(self.expression()?)),
@@ -45,21 +150,29 @@ impl Parser {
             TokenType::If => self.parse_if(),
             TokenType::Loop => self.parse_loop(),
             TokenType::Break => Ok(Statement::Break),
+            TokenType::Continue => Ok(Statement::Continue),
+            TokenType::For => self.parse_for_loop(),
             TokenType::While => self.parse_while_loop(),
             TokenType::BraceLeft => self.parse_block(),
             TokenType::Function => self.parse_function(),
+            TokenType::Class => self.parse_class(),
             TokenType::Return => Ok(Statement::Return(self.expression()?)),
             TokenType::Import => Ok(Statement::Import(self.expression()?)),
             TokenType::Process => self.parse_process_block(),
             TokenType::Finder => self.parse_finder(),
+            TokenType::Expect => self.parse_expect(),
+            TokenType::Try => self.parse_try(),
             _ => self.parse_other(),
         }
     }
 
-    fn parse_other(&mut self) -> Result<Statement, (String, usize)> {
+    fn parse_other(&mut self) -> Result<Statement, HawkError> {
         match self.current().token_type {
             TokenType::Assign => self.parse_assignment(),
             TokenType::BracketLeft => self.parse_array_assign(),
+            TokenType::Dot if matches!(self.tokens.get(self.index + 2), Some(token) if token.token_type == TokenType::Assign) => {
+                self.parse_field_assign()
+            },
             _ => {
                 self.index -= 1;
                 Ok(Statement::Expression(self.expression()?))
@@ -67,7 +180,40 @@ impl Parser {
         }
     }
 
-    fn parse_finder(&mut self) -> Result<Statement, (String, usize)> {
+    fn parse_expect(&mut self) -> Result<Statement, HawkError> {
+        let condition = self.expression()?;
+        let message = if self.current().token_type == TokenType::Comma {
+            self.consume();
+            Some(self.expression()?)
+        } else {
+            None
+        };
+        Ok(Statement::Expect{condition, message})
+    }
+
+    /// Parses `try { ... } catch error { ... }`, where the name of the error is optional
+    fn parse_try(&mut self) -> Result<Statement, HawkError> {
+        let block = Box::new(self.statement()?);
+
+        if !self.expect(TokenType::Catch) {
+            return Err(HawkError::syntax(
+                format!("Expected 'catch' after try block, found {}", self.previous().token_type.user_print()),
+                self.previous().span(),
+            ))
+        }
+
+        let error = if let (TokenType::Identifier, Some(Object::Identifier(name))) = (self.current().token_type, self.current().literal) {
+            self.consume();
+            Some(name)
+        } else {
+            None
+        };
+
+        let catch = Box::new(self.statement()?);
+        Ok(Statement::Try { block, error, catch })
+    }
+
+    fn parse_finder(&mut self) -> Result<Statement, HawkError> {
         if let Some(Object::Identifier(identifier)) = self.current().literal {
             self.consume();
             let mut equations: Vec<(Expression, Expression)> = Vec::new();
@@ -76,20 +222,20 @@ impl Parser {
                 self.consume();
                 while !(self.current().token_type == TokenType::BraceRight) {
                     if !self.expect(TokenType::Equation) {
-                        return Err(("Expected 'equation'".to_string(), self.current().line));
+                        return Err(HawkError::syntax("Expected 'equation'".to_string(), self.current().span()));
                     }
                     let lhs = self.expression()?;
                     if !self.expect(TokenType::Assign) {
-                        return Err(("Expected '='".to_string(), self.current().line));
+                        return Err(HawkError::syntax("Expected '='".to_string(), self.current().span()));
                     }
                     let rhs = self.expression()?;
                     equations.push((*lhs, *rhs));
                 }
                 self.consume();
             } else {
-                return Err((
+                return Err(HawkError::syntax(
                     "Expected curly brace after ".to_string(),
-                    self.current().line,
+                    self.current().span(),
                 ));
             }
             Ok(Statement::Finder {
@@ -97,24 +243,21 @@ impl Parser {
                 equations,
             })
         } else {
-            Err((format!("Finder needs identifier"), self.current().line))
+            Err(HawkError::syntax("Finder needs identifier".to_string(), self.current().span()))
         }
     }
 
-    fn parse_assignment(&mut self) -> Result<Statement, (String, usize)> {
+    fn parse_assignment(&mut self) -> Result<Statement, HawkError> {
         let name: String;
         if let Some(Object::Identifier(x)) = self.previous().literal {
             name = x
         } else {
-            return Err((
+            return Err(HawkError::syntax(
                 format!(
                     "Expected identifier as left hand side of assignment, found {}",
-                    self.previous()
-                        .literal
-                        .unwrap()
-                        .user_print(self.previous().line)?
+                    Self::describe(&self.previous())?
                 ),
-                self.previous().line,
+                self.previous().span(),
             ));
         }
         self.consume();
@@ -122,20 +265,29 @@ impl Parser {
         Ok(Statement::Definition { name, value })
     }
 
-    fn parse_array_assign(&mut self) -> Result<Statement, (String, usize)> {
+    fn parse_field_assign(&mut self) -> Result<Statement, HawkError> {
+        if let (Some(Object::Identifier(object)), Some(Object::Identifier(field))) = (self.previous().literal, self.next().literal) {
+            self.consume();
+            self.consume();
+            self.consume();
+            let value = self.expression()?;
+            Ok(Statement::FieldAssign { object, field, value })
+        } else {
+            Err(HawkError::syntax("Expected object and field name before '='".to_string(), self.current().span()))
+        }
+    }
+
+    fn parse_array_assign(&mut self) -> Result<Statement, HawkError> {
         let name: String;
         if let Some(Object::Identifier(x)) = self.previous().literal {
             name = x
         } else {
-            return Err((
+            return Err(HawkError::syntax(
                 format!(
                     "Expected identifier for array assignment, found {}",
-                    self.previous()
-                        .literal
-                        .unwrap()
-                        .user_print(self.previous().line)?
+                    Self::describe(&self.previous())?
                 ),
-                self.previous().line,
+                self.previous().span(),
             ));
         }
         self.consume();
@@ -147,25 +299,22 @@ impl Parser {
         Ok(Statement::ArrayAssign { name, idx, value })
     }
 
-    fn parse_loop(&mut self) -> Result<Statement, (String, usize)> {
+    fn parse_loop(&mut self) -> Result<Statement, HawkError> {
         let block = Box::new(self.statement()?);
         Ok(Statement::Loop(block))
     }
 
-    fn parse_let(&mut self) -> Result<Statement, (String, usize)> {
+    fn parse_let(&mut self) -> Result<Statement, HawkError> {
         let name: String;
         if let Some(Object::Identifier(x)) = self.current().literal {
             name = x
         } else {
-            return Err((
+            return Err(HawkError::syntax(
                 format!(
                     "Expected identifier after 'let', found {}",
-                    self.previous()
-                        .literal
-                        .unwrap()
-                        .user_print(self.previous().line)?
+                    Self::describe(&self.current())?
                 ),
-                self.previous().line,
+                self.current().span(),
             ));
         }
         self.consume();
@@ -179,7 +328,7 @@ impl Parser {
         Ok(Statement::Definition { name, value })
     }
 
-    fn parse_if(&mut self) -> Result<Statement, (String, usize)> {
+    fn parse_if(&mut self) -> Result<Statement, HawkError> {
         let condition = self.expression()?;
         let block = Box::new(self.statement()?);
 
@@ -196,7 +345,7 @@ impl Parser {
         }
     }
 
-    fn parse_while_loop(&mut self) -> Result<Statement, (String, usize)> {
+    fn parse_while_loop(&mut self) -> Result<Statement, HawkError> {
         let condition = self.expression()?;
         let block = self.statement()?;
         Ok(Statement::While {
@@ -205,23 +354,84 @@ impl Parser {
         })
     }
 
-    fn parse_block(&mut self) -> Result<Statement, (String, usize)> {
+    fn parse_for_loop(&mut self) -> Result<Statement, HawkError> {
+        let mut variables: Vec<String> = Vec::new();
+        let unpack = self.current().token_type == TokenType::ParenthesisLeft;
+        if unpack {
+            self.consume();
+        }
+
+        loop {
+            if let Some(Object::Identifier(variable)) = self.current().literal {
+                variables.push(variable);
+                self.consume();
+            } else {
+                return Err(HawkError::syntax(format!("Expected identifier after 'for', found {}", self.current().token_type.user_print()), self.current().span()))
+            }
+
+            if unpack && self.current().token_type == TokenType::Comma {
+                self.consume();
+            } else {
+                break
+            }
+        }
+
+        if unpack && !self.expect(TokenType::ParenthesisRight) {
+            return Err(HawkError::syntax("Expected ')' after loop variables".to_string(), self.previous().span()))
+        }
+        if !self.expect(TokenType::In) {
+            return Err(HawkError::syntax("Expected 'in' after loop variables".to_string(), self.previous().span()))
+        }
+
+        let start = self.expression()?;
+        let iterable = if self.current().token_type == TokenType::DotDot {
+            self.consume();
+            let end = self.expression()?;
+
+            // `step` isn't a keyword, so that it can still be used as a variable name
+            let step = if matches!(&self.current().literal, Some(Object::Identifier(word)) if word == "step") {
+                self.consume();
+                Some(self.expression()?)
+            } else {
+                None
+            };
+            Iterable::Range { start, end, step }
+        } else {
+            Iterable::Collection(start)
+        };
+
+        let block = Box::new(self.statement()?);
+        Ok(Statement::For { variables, iterable, block })
+    }
+
+    fn parse_block(&mut self) -> Result<Statement, HawkError> {
+        let brace = self.previous();
         let mut block: Vec<Statement> = Vec::new();
         let mut in_block = true;
 
         while in_block {
-            if let TokenType::BraceRight = self.current().token_type {
-                in_block = false;
-                self.consume();
-            } else {
-                block.push(self.statement()?)
+            match self.current().token_type {
+                TokenType::BraceRight => {
+                    in_block = false;
+                    self.consume();
+                },
+                TokenType::EOF => return Err(HawkError::syntax("Expected '}' to close this block".to_string(), brace.span())),
+                _ => {
+                    // Errors in the block are recorded, and the rest of the block is still parsed
+                    let start = self.index;
+                    block.push(Statement::Line(self.current().line));
+                    match self.statement() {
+                        Ok(statement) => block.push(statement),
+                        Err(error) => self.recover(error, start),
+                    }
+                },
             }
         }
 
         Ok(Statement::Block(block))
     }
 
-    fn parse_process_block(&mut self) -> Result<Statement, (String, usize)> {
+    fn parse_process_block(&mut self) -> Result<Statement, HawkError> {
         let readfile = self.expression()?;
         let writefile = self.expression()?;
 
@@ -233,34 +443,83 @@ impl Parser {
         })
     }
 
-    fn parse_function(&mut self) -> Result<Statement, (String, usize)> {
+    fn parse_class(&mut self) -> Result<Statement, HawkError> {
+        let identifier = if let Some(Object::Identifier(identifier)) = self.current().literal {
+            self.consume();
+            identifier
+        } else {
+            return Err(HawkError::syntax("Expected class name after 'class'".to_string(), self.current().span()))
+        };
+
+        // The class to inherit from is given in parentheses, e.g. `class DampedPendulum(Pendulum)`
+        let parent = if let TokenType::ParenthesisLeft = self.current().token_type {
+            self.consume();
+            if let (Some(Object::Identifier(parent)), TokenType::ParenthesisRight) = (self.current().literal, self.next().token_type) {
+                self.consume();
+                self.consume();
+                Some(parent)
+            } else {
+                return Err(HawkError::syntax("Expected the name of a class to inherit from in parentheses".to_string(), self.current().span()))
+            }
+        } else {
+            None
+        };
+
+        if !self.expect(TokenType::BraceLeft) {
+            return Err(HawkError::syntax(format!("Expected '{{' after class {identifier}"), self.previous().span()))
+        }
+
+        let mut fields: Vec<(String, Expression)> = Vec::new();
+        let mut methods: Vec<Statement> = Vec::new();
+        while self.current().token_type != TokenType::BraceRight {
+            if self.at_end() {
+                return Err(HawkError::syntax(format!("Expected '}}' at the end of class {identifier}"), self.current().span()))
+            }
+            match self.statement()? {
+                Statement::Definition { name, value } => fields.push((name, *value)),
+                method @ Statement::Function { .. } => methods.push(method),
+                _ => return Err(HawkError::syntax("Classes can only contain fields and methods".to_string(), self.previous().span())),
+            }
+        }
+        self.consume();
+
+        Ok(Statement::Class { identifier, parent, fields, methods })
+    }
+
+    fn parse_function(&mut self) -> Result<Statement, HawkError> {
         if let Some(Object::Identifier(identifier)) = self.current().literal {
             self.consume();
             if let TokenType::ParenthesisLeft = self.current().token_type {
+                let paren = self.current();
                 self.consume();
                 let mut params: Vec<String> = Vec::new();
                 if let TokenType::ParenthesisRight = self.current().token_type {
                     self.consume();
                 } else {
-                    while let TokenType::Comma | TokenType::ParenthesisRight =
-                        self.next().token_type
-                    {
+                    loop {
                         if let Some(Object::Identifier(identifier)) = self.current().literal {
-                            params.push(identifier)
+                            params.push(identifier);
+                            self.consume();
+                        } else if self.at_end() || self.at_statement() || self.current().token_type == TokenType::BraceLeft {
+                            return Err(self.unclosed_error("')' to close the parameters", &paren));
                         } else {
-                            return Err((
+                            return Err(HawkError::syntax(
                                 format!(
                                     "Expected identifier as function parameter, found {}",
-                                    self.current()
-                                        .literal
-                                        .unwrap()
-                                        .user_print(self.current().line)?
+                                    Self::describe(&self.current())?
                                 ),
-                                self.current().line,
+                                self.current().span(),
                             ));
                         }
-                        self.consume();
-                        self.consume()
+
+                        match self.current().token_type {
+                            TokenType::Comma => self.consume(),
+                            TokenType::ParenthesisRight => {
+                                self.consume();
+                                break
+                            },
+                            _ => return Err(self.unclosed_error("')' to close the parameters", &paren)),
+                        }
                     }
                 }
                 let block = Box::new(self.statement()?);
@@ -270,21 +529,21 @@ impl Parser {
                     block,
                 })
             } else {
-                Err((
-                    format!("Expected parentheses after function identifier"),
-                    self.current().line,
+                Err(HawkError::syntax(
+                    "Expected parentheses after function identifier".to_string(),
+                    self.current().span(),
                 ))
             }
         } else {
-            Err((format!("Function needs identifier"), self.current().line))
+            Err(HawkError::syntax("Function needs identifier".to_string(), self.current().span()))
         }
     }
 
-    fn expression(&mut self) -> Result<Box<Expression>, (String, usize)> {
+    fn expression(&mut self) -> Result<Box<Expression>, HawkError> {
         self.or()
     }
 
-    fn or(&mut self) -> Result<Box<Expression>, (String, usize)> {
+    fn or(&mut self) -> Result<Box<Expression>, HawkError> {
         let mut temp = self.and()?;
 
         while let TokenType::Or = self.current().token_type {
@@ -302,7 +561,7 @@ impl Parser {
         Ok(temp)
     }
 
-    fn and(&mut self) -> Result<Box<Expression>, (String, usize)> {
+    fn and(&mut self) -> Result<Box<Expression>, HawkError> {
         let mut temp = self.equality()?;
 
         while let TokenType::And = self.current().token_type {
@@ -320,7 +579,7 @@ impl Parser {
         Ok(temp)
     }
 
-    fn equality(&mut self) -> Result<Box<Expression>, (String, usize)> {
+    fn equality(&mut self) -> Result<Box<Expression>, HawkError> {
         let mut temp = self.comparison()?;
 
         while let TokenType::EqualEqual | TokenType::NotEqual = self.current().token_type {
@@ -338,7 +597,7 @@ impl Parser {
         Ok(temp)
     }
 
-    fn comparison(&mut self) -> Result<Box<Expression>, (String, usize)> {
+    fn comparison(&mut self) -> Result<Box<Expression>, HawkError> {
         let mut temp = self.term()?;
 
         while let TokenType::LessThan
@@ -360,7 +619,7 @@ impl Parser {
         Ok(temp)
     }
 
-    fn term(&mut self) -> Result<Box<Expression>, (String, usize)> {
+    fn term(&mut self) -> Result<Box<Expression>, HawkError> {
         let mut temp = self.factor()?;
 
         while let TokenType::Minus | TokenType::Plus = self.current().token_type {
@@ -378,7 +637,7 @@ impl Parser {
         Ok(temp)
     }
 
-    fn factor(&mut self) -> Result<Box<Expression>, (String, usize)> {
+    fn factor(&mut self) -> Result<Box<Expression>, HawkError> {
         let mut temp = self.power()?;
 
         while let TokenType::Slash | TokenType::Asterisk = self.current().token_type {
@@ -396,7 +655,7 @@ impl Parser {
         Ok(temp)
     }
 
-    fn power(&mut self) -> Result<Box<Expression>, (String, usize)> {
+    fn power(&mut self) -> Result<Box<Expression>, HawkError> {
         let mut temp = self.uncertainty()?;
 
         while let TokenType::Caret = self.current().token_type {
@@ -414,7 +673,7 @@ impl Parser {
         Ok(temp)
     }
 
-    fn uncertainty(&mut self) -> Result<Box<Expression>, (String, usize)> {
+    fn uncertainty(&mut self) -> Result<Box<Expression>, HawkError> {
         let mut temp = self.unary()?;
 
         while let TokenType::PlusMinus = self.current().token_type {
@@ -432,42 +691,77 @@ impl Parser {
         Ok(temp)
     }
 
-    fn unary(&mut self) -> Result<Box<Expression>, (String, usize)> {
+    fn unary(&mut self) -> Result<Box<Expression>, HawkError> {
         if let TokenType::Not | TokenType::Minus = self.current().token_type {
             let operator = self.current().token_type;
             self.consume();
             let operand = self.unary()?;
             Ok(Box::new(Expression::Unary { operator, operand }))
         } else {
-            self.primary()
+            let operand = self.primary()?;
+            let operand = self.parse_postfix(operand)?;
+            self.parse_unit(operand)
+        }
+    }
+
+    /// Multiplies by a unit written after a number, e.g. `9.81 m/s^2` becomes `9.81 * (1 m/s^2)`
+    fn parse_unit(&mut self, operand: Box<Expression>) -> Result<Box<Expression>, HawkError> {
+        if let TokenType::Unit = self.current().token_type {
+            let unit = self.parse_literal()?;
+            Ok(Box::new(Expression::Binary {
+                operand1: operand,
+                operator: TokenType::Asterisk,
+                operand2: unit,
+            }))
+        } else {
+            Ok(operand)
         }
     }
 
-    fn primary(&mut self) -> Result<Box<Expression>, (String, usize)> {
+    fn primary(&mut self) -> Result<Box<Expression>, HawkError> {
         if let TokenType::Int
         | TokenType::Decimal
         | TokenType::True
         | TokenType::False
         | TokenType::String
-        | TokenType::Identifier = self.current().token_type
+        | TokenType::Identifier
+        | TokenType::This
+        | TokenType::Super = self.current().token_type
         {
-            if let TokenType::ParenthesisLeft = self.next().token_type {
+            if let TokenType::FatArrow = self.next().token_type {
+                self.parse_lambda()
+            } else if let TokenType::ParenthesisLeft = self.next().token_type {
                 self.parse_functioncall()
             } else if let TokenType::BracketLeft = self.next().token_type {
                 self.parse_array_index()
-            } else if let TokenType::Dot = self.next().token_type {
-                self.parse_methodcall()
             } else {
                 self.parse_literal()
             }
+        } else if self.at_lambda() {
+            self.parse_lambda()
         } else if let TokenType::ParenthesisLeft = self.current().token_type {
             self.parse_parenthesized()
         } else if let TokenType::BracketLeft = self.current().token_type {
             self.parse_array_literal()
+        } else if let TokenType::BraceLeft = self.current().token_type {
+            self.parse_record_literal()
         } else if let TokenType::Find = self.current().token_type {
             self.parse_finder_call()
+        } else if self.at_end() || self.at_statement() || Self::nesting(self.current().token_type) + Self::brace_nesting(self.current().token_type) < 0 {
+            // Nothing is left of the expression, so don't take the token away from what it belongs to
+            if self.current().line > self.previous().line {
+                Err(HawkError::syntax(
+                    format!("Expected an expression after {}", Self::describe(&self.previous())?),
+                    self.previous().span(),
+                ))
+            } else {
+                Err(HawkError::syntax(
+                    format!("Expected an expression, found {}", Self::describe(&self.current())?),
+                    self.current().span(),
+                ))
+            }
         } else {
-            (self.warn)(
+            self.warn(
                 format!(
                     "Unexpected token '{}'",
                     self.current().token_type.user_print()
@@ -479,112 +773,183 @@ impl Parser {
         }
     }
 
-    fn parse_array_literal(&mut self) -> Result<Box<Expression>, (String, usize)> {
+    fn parse_array_literal(&mut self) -> Result<Box<Expression>, HawkError> {
+        let bracket = self.current();
         self.consume();
+        let items = self.parse_list(TokenType::BracketRight, "']' to close this array", &bracket)?;
+        Ok(Box::new(Expression::Array(items)))
+    }
+
+    /// Parses expressions separated by commas, up to and including the `closing` token, e.g. the arguments of a
+    /// function call. If the list isn't closed, the error points at the `opening` bracket
+    // Arrays and calls keep their expressions boxed in the tree
+    #[allow(clippy::vec_box)]
+    fn parse_list(&mut self, closing: TokenType, expected: &str, opening: &Token) -> Result<Vec<Box<Expression>>, HawkError> {
         let mut items: Vec<Box<Expression>> = Vec::new();
 
-        while self.previous().token_type != TokenType::BracketRight {
-            items.push(self.expression()?);
+        loop {
+            self.expect_not_end(expected, opening)?;
+            if self.current().token_type == closing {
+                self.consume();
+                return Ok(items)
+            }
+            items.push(self.expression().map_err(|error| self.unclosed(error, expected, opening))?);
+
+            match self.current().token_type {
+                TokenType::Comma => self.consume(),
+                token_type if token_type == closing => {
+                    self.consume();
+                    return Ok(items)
+                },
+                _ => return Err(self.unclosed_error(expected, opening)),
+            }
+        }
+    }
+
+    /// Parses a record with named fields, e.g. `{x: 3, y: 2}`. Blocks are statements, so a brace in an expression
+    /// always starts a record
+    fn parse_record_literal(&mut self) -> Result<Box<Expression>, HawkError> {
+        let brace = self.current();
+        self.consume();
+        let mut fields: Vec<(String, Box<Expression>)> = Vec::new();
+
+        while self.previous().token_type != TokenType::BraceRight {
+            self.expect_not_end("'}' to close this record", &brace)?;
+            if let Some(Object::Identifier(name)) = self.current().literal {
+                if self.next().token_type != TokenType::Colon {
+                    return Err(HawkError::syntax(format!("Expected ':' after field {name}"), self.next().span()))
+                }
+                self.consume();
+                self.consume();
+                fields.push((name, self.expression()?));
+            }
 
             self.consume()
         }
-        Ok(Box::new(Expression::Array(items)))
+        Ok(Box::new(Expression::Record(fields)))
     }
 
-    fn parse_parenthesized(&mut self) -> Result<Box<Expression>, (String, usize)> {
+    /// Checks for parameters in parentheses followed by `=>`, which start a lambda rather than a parenthesized
+    /// expression
+    fn at_lambda(&self) -> bool {
+        if self.current().token_type != TokenType::ParenthesisLeft {
+            return false
+        }
+        let mut index = self.index + 1;
+        while let Some(token) = self.tokens.get(index) {
+            match token.token_type {
+                TokenType::Identifier | TokenType::Comma => index += 1,
+                TokenType::ParenthesisRight => {
+                    return matches!(self.tokens.get(index + 1), Some(token) if token.token_type == TokenType::FatArrow)
+                },
+                _ => return false
+            }
+        }
+        false
+    }
+
+    /// Parses a lambda, either `x => body` or `(x, y) => body`. The body is either an expression or a block
+    fn parse_lambda(&mut self) -> Result<Box<Expression>, HawkError> {
+        let mut params: Vec<String> = Vec::new();
+        if let TokenType::ParenthesisLeft = self.current().token_type {
+            self.consume();
+            while self.current().token_type != TokenType::ParenthesisRight {
+                if let Some(Object::Identifier(param)) = self.current().literal {
+                    params.push(param)
+                }
+                self.consume()
+            }
+        } else if let Some(Object::Identifier(param)) = self.current().literal {
+            params.push(param)
+        }
         self.consume();
-        let expression = self.expression()?;
+        self.consume();
+
+        let body = if let TokenType::BraceLeft = self.current().token_type {
+            self.statement()?
+        } else {
+            Statement::Return(self.expression()?)
+        };
+
+        Ok(Box::new(Expression::Lambda { params, body: Box::new(body) }))
+    }
+
+    fn parse_parenthesized(&mut self) -> Result<Box<Expression>, HawkError> {
+        let paren = self.current();
+        self.consume();
+        let expression = self.expression()
+            .map_err(|error| self.unclosed(error, "')' to close this parenthesis", &paren))?;
 
         if let TokenType::ParenthesisRight = self.current().token_type {
             self.consume();
         } else {
-            return Err((
-                format!(
-                    "Expected closing parenthesis, instead found {}",
-                    self.current().token_type
-                ),
-                self.current().line,
-            ));
+            return Err(self.unclosed_error("')' to close this parenthesis", &paren));
         }
 
         Ok(expression)
     }
 
-    fn parse_literal(&mut self) -> Result<Box<Expression>, (String, usize)> {
+    fn parse_literal(&mut self) -> Result<Box<Expression>, HawkError> {
         if let Some(x) = self.current().literal {
             self.consume();
             Ok(Box::new(Expression::Literal(x)))
         } else {
-            Err((format!("Couldn't parse literal"), self.current().line))
+            Err(HawkError::syntax("Couldn't parse literal".to_string(), self.current().span()))
         }
     }
 
-    fn parse_methodcall(&mut self) -> Result<Box<Expression>, (String, usize)> {
-        if let Some(Object::Identifier(identifier)) = self.current().literal {
-            if let Some(Object::Identifier(methodname)) =
-                self.tokens[self.index + 2].literal.clone()
-            {
-                self.consume();
-                self.consume();
+    /// Parses method calls (`.method(args)`) and getting fields (`.field`) after an expression, which can be any
+    /// primary expression, e.g. `"a,b".split(",")` or `propagate(f, x).value`
+    fn parse_postfix(&mut self, mut object: Box<Expression>) -> Result<Box<Expression>, HawkError> {
+        while let TokenType::Dot = self.current().token_type {
+            self.consume();
+            if let Some(Object::Identifier(name)) = self.current().literal {
                 self.consume();
-                let mut args: Vec<Box<Expression>> = Vec::new();
-
-                while self.previous().token_type != TokenType::ParenthesisRight {
-                    args.push(self.expression()?);
-
-                    self.consume()
-                }
-                Ok(Box::new(Expression::MethodCall {
-                    object: identifier,
-                    method: methodname,
-                    args,
-                }))
+                object = if let TokenType::ParenthesisLeft = self.current().token_type {
+                    let paren = self.current();
+                    self.consume();
+                    let args = self.parse_list(TokenType::ParenthesisRight, "')' to close the arguments", &paren)?;
+                    Box::new(Expression::MethodCall { object, method: name, args })
+                } else {
+                    Box::new(Expression::Field { object, field: name })
+                };
             } else {
-                Err((
-                    format!(
-                        "Expected method name, instead found {}",
-                        self.tokens[self.index + 2]
-                            .literal
-                            .clone()
-                            .unwrap()
-                            .user_print(self.current().line)?
-                    ),
-                    self.current().line,
+                return Err(HawkError::syntax(
+                    format!("Expected method or field name after '.', instead found {}", self.current().token_type.user_print()),
+                    self.current().span(),
                 ))
             }
-        } else {
-            Err((
-                format!("Expected object identifier, found {}", self.current()),
-                self.current().line,
-            ))
         }
+        Ok(object)
     }
 
-    fn parse_array_index(&mut self) -> Result<Box<Expression>, (String, usize)> {
+    fn parse_array_index(&mut self) -> Result<Box<Expression>, HawkError> {
         if let Some(Object::Identifier(identifier)) = self.current().literal {
             self.consume();
             self.consume();
             let index = self.expression()?;
             if !(TokenType::BracketRight == self.current().token_type) {
-                (self.warn)(format!("Hawk respects your freedom, so using {} is fine, but consider using a ']' to end array index.", self.current().token_type.user_print()), self.current().line);
+                self.warn(format!("Hawk respects your freedom, so using {} is fine, but consider using a ']' to end array index.", self.current().token_type.user_print()), self.current().line);
             }
             self.consume();
             Ok(Box::new(Expression::ArrayIndex { identifier, index }))
         } else {
-            Err((format!("Couldn't get array index"), self.current().line))
+            Err(HawkError::syntax("Couldn't get array index".to_string(), self.current().span()))
         }
     }
 
-    fn parse_finder_call(&mut self) -> Result<Box<Expression>, (String, usize)> {
+    fn parse_finder_call(&mut self) -> Result<Box<Expression>, HawkError> {
         self.consume();
         if let Some(Object::Identifier(identifier)) = self.current().literal {
             self.consume();
+            let paren = self.current();
             self.consume();
 
             let mut given: HashMap<String, Expression> = HashMap::new();
             let mut to_find = String::new();
 
             while self.previous().token_type != TokenType::ParenthesisRight {
+                self.expect_not_end("')' to close the finder call", &paren)?;
                 if let Some(Object::Identifier(var)) = self.current().literal {
                     if self.next().token_type == TokenType::QuestionMark {
                         to_find = var;
@@ -607,33 +972,24 @@ impl Parser {
                 to_find,
             }))
         } else {
-            Err((
-                format!("Couldn't get finder parameters"),
-                self.current().line,
+            Err(HawkError::syntax(
+                "Couldn't get finder parameters".to_string(),
+                self.current().span(),
             ))
         }
     }
 
-    fn parse_functioncall(&mut self) -> Result<Box<Expression>, (String, usize)> {
+    fn parse_functioncall(&mut self) -> Result<Box<Expression>, HawkError> {
         if let Some(Object::Identifier(identifier)) = self.current().literal {
             self.consume();
+            let paren = self.current();
             self.consume();
-            let mut args: Vec<Box<Expression>> = Vec::new();
-
-            if !(self.current().token_type == TokenType::ParenthesisRight) {
-                while self.previous().token_type != TokenType::ParenthesisRight {
-                    args.push(self.expression()?);
-
-                    self.consume()
-                }
-            } else {
-                self.consume()
-            }
+            let args = self.parse_list(TokenType::ParenthesisRight, "')' to close the arguments", &paren)?;
             Ok(Box::new(Expression::FunctionCall { identifier, args }))
         } else {
-            Err((
-                format!("Couldn't get function parameters"),
-                self.current().line,
+            Err(HawkError::syntax(
+                "Couldn't get function parameters".to_string(),
+                self.current().span(),
             ))
         }
     }
@@ -643,18 +999,52 @@ impl Parser {
             || self.current().token_type == TokenType::EOF
     }
 
+    /// Gets the token at the current index. After a syntax error the index can be past the end, so this gives the
+    /// final `EOF` token instead
     fn current(&self) -> Token {
-        self.tokens[self.index].clone()
+        self.tokens[self.index.min(self.tokens.len() - 1)].clone()
     }
 
     fn previous(&self) -> Token {
-        self.tokens[self.index - 1].clone()
+        self.tokens[self.index.saturating_sub(1).min(self.tokens.len() - 1)].clone()
     }
 
     fn next(&self) -> Token {
         self.tokens[self.index + 1].clone()
     }
 
+    /// Gives an error pointing at `opening` if the code ends before the bracket it opens is closed
+    fn expect_not_end(&self, expected: &str, opening: &Token) -> Result<(), HawkError> {
+        if self.at_end() {
+            Err(HawkError::syntax(format!("Expected {expected}"), opening.span()))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Whether the current token is a statement keyword at the start of a line, so the code before it ended
+    fn at_statement(&self) -> bool {
+        Self::starts_statement(self.current().token_type) && self.current().line > self.previous().line
+    }
+
+    /// The error for a bracket that isn't closed where the current token is, pointing at the `opening` bracket
+    fn unclosed_error(&self, expected: &str, opening: &Token) -> HawkError {
+        match Self::describe(&self.current()) {
+            Ok(found) if !self.at_end() => HawkError::syntax(format!("Expected {expected}, found {found}"), opening.span()),
+            _ => HawkError::syntax(format!("Expected {expected}"), opening.span()),
+        }
+    }
+
+    /// Replaces an error inside brackets with one pointing at the `opening` bracket when it was caused by the
+    /// brackets never being closed, i.e. the code or the statement ended inside them
+    fn unclosed(&self, error: HawkError, expected: &str, opening: &Token) -> HawkError {
+        if self.at_end() || self.at_statement() {
+            self.unclosed_error(expected, opening)
+        } else {
+            error
+        }
+    }
+
     fn consume(&mut self) {
         self.index += 1;
     }
@@ -669,3 +1059,98 @@ impl Parser {
         }
     }
 }
+
+#[test]
+fn error_recovery() {
+    let errors = |source| match Parser::parse(&crate::lexer::Lexer::lex(source, &mut |_, _| ()).unwrap(), &mut |_, _| ()) {
+        Ok(_) => vec![],
+        Err(errors) => errors.into_iter().map(|error| error.line()).collect::<Vec<_>>(),
+    };
+
+    assert_eq!(errors("let x = 1\nprint x"), vec![]);
+    assert_eq!(errors("let 5 = 2\nlet = 3"), vec![1, 2]);
+    assert_eq!(errors("let = 1\nprint x\nlet 5 = 2\nx = (1 + )"), vec![1, 3, 4]);
+
+    // A mistake inside a block or a multi-line array only gives one error
+    assert_eq!(errors("if true {\n    let = 1\n    print 2\n}\nlet = 3"), vec![2, 5]);
+    assert_eq!(errors("let a = [1,\n    (2 + 3,\n    4]\nprint a"), vec![2]);
+
+    // Unclosed brackets are reported, and errors before them are kept
+    assert_eq!(errors("let = 1\nif true {\n    print 2"), vec![1, 2]);
+    assert_eq!(errors("let = 1\nlet a = [1, 2"), vec![1, 2]);
+    assert_eq!(errors("print f(1,\n    2"), vec![1]);
+
+    // Statement keywords end what is left unclosed, and the error points at the bracket that wasn't closed
+    let source = "let x = (1 +\nlet y = 3 *\nprint 5\nlet z = [1, 2\nfunction f( {\n}\nprint \"ok\"";
+    assert_eq!(errors(source), vec![1, 2, 4, 5]);
+    let parsed = Parser::parse(&crate::lexer::Lexer::lex(source, &mut |_, _| ()).unwrap(), &mut |_, _| ());
+    let spans = parsed.unwrap_err().into_iter().map(|error| (error.span.line, error.span.column)).collect::<Vec<_>>();
+    assert_eq!(spans, vec![(1, 9), (2, 11), (4, 9), (5, 11)]);
+}
+
+#[test]
+fn statement_after_condition() {
+    let parse = |source| Parser::parse(&crate::lexer::Lexer::lex(source, &mut |_, _| ()).unwrap(), &mut |_, _| ()).unwrap()
+        .into_iter().filter(|statement| !matches!(statement, Statement::Line(_))).collect::<Vec<_>>();
+
+    // An identifier after a condition starts the block, it isn't a unit
+    match &parse("if (1 > 0) s = 5")[..] {
+        [Statement::If { block, .. }, ..] => assert!(matches!(&**block, Statement::Definition { name, .. } if name == "s"), "found {block:?}"),
+        statements => panic!("expected an if statement, found {statements:?}"),
+    }
+    match &parse("while (K < 3) K = K + 1")[..] {
+        [Statement::While { block, .. }, ..] => assert!(matches!(&**block, Statement::Definition { name, .. } if name == "K"), "found {block:?}"),
+        statements => panic!("expected a while loop, found {statements:?}"),
+    }
+    match &parse("if x > 3 s = 1")[..] {
+        [Statement::If { block, .. }, ..] => assert!(matches!(&**block, Statement::Definition { name, .. } if name == "s"), "found {block:?}"),
+        statements => panic!("expected an if statement, found {statements:?}"),
+    }
+    match &parse("while i < 3 s = s + 1")[..] {
+        [Statement::While { block, .. }, ..] => assert!(matches!(&**block, Statement::Definition { name, .. } if name == "s"), "found {block:?}"),
+        statements => panic!("expected a while loop, found {statements:?}"),
+    }
+}
+
+#[test]
+fn method_calls() {
+    let parse = |source| match Parser::parse(&crate::lexer::Lexer::lex(source, &mut |_, _| ()).unwrap(), &mut |_, _| ()).unwrap()
+        .into_iter().find(|statement| matches!(statement, Statement::Print(_))) {
+        Some(Statement::Print(expression)) => expression,
+        statement => panic!("expected a print statement, found {statement:?}"),
+    };
+
+    // Any primary expression can have methods called on it, or fields taken from it
+    assert!(matches!(*parse("print \"a,b\".split(\",\")"), Expression::MethodCall { object, method, .. }
+        if method == "split" && *object == Expression::Literal(Object::String(String::from("a,b"))))
+    );
+    assert!(matches!(*parse("print [1, 2].map(f)"), Expression::MethodCall { object, .. } if matches!(*object, Expression::Array(_))));
+    match *parse("print propagate(f, x).value.round(2)") {
+        Expression::MethodCall { object, method, args } => {
+            assert_eq!(method, "round");
+            assert_eq!(args.len(), 1);
+            assert!(matches!(*object, Expression::Field { object, field } if field == "value" && matches!(*object, Expression::FunctionCall { .. })));
+        },
+        expression => panic!("expected a method call, found {expression:?}"),
+    }
+}
+
+#[test]
+fn record_literals() {
+    let parse = |source| Parser::parse(&crate::lexer::Lexer::lex(source, &mut |_, _| ()).unwrap(), &mut |_, _| ());
+
+    match parse("let r = {x: 3, y: 2 * z}").unwrap().into_iter().find(|statement| matches!(statement, Statement::Definition { .. })) {
+        Some(Statement::Definition { value, .. }) => match *value {
+            Expression::Record(fields) => {
+                let names: Vec<&str> = fields.iter().map(|(name, _)| name.as_str()).collect();
+                assert_eq!(names, vec!["x", "y"]);
+            },
+            x => panic!("expected a record, found {x:?}"),
+        },
+        _ => panic!("expected a definition"),
+    }
+
+    assert!(parse("let r = {}").is_ok());
+    assert!(parse("let r = {x 3}").is_err());
+    assert!(parse("let r = {x: 3").is_err());
+}
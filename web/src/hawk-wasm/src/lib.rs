@@ -2,6 +2,11 @@ use wasm_bindgen::prelude::*;
 
 use std::collections::HashMap;
 
+use hawk_common::environment::Environment;
+use hawk_common::error::HawkError;
+use hawk_common::uncertainty::Propagation;
+use hawk_interpreter::monte_carlo::MonteCarlo;
+
 #[wasm_bindgen(module = "/hawk_interface.ts")]
 extern "C" {
     fn print(message: &str);
@@ -13,51 +18,68 @@ extern "C" {
 
 #[wasm_bindgen]
 pub struct Runner{
-    state: HashMap<String, hawk_common::object::Object>
+    state: HashMap<String, hawk_common::object::Object>,
+    propagation: Propagation,
+    monte_carlo: MonteCarlo,
 }
 
 #[wasm_bindgen]
 impl Runner {
     pub fn new() -> Runner {
-        Runner { state: HashMap::new() }
+        Runner { state: HashMap::new(), propagation: Propagation::default(), monte_carlo: MonteCarlo::default() }
     }
 
     pub fn clear(&mut self) {
-        self.state = HashMap::new()
+        self.state = HashMap::new();
+        self.propagation = Propagation::default();
+        self.monte_carlo = MonteCarlo::default()
     }
 
     pub fn run(&mut self, code: &str, in_repl: bool) {
-        let filein_fn = |name: String| {
-            Ok(readfile(&name))
-        };
-
-        let fileout_fn = |name: String, content: String| {
-            writefile(&name, &content);
-            Ok(())
-        };
-
-        let warn_fn = |message: String, line: usize| {
-            if line == 0 {
-                warn(&format!("Warning: {message}"));
-            } else {
-                warn(&format!("Warning on line {line}: {message}"));
-            }
-        };
-
-        let err_fn = |message: String, line: usize| {
-            if line == 0 {
-                error(&format!("Error: {message}"));
-            } else {
-                error(&format!("Error on line {line}: {message}"));
-            }
-        };
-
-        let output_fn = |message: String| print(&message);
-
-
-        match hawk_interpreter::run::run(code.to_string(), self.state.clone(), in_repl, filein_fn, fileout_fn, warn_fn, err_fn, output_fn) {
-            Ok(state) => self.state = state,
-            Err((message, line)) => err_fn(message, line)
+        let mut browser = Browser;
+        let mut interpreter = hawk_interpreter::run::Builder::new()
+            .repl(in_repl)
+            .propagation(self.propagation)
+            .monte_carlo(self.monte_carlo.clone())
+            .interpreter(self.state.clone(), &mut browser);
+
+        match interpreter.run_source(code) {
+            Ok(()) => {
+                self.state = interpreter.globals;
+                self.propagation = interpreter.propagation;
+                self.monte_carlo = interpreter.monte_carlo
+            },
+            Err(hawk_errors) => hawk_errors.into_iter().for_each(|hawk_error| Browser.error(hawk_error))
         }
     }
+}
+
+/// Runs Hawk in the browser, using the functions in `hawk_interface.ts` to show output and store files
+struct Browser;
+
+impl Environment for Browser {
+    fn read_file(&mut self, name: &str) -> Result<String, String> {
+        Ok(readfile(name))
+    }
+
+    fn write_file(&mut self, name: &str, contents: &str) -> Result<(), String> {
+        writefile(name, contents);
+        Ok(())
+    }
+
+    fn output(&mut self, text: String) {
+        print(&text)
+    }
+
+    fn warn(&mut self, message: String, line: usize) {
+        if line == 0 {
+            warn(&format!("Warning: {message}"));
+        } else {
+            warn(&format!("Warning on line {line}: {message}"));
+        }
+    }
+
+    fn error(&mut self, hawk_error: HawkError) {
+        error(&format!("{hawk_error}\n{}", hawk_error.traceback()))
+    }
 }
\ No newline at end of file